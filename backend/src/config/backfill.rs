@@ -0,0 +1,58 @@
+// backend/src/config/backfill.rs
+use serde::Deserialize;
+
+use super::{get_env, get_env_parsed};
+
+// getSignaturesForAddress returns at most this many signatures per call
+const MAX_PAGE_SIZE: usize = 1000;
+
+// Step 1: Historical backfill configuration structure
+#[derive(Debug, Deserialize, Clone)]
+pub struct BackfillConfig {
+    pub enabled: bool,
+    pub cursor_path: String,
+    pub wallet: Option<String>, // Also backfill transactions mentioning this wallet
+    pub page_size: usize,
+    pub max_transactions: usize, // Per address, bounds the first run without a cursor
+}
+
+impl BackfillConfig {
+    // Step 2: Load backfill configuration from environment
+    pub fn load() -> Self {
+        let wallet = get_env("BACKFILL_WALLET");
+
+        Self {
+            enabled: get_env_parsed("BACKFILL_ENABLED", false),
+            cursor_path: get_env("BACKFILL_CURSOR_PATH"),
+            wallet: (!wallet.is_empty()).then_some(wallet),
+            page_size: get_env_parsed("BACKFILL_PAGE_SIZE", MAX_PAGE_SIZE),
+            max_transactions: get_env_parsed("BACKFILL_MAX_TRANSACTIONS", 10_000),
+        }
+    }
+
+    // Step 3: Validate backfill configuration
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.enabled && self.cursor_path.is_empty() {
+            errors.push("BACKFILL_CURSOR_PATH is required when backfill is enabled".to_string());
+        }
+
+        if self.page_size == 0 || self.page_size > MAX_PAGE_SIZE {
+            errors.push(format!(
+                "BACKFILL_PAGE_SIZE must be between 1 and {}",
+                MAX_PAGE_SIZE
+            ));
+        }
+
+        if self.max_transactions == 0 {
+            errors.push("BACKFILL_MAX_TRANSACTIONS cannot be 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
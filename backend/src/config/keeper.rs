@@ -0,0 +1,47 @@
+// backend/src/config/keeper.rs
+use serde::Deserialize;
+
+use super::{get_env, get_env_parsed};
+
+// Step 1: Trigger-order keeper configuration structure
+#[derive(Debug, Deserialize, Clone)]
+pub struct KeeperConfig {
+    pub enabled: bool,
+    pub keypair_path: String,
+    pub poll_interval_secs: u64,
+}
+
+impl KeeperConfig {
+    // Step 2: Load keeper configuration from environment
+    pub fn load() -> Self {
+        Self {
+            enabled: get_env_parsed("KEEPER_ENABLED", false),
+            keypair_path: get_env("KEEPER_KEYPAIR_PATH"),
+            poll_interval_secs: get_env_parsed("KEEPER_POLL_INTERVAL_SECS", 10),
+        }
+    }
+
+    // Step 3: Validate keeper configuration
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.enabled && self.keypair_path.is_empty() {
+            errors.push("KEEPER_KEYPAIR_PATH is required when the keeper is enabled".to_string());
+        }
+
+        if self.poll_interval_secs == 0 {
+            errors.push("KEEPER_POLL_INTERVAL_SECS cannot be 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Step 4: Get poll interval as Duration
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.poll_interval_secs)
+    }
+}
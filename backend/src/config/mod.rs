@@ -4,16 +4,24 @@ use std::env;
 
 // Step 1: Re-export all config structures
 pub use ai::AIConfig;
+pub use backfill::BackfillConfig;
 pub use database::DatabaseConfig;
-pub use pipeline::PipelineConfig;
+pub use keeper::KeeperConfig;
+pub use pipeline::{OverflowPolicy, PipelineConfig};
+pub use price_feed::PriceFeedConfig;
+pub use replay::{ReplayConfig, ReplaySpeed};
 pub use risk::RiskConfig;
 pub use server::ServerConfig;
 pub use solana::SolanaConfig;
 
 // Step 2: Import sub-modules
 mod ai;
+mod backfill;
 mod database;
+mod keeper;
 mod pipeline;
+mod price_feed;
+mod replay;
 mod risk;
 mod server;
 mod solana;
@@ -27,6 +35,10 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub pipeline: PipelineConfig,
     pub risk: RiskConfig,
+    pub keeper: KeeperConfig,
+    pub backfill: BackfillConfig,
+    pub replay: ReplayConfig,
+    pub price_feed: PriceFeedConfig,
 }
 
 impl Config {
@@ -44,6 +56,10 @@ impl Config {
             database: DatabaseConfig::load(),
             pipeline: PipelineConfig::load(),
             risk: RiskConfig::load(),
+            keeper: KeeperConfig::load(),
+            backfill: BackfillConfig::load(),
+            replay: ReplayConfig::load(),
+            price_feed: PriceFeedConfig::load(),
         }
     }
 
@@ -66,6 +82,36 @@ impl Config {
             errors.extend(ai_errors);
         }
 
+        // Validate pipeline config
+        if let Err(pipeline_errors) = self.pipeline.validate() {
+            errors.extend(pipeline_errors);
+        }
+
+        // Validate risk config
+        if let Err(risk_errors) = self.risk.validate() {
+            errors.extend(risk_errors);
+        }
+
+        // Validate keeper config
+        if let Err(keeper_errors) = self.keeper.validate() {
+            errors.extend(keeper_errors);
+        }
+
+        // Validate backfill config
+        if let Err(backfill_errors) = self.backfill.validate() {
+            errors.extend(backfill_errors);
+        }
+
+        // Validate replay config
+        if let Err(replay_errors) = self.replay.validate() {
+            errors.extend(replay_errors);
+        }
+
+        // Validate price feed config
+        if let Err(price_feed_errors) = self.price_feed.validate() {
+            errors.extend(price_feed_errors);
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -97,8 +143,12 @@ pub fn get_env(key: &str) -> String {
             "CORS_ORIGIN" => "*".to_string(),
             "SOLANA_RPC_URL" => "https://api.devnet.solana.com".to_string(),
             "SOLANA_WS_URL" => "wss://api.devnet.solana.com".to_string(),
-            "SOLANA_PROGRAM_ID" => "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS".to_string(),
+            "SOLANA_PROGRAM_ID" => "4xJGvDE2b5k9qCfSBXrT1a7HYPRxh2AvKHiQA9hm2ryS".to_string(),
             "SOLANA_COMMITMENT" => "confirmed".to_string(),
+            "SOLANA_WS_ENABLED" => "false".to_string(),
+            "ACCOUNT_WS_ENABLED" => "false".to_string(),
+            "AMM_POOL_PROGRAM_ID" => "2YsibxDCmwrkAVVwbdkpN64RNVhnFjvJK5HSaZkdvZta".to_string(),
+            "PORTFOLIO_PROGRAM_ID" => "4xJGvDE2b5k9qCfSBXrT1a7HYPRxh2AvKHiQA9hm2ryS".to_string(),
             "AI_SERVICE_URL" => "http://localhost:8001".to_string(),
             "AI_SERVICE_TIMEOUT" => "30".to_string(),
             "AI_SERVICE_ENABLED" => "true".to_string(),
@@ -109,9 +159,40 @@ pub fn get_env(key: &str) -> String {
             "BATCH_SIZE" => "10".to_string(),
             "BATCH_TIMEOUT_MS" => "100".to_string(),
             "MAX_QUEUE_SIZE" => "1000".to_string(),
+            "FINALITY_TRACKING_ENABLED" => "false".to_string(),
+            "FINALITY_POLL_INTERVAL_MS" => "2000".to_string(),
+            "DEAD_LETTER_PATH" => "dead_letters.json".to_string(),
+            "DEAD_LETTER_MAX_ENTRIES" => "10000".to_string(),
+            "PIPELINE_DRAIN_TIMEOUT_MS" => "10000".to_string(),
+            "WORKER_SHARDS" => "4".to_string(),
+            "QUEUE_OVERFLOW_POLICY" => "block".to_string(),
+            "QUEUE_SEND_TIMEOUT_MS" => "5000".to_string(),
+            "QUEUE_SPILL_PATH" => "queue_spill.jsonl".to_string(),
             "ALERT_COOLDOWN_MINUTES" => "60".to_string(),
             "ALERT_RETENTION_DAYS" => "30".to_string(),
+            "ALERTS_PATH" => "alerts.json".to_string(),
+            "ALERT_MAX_ENTRIES" => "10000".to_string(),
             "MIN_CONFIDENCE" => "0.7".to_string(),
+            "RULES_PATH" => "rules.toml".to_string(),
+            "RULE_STATE_PATH" => "rule_state.json".to_string(),
+            "RULE_STATE_SNAPSHOT_SECS" => "60".to_string(),
+            "RULE_STATE_MAX_WALLETS" => "50000".to_string(),
+            "KEEPER_ENABLED" => "false".to_string(),
+            "KEEPER_POLL_INTERVAL_SECS" => "10".to_string(),
+            "BACKFILL_ENABLED" => "false".to_string(),
+            "BACKFILL_CURSOR_PATH" => "backfill_cursor.json".to_string(),
+            "BACKFILL_PAGE_SIZE" => "1000".to_string(),
+            "BACKFILL_MAX_TRANSACTIONS" => "10000".to_string(),
+            "REPLAY_ENABLED" => "false".to_string(),
+            "REPLAY_SPEED" => "original".to_string(),
+            "PRICE_FEED_ENABLED" => "false".to_string(),
+            "PRICE_FEED_URL" => {
+                "https://api.coingecko.com/api/v3/simple/token_price/solana".to_string()
+            }
+            "PRICE_FEED_MINTS" => {
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v,Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB,So11111111111111111111111111111111111111112".to_string()
+            }
+            "PRICE_FEED_POLL_INTERVAL_SECS" => "30".to_string(),
             _ => "".to_string(),
         }
     })
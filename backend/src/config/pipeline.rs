@@ -1,27 +1,69 @@
 // backend/src/config/pipeline.rs
 use serde::Deserialize;
 
-use super::get_env_parsed;
+use super::{get_env, get_env_parsed};
 
-// Step 1: Pipeline configuration structure
+// Step 1: What the event queue does when a lane is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    Block,       // Wait for room, up to QUEUE_SEND_TIMEOUT_MS
+    DropOldest,  // Evict the oldest queued event
+    DropNewest,  // Reject the incoming event
+    SpillToDisk, // Append to QUEUE_SPILL_PATH and read back later
+}
+
+impl OverflowPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.trim().to_lowercase().replace('-', "_").as_str() {
+            "block" => Some(Self::Block),
+            "drop_oldest" => Some(Self::DropOldest),
+            "drop_newest" => Some(Self::DropNewest),
+            "spill" | "spill_to_disk" => Some(Self::SpillToDisk),
+            _ => None,
+        }
+    }
+}
+
+// Step 2: Pipeline configuration structure
 #[derive(Debug, Deserialize, Clone)]
 pub struct PipelineConfig {
     pub batch_size: usize,
     pub batch_timeout_ms: u64,
     pub max_queue_size: usize,
+    pub finality_enabled: bool, // Reconcile live events once their slot finalizes
+    pub finality_poll_interval_ms: u64,
+    pub dead_letter_path: Option<String>, // In-memory only when unset
+    pub dead_letter_max_entries: usize,
+    pub drain_timeout_ms: u64, // How long shutdown waits for buffered events
+    pub worker_shards: usize,  // Batcher workers; a wallet always lands on the same one
+    pub queue_overflow_policy: String,
+    pub queue_send_timeout_ms: u64,
+    pub queue_spill_path: Option<String>, // Required by the spill policy
 }
 
 impl PipelineConfig {
-    // Step 2: Load pipeline configuration from environment
+    // Step 3: Load pipeline configuration from environment
     pub fn load() -> Self {
+        let dead_letter_path = get_env("DEAD_LETTER_PATH");
+        let queue_spill_path = get_env("QUEUE_SPILL_PATH");
+
         Self {
             batch_size: get_env_parsed("BATCH_SIZE", 10),
             batch_timeout_ms: get_env_parsed("BATCH_TIMEOUT_MS", 100),
             max_queue_size: get_env_parsed("MAX_QUEUE_SIZE", 1000),
+            finality_enabled: get_env_parsed("FINALITY_TRACKING_ENABLED", false),
+            finality_poll_interval_ms: get_env_parsed("FINALITY_POLL_INTERVAL_MS", 2000),
+            dead_letter_path: (!dead_letter_path.is_empty()).then_some(dead_letter_path),
+            dead_letter_max_entries: get_env_parsed("DEAD_LETTER_MAX_ENTRIES", 10_000),
+            drain_timeout_ms: get_env_parsed("PIPELINE_DRAIN_TIMEOUT_MS", 10_000),
+            worker_shards: get_env_parsed("WORKER_SHARDS", 4),
+            queue_overflow_policy: get_env("QUEUE_OVERFLOW_POLICY"),
+            queue_send_timeout_ms: get_env_parsed("QUEUE_SEND_TIMEOUT_MS", 5000),
+            queue_spill_path: (!queue_spill_path.is_empty()).then_some(queue_spill_path),
         }
     }
 
-    // Step 3: Validate pipeline configuration
+    // Step 4: Validate pipeline configuration
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
@@ -49,6 +91,45 @@ impl PipelineConfig {
             errors.push("MAX_QUEUE_SIZE cannot exceed 100000".to_string());
         }
 
+        if self.finality_poll_interval_ms == 0 {
+            errors.push("FINALITY_POLL_INTERVAL_MS cannot be 0".to_string());
+        }
+
+        if self.finality_poll_interval_ms > 60000 {
+            errors.push("FINALITY_POLL_INTERVAL_MS cannot exceed 60000 (1 minute)".to_string());
+        }
+
+        if self.dead_letter_max_entries == 0 {
+            errors.push("DEAD_LETTER_MAX_ENTRIES cannot be 0".to_string());
+        }
+
+        if self.drain_timeout_ms == 0 {
+            errors.push("PIPELINE_DRAIN_TIMEOUT_MS cannot be 0".to_string());
+        }
+
+        if self.worker_shards == 0 {
+            errors.push("WORKER_SHARDS cannot be 0".to_string());
+        }
+
+        if self.worker_shards > 256 {
+            errors.push("WORKER_SHARDS cannot exceed 256".to_string());
+        }
+
+        match self.overflow_policy() {
+            None => errors.push(format!(
+                "QUEUE_OVERFLOW_POLICY must be 'block', 'drop_oldest', 'drop_newest' or 'spill', got '{}'",
+                self.queue_overflow_policy
+            )),
+            Some(OverflowPolicy::SpillToDisk) if self.queue_spill_path.is_none() => {
+                errors.push("QUEUE_SPILL_PATH is required by the spill policy".to_string())
+            }
+            Some(_) => {}
+        }
+
+        if self.queue_send_timeout_ms == 0 {
+            errors.push("QUEUE_SEND_TIMEOUT_MS cannot be 0".to_string());
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -56,12 +137,31 @@ impl PipelineConfig {
         }
     }
 
-    // Step 4: Get batch timeout as Duration
+    // Step 5: Get batch timeout as Duration
     pub fn batch_timeout_duration(&self) -> std::time::Duration {
         std::time::Duration::from_millis(self.batch_timeout_ms)
     }
 
-    // Step 5: Check if batching is enabled
+    // Step 6: Get finality poll interval as Duration
+    pub fn finality_poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.finality_poll_interval_ms)
+    }
+
+    // Step 7: Get shutdown drain timeout as Duration
+    pub fn drain_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.drain_timeout_ms)
+    }
+
+    // Step 8: Parsed overflow policy and its send timeout
+    pub fn overflow_policy(&self) -> Option<OverflowPolicy> {
+        OverflowPolicy::parse(&self.queue_overflow_policy)
+    }
+
+    pub fn queue_send_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.queue_send_timeout_ms)
+    }
+
+    // Step 9: Check if batching is enabled
     pub fn is_batching_enabled(&self) -> bool {
         self.batch_size > 1
     }
@@ -0,0 +1,57 @@
+// backend/src/config/price_feed.rs
+use serde::Deserialize;
+
+use super::{get_env, get_env_parsed};
+
+// Step 1: Price feed configuration structure
+#[derive(Debug, Deserialize, Clone)]
+pub struct PriceFeedConfig {
+    pub enabled: bool,
+    pub url: String,        // Coingecko-compatible `simple/token_price` endpoint
+    pub mints: Vec<String>, // Mints to poll, comma-separated in PRICE_FEED_MINTS
+    pub poll_interval_secs: u64,
+}
+
+impl PriceFeedConfig {
+    // Step 2: Load price feed configuration from environment
+    pub fn load() -> Self {
+        Self {
+            enabled: get_env_parsed("PRICE_FEED_ENABLED", false),
+            url: get_env("PRICE_FEED_URL"),
+            mints: get_env("PRICE_FEED_MINTS")
+                .split(',')
+                .map(|mint| mint.trim().to_string())
+                .filter(|mint| !mint.is_empty())
+                .collect(),
+            poll_interval_secs: get_env_parsed("PRICE_FEED_POLL_INTERVAL_SECS", 30),
+        }
+    }
+
+    // Step 3: Validate price feed configuration
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.enabled && self.url.is_empty() {
+            errors.push("PRICE_FEED_URL is required when the price feed is enabled".to_string());
+        }
+
+        if self.enabled && self.mints.is_empty() {
+            errors.push("PRICE_FEED_MINTS is required when the price feed is enabled".to_string());
+        }
+
+        if self.poll_interval_secs == 0 {
+            errors.push("PRICE_FEED_POLL_INTERVAL_SECS cannot be 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Step 4: Get poll interval as Duration
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.poll_interval_secs)
+    }
+}
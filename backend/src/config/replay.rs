@@ -0,0 +1,82 @@
+// backend/src/config/replay.rs
+use serde::Deserialize;
+
+use super::{get_env, get_env_parsed};
+
+// Step 1: How fast recorded events are fed back into the pipeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    Original,         // Keep the recorded gaps between events
+    Accelerated(f64), // Divide the recorded gaps by this factor
+    Max,              // No delay at all
+}
+
+impl ReplaySpeed {
+    // Accepts `original`, `max`, or a factor such as `10` / `10x`
+    pub fn parse(speed: &str) -> Option<Self> {
+        match speed.trim().to_lowercase().as_str() {
+            "original" | "1x" => Some(Self::Original),
+            "max" => Some(Self::Max),
+            other => other
+                .trim_end_matches('x')
+                .parse::<f64>()
+                .ok()
+                .filter(|factor| factor.is_finite() && *factor > 0.0)
+                .map(Self::Accelerated),
+        }
+    }
+}
+
+// Step 2: Replay/record configuration structure
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReplayConfig {
+    pub enabled: bool,
+    pub path: String,
+    pub speed: String,
+    pub record_path: Option<String>, // Append live notifications here when set
+}
+
+impl ReplayConfig {
+    // Step 3: Load replay configuration from environment
+    pub fn load() -> Self {
+        let record_path = get_env("RECORD_PATH");
+
+        Self {
+            enabled: get_env_parsed("REPLAY_ENABLED", false),
+            path: get_env("REPLAY_PATH"),
+            speed: get_env("REPLAY_SPEED"),
+            record_path: (!record_path.is_empty()).then_some(record_path),
+        }
+    }
+
+    // Step 4: Validate replay configuration
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.enabled && self.path.is_empty() {
+            errors.push("REPLAY_PATH is required when replay is enabled".to_string());
+        }
+
+        if self.speed().is_none() {
+            errors.push(format!(
+                "REPLAY_SPEED must be 'original', 'max' or a positive factor such as '10x', got '{}'",
+                self.speed
+            ));
+        }
+
+        if self.enabled && self.record_path.as_deref() == Some(self.path.as_str()) {
+            errors.push("RECORD_PATH cannot be the file being replayed".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Step 5: Parsed replay speed
+    pub fn speed(&self) -> Option<ReplaySpeed> {
+        ReplaySpeed::parse(&self.speed)
+    }
+}
@@ -1,14 +1,20 @@
 // backend/src/config/risk.rs
 use serde::Deserialize;
 
-use super::get_env_parsed;
+use super::{get_env, get_env_parsed};
 
 // Step 1: Risk engine configuration structure
 #[derive(Debug, Deserialize, Clone)]
 pub struct RiskConfig {
     pub cooldown_minutes: u64,
     pub alert_retention_days: u32,
+    pub alerts_path: Option<String>, // Raised alerts and their status; in-memory only when unset
+    pub alert_max_entries: usize,
     pub min_confidence: f64,
+    pub rules_path: Option<String>, // TOML or YAML rule definitions; built-in rules when unset
+    pub state_path: Option<String>, // Snapshot of the per-wallet rule windows
+    pub state_snapshot_secs: u64,
+    pub state_max_wallets: usize,
 }
 
 impl RiskConfig {
@@ -17,7 +23,13 @@ impl RiskConfig {
         Self {
             cooldown_minutes: get_env_parsed("ALERT_COOLDOWN_MINUTES", 60),
             alert_retention_days: get_env_parsed("ALERT_RETENTION_DAYS", 30),
+            alerts_path: Some(get_env("ALERTS_PATH")).filter(|path| !path.is_empty()),
+            alert_max_entries: get_env_parsed("ALERT_MAX_ENTRIES", 10_000),
             min_confidence: get_env_parsed("MIN_CONFIDENCE", 0.7),
+            rules_path: Some(get_env("RULES_PATH")).filter(|path| !path.is_empty()),
+            state_path: Some(get_env("RULE_STATE_PATH")).filter(|path| !path.is_empty()),
+            state_snapshot_secs: get_env_parsed("RULE_STATE_SNAPSHOT_SECS", 60),
+            state_max_wallets: get_env_parsed("RULE_STATE_MAX_WALLETS", 50_000),
         }
     }
 
@@ -33,10 +45,34 @@ impl RiskConfig {
             errors.push("ALERT_RETENTION_DAYS cannot be 0".to_string());
         }
 
+        if self.alert_max_entries == 0 {
+            errors.push("ALERT_MAX_ENTRIES cannot be 0".to_string());
+        }
+
         if self.min_confidence < 0.0 || self.min_confidence > 1.0 {
             errors.push("MIN_CONFIDENCE must be between 0.0 and 1.0".to_string());
         }
 
+        if let Some(path) = &self.rules_path {
+            let extension = std::path::Path::new(path)
+                .extension()
+                .and_then(|extension| extension.to_str());
+            if !matches!(extension, Some("toml" | "yaml" | "yml")) {
+                errors.push(format!(
+                    "RULES_PATH must be a .toml, .yaml or .yml file: {}",
+                    path
+                ));
+            }
+        }
+
+        if self.state_snapshot_secs == 0 {
+            errors.push("RULE_STATE_SNAPSHOT_SECS cannot be 0".to_string());
+        }
+
+        if self.state_max_wallets == 0 {
+            errors.push("RULE_STATE_MAX_WALLETS cannot be 0".to_string());
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -49,7 +85,17 @@ impl RiskConfig {
         std::time::Duration::from_secs(self.cooldown_minutes * 60)
     }
 
-    // Step 5: Check if confidence threshold is high
+    // Step 5: Get alert retention as Duration
+    pub fn alert_retention(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(u64::from(self.alert_retention_days) * 86_400)
+    }
+
+    // Step 6: Get rule state snapshot interval as Duration
+    pub fn state_snapshot_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.state_snapshot_secs)
+    }
+
+    // Step 7: Check if confidence threshold is high
     pub fn is_high_confidence(&self) -> bool {
         self.min_confidence > 0.8
     }
@@ -1,6 +1,8 @@
 // backend/src/config/server.rs
 use serde::Deserialize;
 
+use super::get_env_parsed;
+
 /// Server configuration
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerConfig {
@@ -12,6 +14,10 @@ pub struct ServerConfig {
     pub log_level: String,
     /// CORS origin(s). Comma-separated; "*" allowed for quick demos.
     pub cors_origin: String,
+    /// Bearer token for /api/admin routes. Unset: admin routes are closed.
+    pub admin_token: Option<String>,
+    /// Opt-in for local development: open token-guarded admin routes while ADMIN_TOKEN is unset.
+    pub admin_open_without_token: bool,
 }
 
 impl ServerConfig {
@@ -24,12 +30,18 @@ impl ServerConfig {
             .unwrap_or(3000);
         let log_level = std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
         let cors_origin = std::env::var("CORS_ORIGIN").unwrap_or_else(|_| "*".to_string());
+        let admin_token = std::env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+        let admin_open_without_token = get_env_parsed("ADMIN_OPEN_WITHOUT_TOKEN", false);
 
         Self {
             host,
             port,
             log_level,
             cors_origin,
+            admin_token,
+            admin_open_without_token,
         }
     }
 
@@ -37,7 +49,7 @@ impl ServerConfig {
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
-        if self.port == 0 || self.port > 65535 {
+        if self.port == 0 {
             errors.push("PORT must be between 1 and 65535".to_string());
         }
 
@@ -1,9 +1,10 @@
-
 use serde::Deserialize;
 use solana_commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
 
-
-use super::get_env;
+use super::{get_env, get_env_parsed};
+use crate::{BackendError, BackendResult};
 
 // Step 1: Solana configuration structure
 #[derive(Debug, Deserialize, Clone)]
@@ -11,7 +12,13 @@ pub struct SolanaConfig {
     pub rpc_url: String,
     pub ws_url: String,
     pub program_id: String,
+    pub amm_pool_program_id: String,
+    pub portfolio_program_id: String,
+    pub extra_program_ids: Vec<String>, // Third-party programs to ingest logs from
+    pub idl_dir: Option<String>,        // Anchor IDLs (+ mappings.json) for third-party programs
     pub commitment: String,
+    pub logs_ws_enabled: bool,     // Stream program logs into the pipeline
+    pub accounts_ws_enabled: bool, // Stream PoolState/Position account changes
 }
 
 impl SolanaConfig {
@@ -21,7 +28,17 @@ impl SolanaConfig {
             rpc_url: get_env("SOLANA_RPC_URL"),
             ws_url: get_env("SOLANA_WS_URL"),
             program_id: get_env("SOLANA_PROGRAM_ID"),
+            amm_pool_program_id: get_env("AMM_POOL_PROGRAM_ID"),
+            portfolio_program_id: get_env("PORTFOLIO_PROGRAM_ID"),
+            extra_program_ids: get_env("SOLANA_EXTRA_PROGRAM_IDS")
+                .split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect(),
             commitment: get_env("SOLANA_COMMITMENT"),
+            idl_dir: Some(get_env("IDL_DIR")).filter(|dir| !dir.is_empty()),
+            logs_ws_enabled: get_env_parsed("SOLANA_WS_ENABLED", false),
+            accounts_ws_enabled: get_env_parsed("ACCOUNT_WS_ENABLED", false),
         }
     }
 
@@ -49,6 +66,29 @@ impl SolanaConfig {
             errors.push("SOLANA_PROGRAM_ID is required".to_string());
         }
 
+        if self.amm_pool_program_id.is_empty() {
+            errors.push("AMM_POOL_PROGRAM_ID is required".to_string());
+        }
+
+        if self.portfolio_program_id.is_empty() {
+            errors.push("PORTFOLIO_PROGRAM_ID is required".to_string());
+        }
+
+        for program_id in &self.extra_program_ids {
+            if Pubkey::from_str(program_id).is_err() {
+                errors.push(format!(
+                    "SOLANA_EXTRA_PROGRAM_IDS contains an invalid program ID: {}",
+                    program_id
+                ));
+            }
+        }
+
+        if let Some(dir) = &self.idl_dir {
+            if !std::path::Path::new(dir).is_dir() {
+                errors.push(format!("IDL_DIR is not a directory: {}", dir));
+            }
+        }
+
         let valid_commitments = ["processed", "confirmed", "finalized"];
         if !valid_commitments.contains(&self.commitment.to_lowercase().as_str()) {
             errors.push(format!(
@@ -74,18 +114,46 @@ impl SolanaConfig {
         }
     }
 
-    // Step 5: Check if using mainnet
+    // Step 5: Programs whose logs are ingested, without duplicates
+    pub fn subscribed_program_ids(&self) -> Vec<String> {
+        let mut program_ids = vec![
+            self.portfolio_program_id.clone(),
+            self.amm_pool_program_id.clone(),
+        ];
+        for program_id in &self.extra_program_ids {
+            if !program_ids.contains(program_id) {
+                program_ids.push(program_id.clone());
+            }
+        }
+        program_ids
+    }
+
+    // Step 6: Check if using mainnet
     pub fn is_mainnet(&self) -> bool {
         self.rpc_url.contains("mainnet")
     }
 
-    // Step 6: Check if using devnet
+    // Step 7: Check if using devnet
     pub fn is_devnet(&self) -> bool {
         self.rpc_url.contains("devnet")
     }
 
-    // Step 7: Check if using testnet
+    // Step 8: Check if using testnet
     pub fn is_testnet(&self) -> bool {
         self.rpc_url.contains("testnet")
     }
-}
\ No newline at end of file
+
+    // Step 9: Parsed program IDs, for deriving PDAs and building instructions
+    pub fn amm_pool_program_pubkey(&self) -> BackendResult<Pubkey> {
+        parse_program_id(&self.amm_pool_program_id)
+    }
+
+    pub fn portfolio_program_pubkey(&self) -> BackendResult<Pubkey> {
+        parse_program_id(&self.portfolio_program_id)
+    }
+}
+
+fn parse_program_id(program_id: &str) -> BackendResult<Pubkey> {
+    Pubkey::from_str(program_id)
+        .map_err(|e| BackendError::ConfigError(format!("Invalid program ID {}: {}", program_id, e)))
+}
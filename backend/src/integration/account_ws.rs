@@ -0,0 +1,564 @@
+// backend/src/ingestion/account_ws.rs
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as base64_standard;
+use base64::Engine as _;
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio_tungstenite::{
+    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
+};
+
+use crate::config::SolanaConfig;
+use crate::integration::program_accounts::{
+    anchor_discriminator, PoolStateAccount, PositionAccount,
+};
+use crate::integration::solana_ws::reconnect_delay;
+use crate::models::event::{ChainSource, Commitment, PortfolioEvent};
+use crate::pipeline::mpsc_queue::{EventQueue, QueueError};
+use crate::services::solana_client::SolanaClient;
+use crate::BackendResult;
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+// SPL token account layout: mint (32) + owner (32) + amount (8) + ...
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+// SPL mint layout: mint_authority (4+32) + supply (8) + ...
+const MINT_SUPPLY_OFFSET: usize = 36;
+
+// Step 1: What each subscription streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VaultSide {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone)]
+enum SubscriptionTarget {
+    Pools,
+    Positions,
+    Vault { pool: String, side: VaultSide },
+    LpMint { pool: String },
+}
+
+impl SubscriptionTarget {
+    fn unsubscribe_method(&self) -> &'static str {
+        match self {
+            Self::Pools | Self::Positions => "programUnsubscribe",
+            Self::Vault { .. } | Self::LpMint { .. } => "accountUnsubscribe",
+        }
+    }
+}
+
+// Step 2: Latest known state of a pool, its vault balances and LP token supply
+struct TrackedPool {
+    state: PoolStateAccount,
+    reserve_a: Option<u64>,
+    reserve_b: Option<u64>,
+    lp_supply: Option<u64>, // LP mint supply, which PoolState.total_liquidity does not track
+    vaults_subscribed: bool,
+}
+
+// Subscriptions and pools are rebuilt on every connection
+#[derive(Default)]
+struct ConnectionState {
+    next_request_id: u64,
+    pending: HashMap<u64, SubscriptionTarget>, // Request ID -> target
+    subscriptions: HashMap<u64, SubscriptionTarget>, // Subscription ID -> target
+    pools: HashMap<String, TrackedPool>,
+}
+
+// Step 3: Streams PoolState and Position account changes into the pipeline
+pub struct AccountSubscriber {
+    event_tx: EventQueue,
+    solana_client: SolanaClient,
+    ws_url: String,
+    commitment: String,
+    amm_pool_program_id: Pubkey,
+    portfolio_program_id: Pubkey,
+    shutdown: Arc<Notify>,
+}
+
+impl AccountSubscriber {
+    pub fn new(
+        config: &SolanaConfig,
+        solana_client: SolanaClient,
+        event_tx: EventQueue,
+    ) -> BackendResult<Self> {
+        Ok(Self {
+            event_tx,
+            solana_client,
+            ws_url: config.ws_url.clone(),
+            commitment: config.commitment.to_lowercase(),
+            amm_pool_program_id: config.amm_pool_program_pubkey()?,
+            portfolio_program_id: config.portfolio_program_pubkey()?,
+            shutdown: Arc::new(Notify::new()),
+        })
+    }
+
+    // Step 4: Stream account changes until `stop` is called
+    pub async fn start(&self) {
+        tracing::info!("🌐 Starting account-change ingestion");
+
+        let mut attempt: u32 = 0;
+        loop {
+            match self.connect().await {
+                Ok(true) => break,
+                Ok(false) => {
+                    // The connection was healthy, so start backing off from scratch
+                    attempt = 0;
+                    tracing::warn!("⚠️ Account WebSocket closed, reconnecting...");
+                }
+                Err(e) => tracing::error!("❌ Account WebSocket error: {}, reconnecting...", e),
+            }
+
+            attempt = attempt.saturating_add(1);
+            let delay = reconnect_delay(attempt);
+            tracing::debug!("⏳ Account reconnect attempt {} in {:?}", attempt, delay);
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = self.shutdown.notified() => break,
+            }
+        }
+
+        tracing::info!("🛑 Account WebSocket stopped");
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    // Step 5: Subscribe, seed current state, then process notifications
+    async fn connect(&self) -> Result<bool, ThreadSafeError> {
+        let (ws_stream, _) = connect_async(&self.ws_url).await?;
+        let (mut sink, mut stream) = ws_stream.split();
+        let mut state = ConnectionState::default();
+        tracing::info!("✅ Account WebSocket connected");
+
+        // Subscribe before seeding so no change between the two is missed
+        self.subscribe_program(
+            &mut sink,
+            &mut state,
+            self.amm_pool_program_id,
+            "PoolState",
+            SubscriptionTarget::Pools,
+        )
+        .await?;
+        self.subscribe_program(
+            &mut sink,
+            &mut state,
+            self.portfolio_program_id,
+            "Position",
+            SubscriptionTarget::Positions,
+        )
+        .await?;
+        self.seed(&mut sink, &mut state).await?;
+
+        loop {
+            tokio::select! {
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(e) = self.handle_message(&mut sink, &mut state, &text).await {
+                            tracing::error!("Error handling account message: {}", e);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("Account WebSocket error: {}", e);
+                        return Ok(false);
+                    }
+                    None => return Ok(false),
+                    _ => {}
+                },
+                _ = self.shutdown.notified() => break,
+            }
+        }
+
+        // Step 6: Clean unsubscribe before closing
+        for (subscription_id, target) in state.subscriptions.drain() {
+            let request_id = state.next_request_id;
+            state.next_request_id += 1;
+            let message = json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "method": target.unsubscribe_method(),
+                "params": [subscription_id]
+            });
+            sink.send(Message::Text(message.to_string())).await?;
+        }
+        sink.close().await?;
+
+        Ok(true)
+    }
+
+    // Step 7: Emit current pools and positions once per connection
+    async fn seed(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+    ) -> Result<(), ThreadSafeError> {
+        let slot = self.solana_client.get_slot().await?;
+
+        let pools = self
+            .solana_client
+            .get_program_accounts_by_discriminator(
+                &self.amm_pool_program_id.to_string(),
+                anchor_discriminator("account", "PoolState"),
+            )
+            .await?;
+        for (address, data) in pools {
+            match PoolStateAccount::decode(&data) {
+                Ok(pool) => self.on_pool_state(sink, state, address, pool, slot).await?,
+                Err(e) => tracing::warn!("⚠️ Skipping pool {}: {}", address, e),
+            }
+        }
+
+        let positions = self
+            .solana_client
+            .get_program_accounts_by_discriminator(
+                &self.portfolio_program_id.to_string(),
+                anchor_discriminator("account", "Position"),
+            )
+            .await?;
+        for (address, data) in positions {
+            self.on_position(address, &data, slot).await;
+        }
+
+        tracing::info!("🌱 Seeded {} pool(s) from chain", state.pools.len());
+        Ok(())
+    }
+
+    // Step 8: Route subscription confirmations and notifications
+    async fn handle_message(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        message: &str,
+    ) -> Result<(), ThreadSafeError> {
+        let value: serde_json::Value = serde_json::from_str(message)?;
+
+        // Subscription confirmation: {"id": <request ID>, "result": <subscription ID>}
+        if let (Some(request_id), Some(subscription_id)) = (
+            value.get("id").and_then(|id| id.as_u64()),
+            value.get("result").and_then(|result| result.as_u64()),
+        ) {
+            if let Some(target) = state.pending.remove(&request_id) {
+                tracing::debug!("📡 Subscribed to {:?} ({})", target, subscription_id);
+                state.subscriptions.insert(subscription_id, target);
+            }
+            return Ok(());
+        }
+
+        let Some(params) = value.get("params") else {
+            return Ok(());
+        };
+        let Some(target) = params
+            .get("subscription")
+            .and_then(|id| id.as_u64())
+            .and_then(|id| state.subscriptions.get(&id).cloned())
+        else {
+            return Ok(());
+        };
+        let result = &params["result"];
+        let slot = result["context"]["slot"].as_u64().unwrap_or_default();
+        let value = &result["value"];
+
+        match target {
+            // programNotification: {"pubkey": ..., "account": {"data": [<base64>, "base64"]}}
+            SubscriptionTarget::Pools | SubscriptionTarget::Positions => {
+                let (Some(address), Some(data)) =
+                    (value["pubkey"].as_str(), account_data(&value["account"]))
+                else {
+                    return Ok(());
+                };
+                if matches!(target, SubscriptionTarget::Pools) {
+                    let pool = PoolStateAccount::decode(&data)?;
+                    self.on_pool_state(sink, state, address.to_string(), pool, slot)
+                        .await?;
+                } else {
+                    self.on_position(address.to_string(), &data, slot).await;
+                }
+            }
+            // accountNotification: {"data": [<base64>, "base64"], ...}
+            SubscriptionTarget::Vault { pool, side } => {
+                let Some(amount) = account_data(value).as_deref().and_then(token_amount) else {
+                    return Ok(());
+                };
+                if let Some(tracked) = state.pools.get_mut(&pool) {
+                    match side {
+                        VaultSide::A => tracked.reserve_a = Some(amount),
+                        VaultSide::B => tracked.reserve_b = Some(amount),
+                    }
+                    self.emit_reserves(&pool, tracked, slot).await;
+                }
+            }
+            SubscriptionTarget::LpMint { pool } => {
+                let Some(supply) = account_data(value).as_deref().and_then(mint_supply) else {
+                    return Ok(());
+                };
+                if let Some(tracked) = state.pools.get_mut(&pool) {
+                    tracked.lp_supply = Some(supply);
+                    self.emit_reserves(&pool, tracked, slot).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Step 9: Track a pool, subscribing to its vaults the first time it is seen
+    async fn on_pool_state(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        address: String,
+        pool: PoolStateAccount,
+        slot: u64,
+    ) -> Result<(), ThreadSafeError> {
+        let tracked = state
+            .pools
+            .entry(address.clone())
+            .or_insert_with(|| TrackedPool {
+                state: pool.clone(),
+                reserve_a: None,
+                reserve_b: None,
+                lp_supply: None,
+                vaults_subscribed: false,
+            });
+        tracked.state = pool.clone();
+
+        if !tracked.vaults_subscribed {
+            match self.resolve_vaults(&address, &pool).await? {
+                Some(((vault_a, reserve_a), (vault_b, reserve_b))) => {
+                    if let Some(tracked) = state.pools.get_mut(&address) {
+                        tracked.reserve_a = Some(reserve_a);
+                        tracked.reserve_b = Some(reserve_b);
+                        tracked.vaults_subscribed = true;
+                    }
+                    for (vault, side) in [(vault_a, VaultSide::A), (vault_b, VaultSide::B)] {
+                        let target = SubscriptionTarget::Vault {
+                            pool: address.clone(),
+                            side,
+                        };
+                        self.subscribe_account(sink, state, &vault, target).await?;
+                    }
+                    self.track_lp_supply(sink, state, &address, &pool.lp_mint)
+                        .await?;
+                }
+                None => tracing::warn!("⚠️ Pool {} has no vaults yet", address),
+            }
+        }
+
+        if let Some(tracked) = state.pools.get(&address) {
+            self.emit_reserves(&address, tracked, slot).await;
+        }
+        Ok(())
+    }
+
+    // Vaults are recorded from v3; older pools use the pool authority's token account per mint
+    async fn resolve_vaults(
+        &self,
+        pool_address: &str,
+        pool: &PoolStateAccount,
+    ) -> Result<Option<((String, u64), (String, u64))>, ThreadSafeError> {
+        let authority = PoolStateAccount::authority_address(
+            &Pubkey::from_str(pool_address)?,
+            &self.amm_pool_program_id,
+        )
+        .to_string();
+
+        let mut vaults = Vec::with_capacity(2);
+        for (recorded, mint) in [
+            (&pool.vault_a, &pool.token_a),
+            (&pool.vault_b, &pool.token_b),
+        ] {
+            let vault = match recorded {
+                Some(vault) => vault.clone(),
+                None => match self
+                    .solana_client
+                    .find_token_account(&authority, mint)
+                    .await?
+                {
+                    Some(vault) => vault,
+                    None => return Ok(None),
+                },
+            };
+            let data = self.solana_client.get_account_data(&vault).await?;
+            let amount = token_amount(&data).ok_or("vault is not a token account")?;
+            vaults.push((vault, amount));
+        }
+
+        let vault_b = vaults.pop();
+        let vault_a = vaults.pop();
+        Ok(vault_a.zip(vault_b))
+    }
+
+    // LP holders are valued by their share of the mint's supply; without it the pool's
+    // recorded total_liquidity is used
+    async fn track_lp_supply(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        pool: &str,
+        lp_mint: &str,
+    ) -> Result<(), ThreadSafeError> {
+        match self.solana_client.get_account_data(lp_mint).await {
+            Ok(data) => {
+                if let Some(tracked) = state.pools.get_mut(pool) {
+                    tracked.lp_supply = mint_supply(&data);
+                }
+            }
+            Err(e) => tracing::warn!(
+                "⚠️ Could not read LP mint {} of pool {}: {}",
+                lp_mint,
+                pool,
+                e
+            ),
+        }
+        let target = SubscriptionTarget::LpMint {
+            pool: pool.to_string(),
+        };
+        self.subscribe_account(sink, state, lp_mint, target).await
+    }
+
+    // Step 10: Emit events
+    async fn emit_reserves(&self, address: &str, tracked: &TrackedPool, slot: u64) {
+        let (Some(reserve_a), Some(reserve_b)) = (tracked.reserve_a, tracked.reserve_b) else {
+            return;
+        };
+        let event = PortfolioEvent::PoolReservesChanged {
+            pool: address.to_string(),
+            token_a: tracked.state.token_a.clone(),
+            token_b: tracked.state.token_b.clone(),
+            lp_mint: tracked.state.lp_mint.clone(),
+            reserve_a,
+            reserve_b,
+            total_liquidity: tracked.lp_supply.unwrap_or(tracked.state.total_liquidity),
+            timestamp: chrono::Utc::now(),
+            source: Some(self.chain_source(slot)),
+        };
+        self.emit(event).await;
+    }
+
+    async fn on_position(&self, address: String, data: &[u8], slot: u64) {
+        let position = match PositionAccount::decode(data) {
+            Ok(position) => position,
+            Err(e) => {
+                tracing::warn!("⚠️ Skipping position {}: {}", address, e);
+                return;
+            }
+        };
+        let event = PortfolioEvent::PositionSnapshot {
+            wallet: position.owner,
+            position: address,
+            mint: position.mint,
+            pnl: position.pnl,
+            trade_count: position.trade_count,
+            timestamp: chrono::Utc::now(),
+            source: Some(self.chain_source(slot)),
+        };
+        self.emit(event).await;
+    }
+
+    // Snapshots are superseded by the next change, so a shed one is only logged
+    async fn emit(&self, event: PortfolioEvent) {
+        match self.event_tx.send(event).await {
+            Ok(()) => {}
+            Err(QueueError::Closed) => {
+                tracing::debug!("📭 Pipeline closed, dropping account event")
+            }
+            Err(e) => tracing::warn!("⚠️ Dropped account event: {}", e),
+        }
+    }
+
+    fn chain_source(&self, slot: u64) -> ChainSource {
+        ChainSource {
+            slot,
+            signature: None,
+            commitment: Commitment::from_config(&self.commitment),
+        }
+    }
+
+    // Step 11: Subscription requests
+    async fn subscribe_program(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        program_id: Pubkey,
+        account_name: &str,
+        target: SubscriptionTarget,
+    ) -> Result<(), ThreadSafeError> {
+        let discriminator =
+            bs58::encode(anchor_discriminator("account", account_name)).into_string();
+        let params = json!([
+            program_id.to_string(),
+            {
+                "encoding": "base64",
+                "commitment": self.commitment,
+                "filters": [{ "memcmp": { "offset": 0, "bytes": discriminator } }]
+            }
+        ]);
+        self.send_subscribe(sink, state, "programSubscribe", params, target)
+            .await
+    }
+
+    async fn subscribe_account(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        address: &str,
+        target: SubscriptionTarget,
+    ) -> Result<(), ThreadSafeError> {
+        let params = json!([
+            address,
+            { "encoding": "base64", "commitment": self.commitment }
+        ]);
+        self.send_subscribe(sink, state, "accountSubscribe", params, target)
+            .await
+    }
+
+    async fn send_subscribe(
+        &self,
+        sink: &mut WsSink,
+        state: &mut ConnectionState,
+        method: &str,
+        params: serde_json::Value,
+        target: SubscriptionTarget,
+    ) -> Result<(), ThreadSafeError> {
+        let request_id = state.next_request_id;
+        state.next_request_id += 1;
+        state.pending.insert(request_id, target);
+
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": method,
+            "params": params
+        });
+        sink.send(Message::Text(message.to_string())).await?;
+        Ok(())
+    }
+}
+
+// Step 12: Helpers
+fn account_data(account: &serde_json::Value) -> Option<Vec<u8>> {
+    let encoded = account.get("data")?.get(0)?.as_str()?;
+    base64_standard.decode(encoded).ok()
+}
+
+fn token_amount(data: &[u8]) -> Option<u64> {
+    let bytes = data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn mint_supply(data: &[u8]) -> Option<u64> {
+    let bytes = data.get(MINT_SUPPLY_OFFSET..MINT_SUPPLY_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
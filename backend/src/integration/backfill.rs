@@ -0,0 +1,292 @@
+// backend/src/ingestion/backfill.rs
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+
+use crate::config::{BackfillConfig, Config};
+use crate::integration::idl::IdlRegistry;
+use crate::integration::normalizer::EventNormalizer;
+use crate::integration::solana_ws::{decode_program_logs, ProgramSubscription};
+use crate::models::event::{ChainSource, Commitment, PortfolioEvent};
+use crate::pipeline::dead_letter::{DeadLetterPayload, DeadLetterQueue, DeadLetterStage};
+use crate::pipeline::finality::FinalityTracker;
+use crate::pipeline::mpsc_queue::{EventQueue, QueueError};
+use crate::services::solana_client::{SolanaClient, TransactionInstruction};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Step 1: Newest processed signature per address, persisted between runs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BackfillCursor {
+    pub signatures: HashMap<String, String>, // Address -> newest processed signature
+}
+
+impl BackfillCursor {
+    pub fn load(path: &str) -> Result<Self, ThreadSafeError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Write-then-rename so a crash never leaves a truncated cursor behind
+    pub fn save(&self, path: &str) -> Result<(), ThreadSafeError> {
+        let tmp_path = format!("{}.tmp", path);
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+// A transaction to replay and every backfilled address it mentions
+struct PendingTransaction {
+    slot: u64,
+    position: usize, // Index in the newest-first listing, orders transactions within a slot
+    addresses: Vec<String>,
+}
+
+// Step 2: Replays history missed while the backend was down
+pub struct BackfillWorker {
+    config: BackfillConfig,
+    solana_client: SolanaClient,
+    programs: Vec<ProgramSubscription>,
+    idls: Arc<IdlRegistry>,
+    instruction_normalizer: EventNormalizer, // Maps IDL-decoded instructions
+    commitment: Commitment,
+    event_tx: EventQueue,
+    finality: Option<FinalityTracker>,
+    dead_letters: Option<DeadLetterQueue>,
+}
+
+impl BackfillWorker {
+    pub fn new(
+        config: &Config,
+        idls: Arc<IdlRegistry>,
+        solana_client: SolanaClient,
+        event_tx: EventQueue,
+    ) -> Self {
+        Self {
+            config: config.backfill.clone(),
+            solana_client,
+            programs: ProgramSubscription::from_config(&config.solana, &idls),
+            instruction_normalizer: EventNormalizer::with_idls(idls.clone()),
+            idls,
+            // History endpoints answer at `confirmed` or above
+            commitment: Commitment::from_config(&config.solana.commitment.to_lowercase())
+                .max(Commitment::Confirmed),
+            event_tx,
+            finality: None,
+            dead_letters: None,
+        }
+    }
+
+    // Hand every backfilled event below `finalized` to `tracker` so a revert is compensated
+    pub fn with_finality_tracker(mut self, tracker: FinalityTracker) -> Self {
+        self.finality = Some(tracker);
+        self
+    }
+
+    // Park log lines and instructions that fail to decode instead of only logging them
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    // Step 3: Backfill every address back to its cursor; returns the number of events emitted
+    pub async fn run(&self) -> Result<usize, ThreadSafeError> {
+        let mut cursor = BackfillCursor::load(&self.config.cursor_path)?;
+
+        let mut addresses: Vec<String> =
+            self.programs.iter().map(|p| p.program_id.clone()).collect();
+        if let Some(wallet) = &self.config.wallet {
+            addresses.push(wallet.clone());
+        }
+
+        // Step 4: Collect unseen signatures, deduplicating transactions that mention several addresses
+        let mut pending: HashMap<String, PendingTransaction> = HashMap::new();
+        let mut newest: HashMap<String, String> = HashMap::new();
+        for address in &addresses {
+            let until = cursor.signatures.get(address).map(String::as_str);
+            // Without a cursor, only the latest max_transactions are replayed
+            let limit = until.is_none().then_some(self.config.max_transactions);
+            let (statuses, truncated) = collect_signatures(
+                &self.solana_client,
+                address,
+                until,
+                self.config.page_size,
+                limit,
+            )
+            .await?;
+            if truncated {
+                tracing::warn!(
+                    "⚠️ No cursor for {}; backfilling only the latest {} transactions",
+                    address,
+                    self.config.max_transactions
+                );
+            }
+            if let Some(first) = statuses.first() {
+                newest.insert(address.clone(), first.signature.clone());
+            }
+
+            // Failed transactions have no on-chain effect
+            for (position, status) in statuses.into_iter().enumerate() {
+                if status.err.is_some() {
+                    continue;
+                }
+                pending
+                    .entry(status.signature)
+                    .or_insert_with(|| PendingTransaction {
+                        slot: status.slot,
+                        position,
+                        addresses: Vec::new(),
+                    })
+                    .addresses
+                    .push(address.clone());
+            }
+        }
+
+        // Step 5: Replay oldest first so downstream sees events in slot order
+        let mut ordered: Vec<(String, PendingTransaction)> = pending.into_iter().collect();
+        ordered.sort_by_key(|(_, tx)| (tx.slot, Reverse(tx.position)));
+        tracing::info!("⏪ Backfilling {} transaction(s)", ordered.len());
+
+        let mut emitted = 0;
+        for (signature, tx) in ordered {
+            let transaction = self.solana_client.get_transaction_logs(&signature).await?;
+            let logs: Vec<&str> = transaction.logs.iter().map(String::as_str).collect();
+            let source = ChainSource {
+                slot: transaction.slot,
+                signature: Some(signature.clone()),
+                commitment: self.commitment,
+            };
+
+            let decoded = decode_program_logs(&self.programs, &logs);
+            for failure in decoded.failures {
+                let payload = DeadLetterPayload::ProgramLog {
+                    program_id: failure.program_id,
+                    log: failure.log,
+                    source: source.clone(),
+                };
+                self.park(&signature, payload, failure.error.to_string())
+                    .await;
+            }
+            let mut events: Vec<PortfolioEvent> = decoded
+                .events
+                .into_iter()
+                .map(|(_, mut event)| {
+                    event.set_source(source.clone());
+                    event
+                })
+                .collect();
+            events.extend(
+                self.decode_instructions(&signature, &source, &transaction.instructions)
+                    .await,
+            );
+            // Decoders stamp the time of decoding; replayed events happened at their block
+            if let Some(at) = transaction.timestamp() {
+                for event in &mut events {
+                    event.set_timestamp(at);
+                }
+            }
+
+            for event in events {
+                match self.event_tx.send(event.clone()).await {
+                    Ok(()) => {
+                        if let Some(finality) = &self.finality {
+                            finality.track(&event).await;
+                        }
+                        emitted += 1;
+                    }
+                    Err(QueueError::Closed) => return Err("pipeline closed during backfill".into()),
+                    Err(e) => {
+                        tracing::warn!("⚠️ Backfilled event of {} not queued: {}", signature, e)
+                    }
+                }
+            }
+
+            // Saved after every transaction: nothing dedupes a re-emitted one after a restart
+            for address in tx.addresses {
+                cursor.signatures.insert(address, signature.clone());
+            }
+            cursor.save(&self.config.cursor_path)?;
+        }
+
+        // Step 6: Advance past skipped (failed) transactions too
+        cursor.signatures.extend(newest);
+        cursor.save(&self.config.cursor_path)?;
+
+        tracing::info!("✅ Backfill complete: {} event(s)", emitted);
+        Ok(emitted)
+    }
+
+    // Events from the instructions of programs with IDL instruction mappings
+    async fn decode_instructions(
+        &self,
+        signature: &str,
+        source: &ChainSource,
+        instructions: &[TransactionInstruction],
+    ) -> Vec<PortfolioEvent> {
+        let mut events = Vec::new();
+        for instruction in instructions {
+            if !self.idls.has_instruction_mappings(&instruction.program_id) {
+                continue;
+            }
+            match self
+                .instruction_normalizer
+                .normalize_instruction(instruction, source)
+            {
+                Ok(decoded) => events.extend(decoded),
+                Err(e) => {
+                    let payload = DeadLetterPayload::Instruction {
+                        instruction: instruction.clone(),
+                        source: source.clone(),
+                    };
+                    self.park(signature, payload, e.to_string()).await;
+                }
+            }
+        }
+        events
+    }
+
+    async fn park(&self, signature: &str, payload: DeadLetterPayload, error: String) {
+        tracing::warn!("⚠️ Failed to decode event of {}: {}", signature, error);
+        if let Some(dead_letters) = &self.dead_letters {
+            dead_letters
+                .push(DeadLetterStage::Normalization, payload, error)
+                .await;
+        }
+    }
+}
+
+// Step 7: Page newest-first down to `until`, stopping early at `limit`; returns whether the
+// listing was cut short before reaching `until`
+pub async fn collect_signatures(
+    solana_client: &SolanaClient,
+    address: &str,
+    until: Option<&str>,
+    page_size: usize,
+    limit: Option<usize>,
+) -> Result<(Vec<RpcConfirmedTransactionStatusWithSignature>, bool), ThreadSafeError> {
+    let mut statuses: Vec<RpcConfirmedTransactionStatusWithSignature> = Vec::new();
+
+    loop {
+        let before = statuses.last().map(|status| status.signature.clone());
+        let page = solana_client
+            .get_signatures_for_address(address, before.as_deref(), until, page_size)
+            .await?;
+        let exhausted = page.len() < page_size;
+        statuses.extend(page);
+
+        if exhausted {
+            return Ok((statuses, false));
+        }
+        if let Some(limit) = limit.filter(|limit| statuses.len() >= *limit) {
+            statuses.truncate(limit);
+            return Ok((statuses, true));
+        }
+    }
+}
@@ -0,0 +1,951 @@
+// backend/src/ingestion/idl.rs
+use std::collections::HashMap;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as base64_standard;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::integration::normalizer::RawEvent;
+use crate::integration::program_accounts::anchor_discriminator;
+use crate::integration::program_events::PROGRAM_DATA_PREFIX;
+use crate::services::solana_client::TransactionInstruction;
+
+// File in the IDL directory holding IDL -> `RawEvent` mappings; every other `.json` is an IDL
+pub const MAPPINGS_FILE: &str = "mappings.json";
+
+// Guards against self-referential type definitions
+const MAX_TYPE_DEPTH: usize = 32;
+
+// Step 1: Anchor IDL model. Accepts both the 0.30+ format (`address`, explicit
+// discriminators, `pubkey`) and the legacy format (`metadata.address`, `publicKey`,
+// inline event fields, derived discriminators).
+#[derive(Debug, Clone, Deserialize)]
+struct Idl {
+    address: Option<String>,
+    name: Option<String>,
+    #[serde(default)]
+    metadata: IdlMetadata,
+    #[serde(default)]
+    instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    events: Vec<IdlEvent>,
+    #[serde(default)]
+    types: Vec<IdlTypeDef>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct IdlMetadata {
+    name: Option<String>,
+    address: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlInstruction {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    accounts: Vec<IdlAccountItem>,
+    #[serde(default)]
+    args: Vec<IdlField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlAccountItem {
+    Composite {
+        name: String,
+        accounts: Vec<IdlAccountItem>,
+    },
+    Single {
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlEvent {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    fields: Option<Vec<IdlField>>, // Legacy IDLs inline the fields; newer ones point at `types`
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlTypeDef {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlTypeDefBody,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum IdlTypeDefBody {
+    Struct {
+        #[serde(default)]
+        fields: Option<IdlDefinedFields>,
+    },
+    Enum {
+        variants: Vec<IdlEnumVariant>,
+    },
+    Type {
+        alias: IdlType,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlEnumVariant {
+    name: String,
+    #[serde(default)]
+    fields: Option<IdlDefinedFields>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlDefinedFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlType {
+    Primitive(String),
+    Vec { vec: Box<IdlType> },
+    Option { option: Box<IdlType> },
+    COption { coption: Box<IdlType> },
+    Array { array: (Box<IdlType>, usize) },
+    Defined { defined: IdlDefined },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlDefined {
+    Name(String),
+    Reference { name: String },
+}
+
+impl IdlDefined {
+    fn name(&self) -> &str {
+        match self {
+            Self::Name(name) | Self::Reference { name } => name,
+        }
+    }
+}
+
+// Step 2: Generic decode results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecodedKind {
+    Instruction,
+    Event,
+}
+
+// An instruction or event from any IDL-described program, as plain JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedItem {
+    pub program_id: String,
+    pub program: String, // IDL name
+    pub kind: DecodedKind,
+    pub name: String,
+    pub data: serde_json::Value, // Instruction args or event fields
+    pub accounts: serde_json::Map<String, serde_json::Value>, // Instruction account name -> address
+}
+
+// Step 3: How a decoded item becomes a `RawEvent`. Field paths are dotted lookups into the
+// `DecodedItem` JSON, e.g. `data.amount` or `accounts.token_authority`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlMapping {
+    pub program: String, // IDL name or program ID
+    pub kind: DecodedKind,
+    pub name: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub fields: HashMap<String, String>, // Canonical RawEvent field -> path
+    #[serde(default)]
+    pub constants: HashMap<String, serde_json::Value>, // Canonical RawEvent field -> fixed value
+}
+
+// A loaded IDL with discriminators resolved
+struct ProgramIdl {
+    name: String,
+    instructions: Vec<([u8; 8], IdlInstruction)>,
+    events: Vec<([u8; 8], IdlEvent)>,
+    types: HashMap<String, IdlTypeDef>,
+}
+
+// Step 4: Every IDL in the configured directory, keyed by program ID
+#[derive(Default)]
+pub struct IdlRegistry {
+    programs: HashMap<String, ProgramIdl>,
+    mappings: Vec<IdlMapping>,
+}
+
+impl IdlRegistry {
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, IdlError> {
+        let dir = dir.as_ref();
+        let mut registry = Self::default();
+
+        let entries = std::fs::read_dir(dir).map_err(|e| IdlError::Io(e.to_string()))?;
+        for entry in entries {
+            let path = entry.map_err(|e| IdlError::Io(e.to_string()))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let contents =
+                std::fs::read_to_string(&path).map_err(|e| IdlError::Io(e.to_string()))?;
+            let file_name = path.display().to_string();
+
+            if path.file_name().and_then(|name| name.to_str()) == Some(MAPPINGS_FILE) {
+                registry.mappings = serde_json::from_str(&contents)
+                    .map_err(|e| IdlError::InvalidIdl(file_name, e.to_string()))?;
+                continue;
+            }
+
+            let idl: Idl = serde_json::from_str(&contents)
+                .map_err(|e| IdlError::InvalidIdl(file_name.clone(), e.to_string()))?;
+            let (program_id, program) = ProgramIdl::resolve(idl)
+                .map_err(|reason| IdlError::InvalidIdl(file_name, reason))?;
+            tracing::info!(
+                "📜 Loaded IDL for {} ({}): {} instruction(s), {} event(s)",
+                program.name,
+                program_id,
+                program.instructions.len(),
+                program.events.len()
+            );
+            registry.programs.insert(program_id, program);
+        }
+
+        Ok(registry)
+    }
+
+    pub fn has_idl(&self, program_id: &str) -> bool {
+        self.programs.contains_key(program_id)
+    }
+
+    pub fn program_ids(&self) -> Vec<String> {
+        self.programs.keys().cloned().collect()
+    }
+
+    // Fetching a transaction is only worth it when some instruction of it could be mapped
+    pub fn has_instruction_mappings(&self, program_id: &str) -> bool {
+        let Some(program) = self.programs.get(program_id) else {
+            return false;
+        };
+        self.mappings.iter().any(|mapping| {
+            mapping.kind == DecodedKind::Instruction
+                && (mapping.program == program_id || mapping.program == program.name)
+        })
+    }
+
+    // Step 5: Decode an instruction; `None` if the program or discriminator is unknown
+    pub fn decode_instruction(
+        &self,
+        instruction: &TransactionInstruction,
+    ) -> Result<Option<DecodedItem>, IdlError> {
+        let Some(program) = self.programs.get(&instruction.program_id) else {
+            return Ok(None);
+        };
+        if instruction.data.len() < 8 {
+            return Ok(None);
+        }
+        let (discriminator, mut body) = instruction.data.split_at(8);
+        let Some((_, idl_instruction)) = program
+            .instructions
+            .iter()
+            .find(|(known, _)| known.as_slice() == discriminator)
+        else {
+            return Ok(None);
+        };
+
+        let data = program.decode_fields(&idl_instruction.args, &mut body, 0)?;
+        let mut names = Vec::new();
+        flatten_accounts(&idl_instruction.accounts, "", &mut names);
+        let accounts = names
+            .into_iter()
+            .zip(&instruction.accounts)
+            .map(|(name, address)| (name, json!(address)))
+            .collect();
+
+        Ok(Some(DecodedItem {
+            program_id: instruction.program_id.clone(),
+            program: program.name.clone(),
+            kind: DecodedKind::Instruction,
+            name: idl_instruction.name.clone(),
+            data,
+            accounts,
+        }))
+    }
+
+    // Step 6: Decode an `emit!` payload (without the `Program data:` prefix)
+    pub fn decode_event(
+        &self,
+        program_id: &str,
+        payload: &[u8],
+    ) -> Result<Option<DecodedItem>, IdlError> {
+        let Some(program) = self.programs.get(program_id) else {
+            return Ok(None);
+        };
+        if payload.len() < 8 {
+            return Ok(None);
+        }
+        let (discriminator, mut body) = payload.split_at(8);
+        let Some((_, event)) = program
+            .events
+            .iter()
+            .find(|(known, _)| known.as_slice() == discriminator)
+        else {
+            return Ok(None);
+        };
+
+        let data = match &event.fields {
+            Some(fields) => program.decode_fields(fields, &mut body, 0)?,
+            None => program.decode_defined(&event.name, &mut body, 0)?,
+        };
+
+        Ok(Some(DecodedItem {
+            program_id: program_id.to_string(),
+            program: program.name.clone(),
+            kind: DecodedKind::Event,
+            name: event.name.clone(),
+            data,
+            accounts: serde_json::Map::new(),
+        }))
+    }
+
+    pub fn decode_log(&self, program_id: &str, log: &str) -> Result<Option<DecodedItem>, IdlError> {
+        let Some(encoded) = log.strip_prefix(PROGRAM_DATA_PREFIX) else {
+            return Ok(None);
+        };
+        let payload = base64_standard
+            .decode(encoded.trim())
+            .map_err(|e| IdlError::InvalidData(e.to_string()))?;
+        self.decode_event(program_id, &payload)
+    }
+
+    // Step 7: Apply every mapping that matches the item
+    pub fn to_raw_events(&self, item: &DecodedItem) -> Vec<RawEvent> {
+        let item_json = json!(item);
+        self.mappings
+            .iter()
+            .filter(|mapping| {
+                (mapping.program == item.program || mapping.program == item.program_id)
+                    && mapping.kind == item.kind
+                    && mapping.name == item.name
+            })
+            .map(|mapping| {
+                let mut raw_event = RawEvent::new(mapping.event_type.clone());
+                for (field, path) in &mapping.fields {
+                    if let Some(value) = item_json.pointer(&json_pointer(path)) {
+                        raw_event.data.insert(field.clone(), value.clone());
+                    }
+                }
+                for (field, value) in &mapping.constants {
+                    raw_event.data.insert(field.clone(), value.clone());
+                }
+                raw_event
+            })
+            .collect()
+    }
+}
+
+impl ProgramIdl {
+    // Step 8: Resolve the program ID and every discriminator
+    fn resolve(idl: Idl) -> Result<(String, Self), String> {
+        let program_id = idl
+            .address
+            .or(idl.metadata.address)
+            .ok_or("IDL has no program address")?;
+        Pubkey::try_from(program_id.as_str())
+            .map_err(|_| format!("invalid program address {}", program_id))?;
+        let name = idl
+            .metadata
+            .name
+            .or(idl.name)
+            .unwrap_or_else(|| program_id.clone());
+
+        let instructions = idl
+            .instructions
+            .into_iter()
+            .map(|instruction| {
+                let discriminator = resolve_discriminator(
+                    instruction.discriminator.as_deref(),
+                    "global",
+                    &to_snake_case(&instruction.name),
+                )?;
+                Ok((discriminator, instruction))
+            })
+            .collect::<Result<_, String>>()?;
+        let events = idl
+            .events
+            .into_iter()
+            .map(|event| {
+                let discriminator =
+                    resolve_discriminator(event.discriminator.as_deref(), "event", &event.name)?;
+                Ok((discriminator, event))
+            })
+            .collect::<Result<_, String>>()?;
+        let types = idl
+            .types
+            .into_iter()
+            .map(|ty| (ty.name.clone(), ty))
+            .collect();
+
+        Ok((
+            program_id,
+            Self {
+                name,
+                instructions,
+                events,
+                types,
+            },
+        ))
+    }
+
+    // Step 9: Borsh decoding driven by IDL types
+    fn decode_fields(
+        &self,
+        fields: &[IdlField],
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<serde_json::Value, IdlError> {
+        let mut object = serde_json::Map::new();
+        for field in fields {
+            object.insert(
+                field.name.clone(),
+                self.decode_type(&field.ty, data, depth)?,
+            );
+        }
+        Ok(serde_json::Value::Object(object))
+    }
+
+    fn decode_defined_fields(
+        &self,
+        fields: &Option<IdlDefinedFields>,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<serde_json::Value, IdlError> {
+        match fields {
+            None => Ok(json!({})),
+            Some(IdlDefinedFields::Named(fields)) => self.decode_fields(fields, data, depth),
+            Some(IdlDefinedFields::Tuple(types)) => types
+                .iter()
+                .map(|ty| self.decode_type(ty, data, depth))
+                .collect::<Result<Vec<_>, _>>()
+                .map(serde_json::Value::Array),
+        }
+    }
+
+    fn decode_defined(
+        &self,
+        name: &str,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<serde_json::Value, IdlError> {
+        if depth > MAX_TYPE_DEPTH {
+            return Err(IdlError::InvalidData(format!(
+                "type {} nests too deeply",
+                name
+            )));
+        }
+        let definition = self
+            .types
+            .get(name)
+            .ok_or_else(|| IdlError::UnknownType(name.to_string()))?;
+
+        match &definition.ty {
+            IdlTypeDefBody::Struct { fields } => {
+                self.decode_defined_fields(fields, data, depth + 1)
+            }
+            IdlTypeDefBody::Enum { variants } => {
+                let index = read_bytes::<1>(data)?[0] as usize;
+                let variant = variants.get(index).ok_or_else(|| {
+                    IdlError::InvalidData(format!("{} has no variant {}", name, index))
+                })?;
+                // Unit variants decode to their name, others to `{ name: fields }`
+                if variant.fields.is_none() {
+                    return Ok(json!(variant.name));
+                }
+                let fields = self.decode_defined_fields(&variant.fields, data, depth + 1)?;
+                Ok(json!({ variant.name.clone(): fields }))
+            }
+            IdlTypeDefBody::Type { alias } => self.decode_type(alias, data, depth + 1),
+        }
+    }
+
+    fn decode_type(
+        &self,
+        ty: &IdlType,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<serde_json::Value, IdlError> {
+        // Inline containers nest as deeply as defined types do
+        if depth > MAX_TYPE_DEPTH {
+            return Err(IdlError::InvalidData("type nests too deeply".to_string()));
+        }
+        match ty {
+            IdlType::Primitive(name) => decode_primitive(name, data),
+            IdlType::Vec { vec } => {
+                let len = u32::from_le_bytes(read_bytes(data)?) as usize;
+                // Every element takes at least one byte, so a longer length is corrupt
+                if len > data.len() {
+                    return Err(IdlError::InvalidData(format!(
+                        "vec length {} out of range",
+                        len
+                    )));
+                }
+                (0..len)
+                    .map(|_| self.decode_type(vec, data, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(serde_json::Value::Array)
+            }
+            IdlType::Option { option } => match read_bytes::<1>(data)?[0] {
+                0 => Ok(serde_json::Value::Null),
+                _ => self.decode_type(option, data, depth + 1),
+            },
+            IdlType::COption { coption } => match u32::from_le_bytes(read_bytes(data)?) {
+                0 => Ok(serde_json::Value::Null),
+                _ => self.decode_type(coption, data, depth + 1),
+            },
+            IdlType::Array { array: (ty, len) } => (0..*len)
+                .map(|_| self.decode_type(ty, data, depth + 1))
+                .collect::<Result<Vec<_>, _>>()
+                .map(serde_json::Value::Array),
+            IdlType::Defined { defined } => self.decode_defined(defined.name(), data, depth),
+        }
+    }
+}
+
+// Step 10: Helpers
+fn decode_primitive(name: &str, data: &mut &[u8]) -> Result<serde_json::Value, IdlError> {
+    Ok(match name {
+        "bool" => json!(read_bytes::<1>(data)?[0] != 0),
+        "u8" => json!(u8::from_le_bytes(read_bytes(data)?)),
+        "i8" => json!(i8::from_le_bytes(read_bytes(data)?)),
+        "u16" => json!(u16::from_le_bytes(read_bytes(data)?)),
+        "i16" => json!(i16::from_le_bytes(read_bytes(data)?)),
+        "u32" => json!(u32::from_le_bytes(read_bytes(data)?)),
+        "i32" => json!(i32::from_le_bytes(read_bytes(data)?)),
+        "u64" => json!(u64::from_le_bytes(read_bytes(data)?)),
+        "i64" => json!(i64::from_le_bytes(read_bytes(data)?)),
+        // JSON numbers cannot hold 128-bit values exactly
+        "u128" => json!(u128::from_le_bytes(read_bytes(data)?).to_string()),
+        "i128" => json!(i128::from_le_bytes(read_bytes(data)?).to_string()),
+        "f32" => json!(f32::from_le_bytes(read_bytes(data)?)),
+        "f64" => json!(f64::from_le_bytes(read_bytes(data)?)),
+        "pubkey" | "publicKey" => json!(Pubkey::new_from_array(read_bytes(data)?).to_string()),
+        "string" => {
+            let bytes = read_length_prefixed(data)?;
+            json!(String::from_utf8(bytes.to_vec())
+                .map_err(|e| IdlError::InvalidData(e.to_string()))?)
+        }
+        "bytes" => json!(base64_standard.encode(read_length_prefixed(data)?)),
+        other => return Err(IdlError::UnknownType(other.to_string())),
+    })
+}
+
+fn read_bytes<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], IdlError> {
+    if data.len() < N {
+        return Err(IdlError::InvalidData("unexpected end of data".to_string()));
+    }
+    let (bytes, rest) = data.split_at(N);
+    *data = rest;
+    Ok(bytes.try_into().expect("split_at returned N bytes"))
+}
+
+fn read_length_prefixed<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], IdlError> {
+    let len = u32::from_le_bytes(read_bytes(data)?) as usize;
+    if data.len() < len {
+        return Err(IdlError::InvalidData("unexpected end of data".to_string()));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn resolve_discriminator(
+    explicit: Option<&[u8]>,
+    namespace: &str,
+    name: &str,
+) -> Result<[u8; 8], String> {
+    match explicit {
+        Some(bytes) => bytes
+            .try_into()
+            .map_err(|_| format!("{} discriminator must be 8 bytes", name)),
+        None => Ok(anchor_discriminator(namespace, name)),
+    }
+}
+
+// Legacy IDLs use camelCase instruction names; Anchor hashes the snake_case Rust name
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+// Dotted mapping path as a JSON pointer, e.g. `data.amount` -> `/data/amount`
+fn json_pointer(path: &str) -> String {
+    path.split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+// Composite account groups become `group.account`
+fn flatten_accounts(items: &[IdlAccountItem], prefix: &str, names: &mut Vec<String>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Composite { name, accounts } => {
+                flatten_accounts(accounts, &format!("{}{}.", prefix, name), names)
+            }
+            IdlAccountItem::Single { name } => names.push(format!("{}{}", prefix, name)),
+        }
+    }
+}
+
+// Step 11: IDL error types
+#[derive(Debug)]
+pub enum IdlError {
+    Io(String),
+    InvalidIdl(String, String),
+    UnknownType(String),
+    InvalidData(String),
+}
+
+impl std::fmt::Display for IdlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(reason) => write!(f, "Failed to read IDL directory: {}", reason),
+            Self::InvalidIdl(file, reason) => write!(f, "Invalid IDL {}: {}", file, reason),
+            Self::UnknownType(name) => write!(f, "Unknown IDL type: {}", name),
+            Self::InvalidData(reason) => write!(f, "Invalid instruction or event data: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for IdlError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const LEGACY_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const ANCHOR_ID: &str = "11111111111111111111111111111111";
+
+    // Pre-0.30 layout: `metadata.address`, camelCase names, inline event fields
+    const LEGACY_IDL: &str = r#"{
+        "version": "0.1.0",
+        "name": "legacy_swap",
+        "instructions": [{
+            "name": "swapTokens",
+            "accounts": [
+                { "name": "user", "isMut": true, "isSigner": true },
+                { "name": "pool", "accounts": [{ "name": "state", "isMut": true, "isSigner": false }] }
+            ],
+            "args": [
+                { "name": "amountIn", "type": "u64" },
+                { "name": "minOut", "type": { "option": "u64" } }
+            ]
+        }],
+        "events": [{
+            "name": "Swapped",
+            "fields": [
+                { "name": "user", "type": "publicKey", "index": false },
+                { "name": "amount", "type": "u64", "index": false }
+            ]
+        }],
+        "metadata": { "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" }
+    }"#;
+
+    // 0.30 layout: top-level `address`, explicit discriminators, event fields under `types`
+    const ANCHOR_IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "orders", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [{
+            "name": "place_order",
+            "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [{ "name": "owner", "writable": true, "signer": true }],
+            "args": [{ "name": "amount", "type": "u64" }]
+        }],
+        "events": [{ "name": "OrderPlaced", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1] }],
+        "types": [
+            {
+                "name": "OrderPlaced",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "order", "type": { "defined": { "name": "Order" } } },
+                    { "name": "fallback", "type": { "defined": "Side" } }
+                ] }
+            },
+            {
+                "name": "Order",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "side", "type": { "defined": { "name": "Side" } } },
+                    { "name": "tags", "type": { "vec": "string" } },
+                    { "name": "memo", "type": { "option": "string" } },
+                    { "name": "authority", "type": { "coption": "pubkey" } },
+                    { "name": "levels", "type": { "array": ["u16", 3] } }
+                ] }
+            },
+            {
+                "name": "Side",
+                "type": { "kind": "enum", "variants": [
+                    { "name": "Buy" },
+                    { "name": "Sell" },
+                    { "name": "Limit", "fields": [{ "name": "price", "type": "u64" }] }
+                ] }
+            }
+        ]
+    }"#;
+
+    const MAPPINGS: &str = r#"[{
+        "program": "legacy_swap",
+        "kind": "instruction",
+        "name": "swapTokens",
+        "event_type": "swap_executed",
+        "fields": { "wallet": "accounts.user", "amount": "data.amountIn", "memo": "data.missing" },
+        "constants": { "input_mint": "So11111111111111111111111111111111111111112" }
+    }]"#;
+
+    // The fixtures loaded the way the service loads them, from a directory of their own
+    fn registry() -> IdlRegistry {
+        static DIRS: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "idl_fixtures_{}_{}",
+            std::process::id(),
+            DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("legacy.json"), LEGACY_IDL).unwrap();
+        std::fs::write(dir.join("orders.json"), ANCHOR_IDL).unwrap();
+        std::fs::write(dir.join(MAPPINGS_FILE), MAPPINGS).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an IDL").unwrap();
+        let registry = IdlRegistry::load_dir(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        registry
+    }
+
+    fn pubkey(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    fn swap_instruction(data: Vec<u8>) -> TransactionInstruction {
+        TransactionInstruction {
+            program_id: LEGACY_ID.to_string(),
+            accounts: vec![pubkey(1).to_string(), pubkey(2).to_string()],
+            data,
+        }
+    }
+
+    fn swap_data(amount_in: u64, min_out: Option<u64>) -> Vec<u8> {
+        let mut data = anchor_discriminator("global", "swap_tokens").to_vec();
+        data.extend(amount_in.to_le_bytes());
+        match min_out {
+            Some(min_out) => {
+                data.push(1);
+                data.extend(min_out.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data
+    }
+
+    #[test]
+    fn discriminators_are_derived_from_snake_case_names() {
+        assert_eq!(to_snake_case("swapTokens"), "swap_tokens");
+        assert_eq!(to_snake_case("SetFeeBps"), "set_fee_bps");
+        assert_eq!(to_snake_case("place_order"), "place_order");
+        assert_eq!(
+            anchor_discriminator("global", "initialize"),
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+
+        let registry = registry();
+        let legacy = &registry.programs[LEGACY_ID];
+        assert_eq!(legacy.name, "legacy_swap");
+        assert_eq!(
+            legacy.instructions[0].0,
+            anchor_discriminator("global", "swap_tokens")
+        );
+        assert_eq!(legacy.events[0].0, anchor_discriminator("event", "Swapped"));
+
+        // Explicit discriminators are used as given
+        let orders = &registry.programs[ANCHOR_ID];
+        assert_eq!(orders.name, "orders");
+        assert_eq!(orders.instructions[0].0, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(orders.events[0].0, [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert!(resolve_discriminator(Some(&[1, 2, 3]), "global", "short").is_err());
+    }
+
+    #[test]
+    fn legacy_instructions_and_events_decode() {
+        let registry = registry();
+        let item = registry
+            .decode_instruction(&swap_instruction(swap_data(500, Some(480))))
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.kind, DecodedKind::Instruction);
+        assert_eq!(item.name, "swapTokens");
+        assert_eq!(item.data, json!({ "amountIn": 500, "minOut": 480 }));
+        assert_eq!(item.accounts["user"], json!(pubkey(1).to_string()));
+        assert_eq!(item.accounts["pool.state"], json!(pubkey(2).to_string()));
+
+        let mut payload = anchor_discriminator("event", "Swapped").to_vec();
+        payload.extend(pubkey(3).to_bytes());
+        payload.extend(75u64.to_le_bytes());
+        let log = format!(
+            "{}{}",
+            PROGRAM_DATA_PREFIX,
+            base64::Engine::encode(&base64_standard, &payload)
+        );
+        let event = registry.decode_log(LEGACY_ID, &log).unwrap().unwrap();
+        assert_eq!(event.name, "Swapped");
+        assert_eq!(
+            event.data,
+            json!({ "user": pubkey(3).to_string(), "amount": 75 })
+        );
+
+        // Unknown programs and discriminators are not errors
+        assert!(registry
+            .decode_event(ANCHOR_ID, &[0; 16])
+            .unwrap()
+            .is_none());
+        assert!(registry
+            .decode_event("unknown", &payload)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn container_enum_and_defined_types_decode() {
+        let mut payload = vec![8, 7, 6, 5, 4, 3, 2, 1];
+        payload.push(2); // Side::Limit
+        payload.extend(500u64.to_le_bytes());
+        payload.extend(2u32.to_le_bytes()); // tags
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(b"a");
+        payload.extend(2u32.to_le_bytes());
+        payload.extend(b"bc");
+        payload.push(0); // memo: None
+        payload.extend(1u32.to_le_bytes()); // authority: Some
+        payload.extend(pubkey(4).to_bytes());
+        for level in [1u16, 2, 3] {
+            payload.extend(level.to_le_bytes());
+        }
+        payload.push(1); // fallback: Side::Sell
+
+        let event = registry()
+            .decode_event(ANCHOR_ID, &payload)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.name, "OrderPlaced");
+        assert_eq!(
+            event.data,
+            json!({
+                "order": {
+                    "side": { "Limit": { "price": 500 } },
+                    "tags": ["a", "bc"],
+                    "memo": null,
+                    "authority": pubkey(4).to_string(),
+                    "levels": [1, 2, 3],
+                },
+                "fallback": "Sell",
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_and_corrupt_data_is_rejected() {
+        let registry = registry();
+        let mut truncated = swap_data(500, Some(480));
+        truncated.truncate(truncated.len() - 4);
+        assert!(matches!(
+            registry.decode_instruction(&swap_instruction(truncated)),
+            Err(IdlError::InvalidData(_))
+        ));
+
+        // A vec claiming more elements than there are bytes left
+        let mut payload = vec![8, 7, 6, 5, 4, 3, 2, 1, 0];
+        payload.extend(1_000u32.to_le_bytes());
+        assert!(matches!(
+            registry.decode_event(ANCHOR_ID, &payload),
+            Err(IdlError::InvalidData(_))
+        ));
+
+        // An enum variant past the end of the list
+        let payload = [8, 7, 6, 5, 4, 3, 2, 1, 9];
+        assert!(matches!(
+            registry.decode_event(ANCHOR_ID, &payload),
+            Err(IdlError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn inline_containers_are_depth_limited() {
+        let mut ty = json!("u8");
+        for _ in 0..=MAX_TYPE_DEPTH {
+            ty = json!({ "vec": ty });
+        }
+        let ty: IdlType = serde_json::from_value(ty).unwrap();
+        let mut data: Vec<u8> = std::iter::repeat_n(1u32.to_le_bytes(), MAX_TYPE_DEPTH + 1)
+            .flatten()
+            .collect();
+        data.push(7);
+
+        let registry = registry();
+        let result = registry.programs[ANCHOR_ID].decode_type(&ty, &mut data.as_slice(), 0);
+        assert!(matches!(result, Err(IdlError::InvalidData(reason)) if reason.contains("deeply")));
+    }
+
+    #[test]
+    fn mappings_build_raw_events_from_paths_and_constants() {
+        let registry = registry();
+        assert!(registry.has_instruction_mappings(LEGACY_ID));
+        assert!(!registry.has_instruction_mappings(ANCHOR_ID));
+
+        let item = registry
+            .decode_instruction(&swap_instruction(swap_data(500, None)))
+            .unwrap()
+            .unwrap();
+        let raw_events = registry.to_raw_events(&item);
+        assert_eq!(raw_events.len(), 1);
+        let raw_event = &raw_events[0];
+        assert_eq!(raw_event.event_type, "swap_executed");
+        assert_eq!(raw_event.data["wallet"], json!(pubkey(1).to_string()));
+        assert_eq!(raw_event.data["amount"], json!(500));
+        assert_eq!(
+            raw_event.data["input_mint"],
+            json!("So11111111111111111111111111111111111111112")
+        );
+        // Paths that resolve to nothing are left out
+        assert!(!raw_event.data.contains_key("memo"));
+
+        let mut other = item.clone();
+        other.name = "otherInstruction".to_string();
+        assert!(registry.to_raw_events(&other).is_empty());
+    }
+
+    #[test]
+    fn mapping_paths_escape_pointer_characters() {
+        assert_eq!(json_pointer("data.amount"), "/data/amount");
+        assert_eq!(json_pointer("data.a/b~c"), "/data/a~1b~0c");
+
+        let item = json!({ "data": { "a/b~c": 1 } });
+        assert_eq!(item.pointer(&json_pointer("data.a/b~c")), Some(&json!(1)));
+    }
+}
@@ -1,3 +1,10 @@
 // backend/src/ingestion/mod.rs
+pub mod account_ws;
+pub mod backfill;
+pub mod idl;
 pub mod normalizer;
+pub mod price_feed;
+pub mod program_accounts;
+pub mod program_events;
+pub mod replay;
 pub mod solana_ws;
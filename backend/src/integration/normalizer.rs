@@ -1,106 +1,494 @@
 // backend/src/ingestion/normalizer.rs
-use crate::models::event::PortfolioEvent;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SolanaConfig;
+use crate::integration::idl::{DecodedItem, IdlRegistry};
+use crate::integration::replay::RECORDED_AT_KEY;
+use crate::integration::solana_ws::{decode_program_logs, ProgramSubscription};
+use crate::models::event::{ChainSource, Commitment, PortfolioEvent, TransferDirection};
+use crate::services::solana_client::TransactionInstruction;
+
+// Newest `RawEvent` schema; version 1 is the original flat format
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+type Timestamp = chrono::DateTime<chrono::Utc>;
 
 // Step 1: Event normalization service
-pub struct EventNormalizer;
+pub struct EventNormalizer {
+    programs: Vec<ProgramSubscription>, // Decoders for `logsNotification` payloads
+    idls: Arc<IdlRegistry>,             // Mappings for IDL-decoded instructions and events
+    commitment: Commitment,
+}
+
+impl Default for EventNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl EventNormalizer {
     pub fn new() -> Self {
-        Self
+        Self {
+            programs: Vec::new(),
+            idls: Arc::new(IdlRegistry::default()),
+            commitment: Commitment::Confirmed,
+        }
+    }
+
+    // Normalizer for IDL-decoded items only
+    pub fn with_idls(idls: Arc<IdlRegistry>) -> Self {
+        Self {
+            idls,
+            ..Self::new()
+        }
+    }
+
+    // Normalizer that also understands raw notifications for every configured program
+    pub fn from_config(config: &SolanaConfig, idls: Arc<IdlRegistry>) -> Self {
+        Self {
+            programs: ProgramSubscription::from_config(config, &idls),
+            idls,
+            commitment: Commitment::from_config(&config.commitment.to_lowercase()),
+        }
     }
 
-    // Step 2: Normalize raw events into standardized format
+    // Step 2: Normalize raw events into standardized format, keeping the event's own time and
+    // on-chain origin when the producer supplied them
     pub fn normalize_event(
         &self,
         raw_event: RawEvent,
     ) -> Result<PortfolioEvent, NormalizationError> {
-        match raw_event.event_type.as_str() {
-            "position_update" => self.normalize_position_update(raw_event),
-            "swap_executed" => self.normalize_swap_executed(raw_event),
-            _ => Err(NormalizationError::UnknownEventType),
+        if !(1..=CURRENT_SCHEMA_VERSION).contains(&raw_event.schema_version) {
+            return Err(NormalizationError::UnsupportedSchemaVersion(
+                raw_event.schema_version,
+            ));
+        }
+
+        let timestamp = raw_event.get_timestamp()?;
+        let mut event = match raw_event.event_type.as_str() {
+            "position_update" => self.normalize_position_update(&raw_event, timestamp)?,
+            "swap_executed" => self.normalize_swap_executed(&raw_event, timestamp)?,
+            "liquidity_added" | "liquidity_removed" => {
+                self.normalize_liquidity(&raw_event, timestamp)?
+            }
+            "transfer" => self.normalize_transfer(&raw_event, timestamp)?,
+            "stake_changed" => self.normalize_stake_changed(&raw_event, timestamp)?,
+            "fees_collected" => self.normalize_fees_collected(&raw_event, timestamp)?,
+            _ => return Err(NormalizationError::UnknownEventType),
+        };
+
+        if let Some(source) = raw_event.get_chain_source(self.commitment)? {
+            event.set_source(source);
         }
+        Ok(event)
     }
 
     // Step 3: Normalize position update events
     fn normalize_position_update(
         &self,
-        raw_event: RawEvent,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
     ) -> Result<PortfolioEvent, NormalizationError> {
-        let wallet = raw_event.get_string("wallet")?;
-        let mint = raw_event.get_string("mint")?;
-        let pnl_delta = raw_event.get_f64("pnl_delta")?;
-
         Ok(PortfolioEvent::PositionUpdate {
-            wallet,
-            mint,
-            pnl_delta,
-            timestamp: chrono::Utc::now(),
+            wallet: raw_event.get_string("wallet")?,
+            mint: raw_event.get_string("mint")?,
+            pnl_delta: raw_event.get_f64("pnl_delta")?,
+            timestamp,
+            source: None,
+            reversal: false,
         })
     }
 
     // Step 4: Normalize swap execution events
     fn normalize_swap_executed(
         &self,
-        raw_event: RawEvent,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
+    ) -> Result<PortfolioEvent, NormalizationError> {
+        Ok(PortfolioEvent::SwapExecuted {
+            wallet: raw_event.get_string("wallet")?,
+            input_mint: raw_event.get_string("input_mint")?,
+            output_mint: raw_event.get_string("output_mint")?,
+            amount: raw_event.get_u64("amount")?,
+            amount_out: raw_event.get_optional_u64("amount_out")?.unwrap_or(0),
+            timestamp,
+            source: None,
+            reversal: false,
+        })
+    }
+
+    // Step 5: Normalize liquidity add/remove events
+    fn normalize_liquidity(
+        &self,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
     ) -> Result<PortfolioEvent, NormalizationError> {
         let wallet = raw_event.get_string("wallet")?;
-        let input_mint = raw_event.get_string("input_mint")?;
-        let output_mint = raw_event.get_string("output_mint")?;
-        let amount = raw_event.get_u64("amount")?;
+        let pool = raw_event.get_string("pool")?;
+        let token_a = raw_event.get_string("token_a")?;
+        let token_b = raw_event.get_string("token_b")?;
+        let amount_a = raw_event.get_u64("amount_a")?;
+        let amount_b = raw_event.get_u64("amount_b")?;
+        let lp_tokens = raw_event.get_u64("lp_tokens")?;
 
-        Ok(PortfolioEvent::SwapExecuted {
-            wallet,
-            input_mint,
-            output_mint,
-            amount,
-            timestamp: chrono::Utc::now(),
+        Ok(if raw_event.event_type == "liquidity_added" {
+            PortfolioEvent::LiquidityAdded {
+                wallet,
+                pool,
+                token_a,
+                token_b,
+                amount_a,
+                amount_b,
+                lp_tokens,
+                timestamp,
+                source: None,
+                reversal: false,
+            }
+        } else {
+            PortfolioEvent::LiquidityRemoved {
+                wallet,
+                pool,
+                token_a,
+                token_b,
+                amount_a,
+                amount_b,
+                lp_tokens,
+                timestamp,
+                source: None,
+                reversal: false,
+            }
+        })
+    }
+
+    // Step 6: Normalize token transfer events
+    fn normalize_transfer(
+        &self,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
+    ) -> Result<PortfolioEvent, NormalizationError> {
+        let direction = match raw_event.get_string("direction")?.as_str() {
+            "in" | "incoming" => TransferDirection::Incoming,
+            "out" | "outgoing" => TransferDirection::Outgoing,
+            _ => return Err(NormalizationError::InvalidField("direction".to_string())),
+        };
+
+        Ok(PortfolioEvent::Transfer {
+            wallet: raw_event.get_string("wallet")?,
+            mint: raw_event.get_string("mint")?,
+            amount: raw_event.get_u64("amount")?,
+            direction,
+            counterparty: raw_event.get_optional_string("counterparty"),
+            timestamp,
+            source: None,
+            reversal: false,
+        })
+    }
+
+    // Step 7: Normalize stake delegation changes
+    fn normalize_stake_changed(
+        &self,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
+    ) -> Result<PortfolioEvent, NormalizationError> {
+        Ok(PortfolioEvent::StakeChanged {
+            wallet: raw_event.get_string("wallet")?,
+            stake_account: raw_event.get_string("stake_account")?,
+            validator: raw_event.get_optional_string("validator"),
+            delta_lamports: raw_event.get_i64("delta_lamports")?,
+            timestamp,
+            source: None,
+            reversal: false,
+        })
+    }
+
+    // Step 8: Normalize LP fee collection events
+    fn normalize_fees_collected(
+        &self,
+        raw_event: &RawEvent,
+        timestamp: Timestamp,
+    ) -> Result<PortfolioEvent, NormalizationError> {
+        Ok(PortfolioEvent::FeesCollected {
+            wallet: raw_event.get_string("wallet")?,
+            pool: raw_event.get_string("pool")?,
+            mint: raw_event.get_string("mint")?,
+            amount: raw_event.get_u64("amount")?,
+            timestamp,
+            source: None,
+            reversal: false,
         })
     }
+
+    // Step 9: Map an IDL-decoded instruction or event through the configured mappings.
+    // Items without a mapping yield no events.
+    pub fn normalize_decoded(
+        &self,
+        item: &DecodedItem,
+    ) -> Result<Vec<PortfolioEvent>, NormalizationError> {
+        self.idls
+            .to_raw_events(item)
+            .into_iter()
+            .map(|raw_event| self.normalize_event(raw_event))
+            .collect()
+    }
+
+    // Step 10: Normalize a raw `logsNotification` JSON-RPC message. Failed transactions and
+    // logs without program events yield no events. Notifications carry no block time, so
+    // recorded ones are dated by when they were recorded and live ones by now.
+    pub fn normalize_notification(
+        &self,
+        notification: &serde_json::Value,
+    ) -> Result<Vec<PortfolioEvent>, NormalizationError> {
+        let method = notification["method"].as_str().unwrap_or_default();
+        if method != "logsNotification" {
+            return Err(NormalizationError::InvalidNotification(format!(
+                "unsupported method '{}'",
+                method
+            )));
+        }
+
+        let result = &notification["params"]["result"];
+        let slot = result["context"]["slot"]
+            .as_u64()
+            .ok_or(NormalizationError::MissingField("context.slot".to_string()))?;
+        let value = &result["value"];
+        let signature = value["signature"]
+            .as_str()
+            .ok_or(NormalizationError::MissingField(
+                "value.signature".to_string(),
+            ))?;
+        if !value["err"].is_null() {
+            return Ok(Vec::new());
+        }
+        let recorded_at: Option<Timestamp> = notification
+            .get(RECORDED_AT_KEY)
+            .map(|at| serde_json::from_value(at.clone()))
+            .transpose()
+            .map_err(|_| NormalizationError::InvalidField(RECORDED_AT_KEY.to_string()))?;
+        let logs: Vec<&str> = value["logs"]
+            .as_array()
+            .ok_or(NormalizationError::MissingField("value.logs".to_string()))?
+            .iter()
+            .filter_map(|log| log.as_str())
+            .collect();
+
+        let decoded = decode_program_logs(&self.programs, &logs);
+        for failure in &decoded.failures {
+            tracing::warn!(
+                "⚠️ Skipping event from {}: {}",
+                failure.program_id,
+                failure.error
+            );
+        }
+        Ok(decoded
+            .events
+            .into_iter()
+            .map(|(_, mut event)| {
+                event.set_source(ChainSource {
+                    slot,
+                    signature: Some(signature.to_string()),
+                    commitment: self.commitment,
+                });
+                if let Some(recorded_at) = recorded_at {
+                    event.set_timestamp(recorded_at);
+                }
+                event
+            })
+            .collect())
+    }
+
+    // Step 11: Decode a single `Program data:` line of `program_id`, e.g. a dead-lettered one
+    pub fn normalize_program_log(
+        &self,
+        program_id: &str,
+        log: &str,
+        source: &ChainSource,
+    ) -> Result<Vec<PortfolioEvent>, NormalizationError> {
+        let program = self
+            .programs
+            .iter()
+            .find(|program| program.program_id == program_id)
+            .ok_or_else(|| NormalizationError::UnknownProgram(program_id.to_string()))?;
+        let mut events = program
+            .decoder
+            .decode_log(log)
+            .map_err(|e| NormalizationError::DecodeFailed(e.to_string()))?;
+        for event in &mut events {
+            event.set_source(source.clone());
+        }
+        Ok(events)
+    }
+
+    // Step 12: Decode and map one top-level instruction of an IDL-described program.
+    // Instructions without a mapping yield no events.
+    pub fn normalize_instruction(
+        &self,
+        instruction: &TransactionInstruction,
+        source: &ChainSource,
+    ) -> Result<Vec<PortfolioEvent>, NormalizationError> {
+        let Some(item) = self
+            .idls
+            .decode_instruction(instruction)
+            .map_err(|e| NormalizationError::DecodeFailed(e.to_string()))?
+        else {
+            return Ok(Vec::new());
+        };
+        let mut events = self.normalize_decoded(&item)?;
+        for event in &mut events {
+            event.set_source(source.clone());
+        }
+        Ok(events)
+    }
 }
 
-// Step 5: Raw event structure
+// Step 13: Raw event structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawEvent {
     pub event_type: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32, // Selects the field mapping; absent means the original format
+    #[serde(default)]
     pub data: std::collections::HashMap<String, serde_json::Value>,
 }
 
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 impl RawEvent {
+    // A v1 event with canonical field names, as before versioning
     pub fn new(event_type: String) -> Self {
         Self {
             event_type,
+            schema_version: legacy_schema_version(),
+            data: std::collections::HashMap::new(),
+        }
+    }
+
+    // A v2 event; `data` must use the v2 field names (see `key`)
+    pub fn new_v2(event_type: String) -> Self {
+        Self {
+            event_type,
+            schema_version: 2,
             data: std::collections::HashMap::new(),
         }
     }
 
-    // Step 6: Helper methods to extract typed data
-    fn get_string(&self, key: &str) -> Result<String, NormalizationError> {
+    // Step 14: Where a canonical field lives in this event's schema version.
+    // v1: flat names with an RFC 3339 `timestamp`.
+    // v2: `owner`/`token_*` names, unix `block_time`, and amounts that may be decimal strings.
+    fn key(&self, field: &'static str) -> &'static str {
+        match (self.schema_version, field) {
+            (2, "wallet") => "owner",
+            (2, "mint") => "token_mint",
+            (2, "input_mint") => "token_in",
+            (2, "output_mint") => "token_out",
+            (2, "timestamp") => "block_time",
+            _ => field,
+        }
+    }
+
+    fn get(&self, field: &'static str) -> Option<&serde_json::Value> {
         self.data
-            .get(key)
+            .get(self.key(field))
+            .filter(|value| !value.is_null())
+    }
+
+    // Step 15: Helper methods to extract typed data
+    fn get_string(&self, field: &'static str) -> Result<String, NormalizationError> {
+        self.get_optional_string(field)
+            .ok_or(NormalizationError::MissingField(
+                self.key(field).to_string(),
+            ))
+    }
+
+    fn get_optional_string(&self, field: &'static str) -> Option<String> {
+        self.get(field)
             .and_then(|v| v.as_str())
             .map(|s| s.to_string())
-            .ok_or(NormalizationError::MissingField(key.to_string()))
     }
 
-    fn get_f64(&self, key: &str) -> Result<f64, NormalizationError> {
-        self.data
-            .get(key)
-            .and_then(|v| v.as_f64())
-            .ok_or(NormalizationError::MissingField(key.to_string()))
+    fn get_f64(&self, field: &'static str) -> Result<f64, NormalizationError> {
+        self.get_number(field, |v| v.as_f64(), |s| s.parse().ok())
     }
 
-    fn get_u64(&self, key: &str) -> Result<u64, NormalizationError> {
-        self.data
-            .get(key)
-            .and_then(|v| v.as_u64())
-            .ok_or(NormalizationError::MissingField(key.to_string()))
+    fn get_u64(&self, field: &'static str) -> Result<u64, NormalizationError> {
+        self.get_number(field, |v| v.as_u64(), |s| s.parse().ok())
+    }
+
+    // Absent fields are `None`; present ones must still parse
+    fn get_optional_u64(&self, field: &'static str) -> Result<Option<u64>, NormalizationError> {
+        self.get(field).map(|_| self.get_u64(field)).transpose()
+    }
+
+    fn get_i64(&self, field: &'static str) -> Result<i64, NormalizationError> {
+        self.get_number(field, |v| v.as_i64(), |s| s.parse().ok())
+    }
+
+    // Numbers may arrive as JSON numbers or, for values past 2^53, as decimal strings
+    fn get_number<T>(
+        &self,
+        field: &'static str,
+        from_number: impl Fn(&serde_json::Value) -> Option<T>,
+        from_str: impl Fn(&str) -> Option<T>,
+    ) -> Result<T, NormalizationError> {
+        let value = self.get(field).ok_or(NormalizationError::MissingField(
+            self.key(field).to_string(),
+        ))?;
+        from_number(value)
+            .or_else(|| value.as_str().and_then(&from_str))
+            .ok_or(NormalizationError::InvalidField(
+                self.key(field).to_string(),
+            ))
+    }
+
+    // Step 16: Source metadata; events without a timestamp are stamped on arrival
+    fn get_timestamp(&self) -> Result<Timestamp, NormalizationError> {
+        let Some(value) = self.get("timestamp") else {
+            return Ok(chrono::Utc::now());
+        };
+        let invalid = || NormalizationError::InvalidField(self.key("timestamp").to_string());
+
+        if let Some(seconds) = value.as_i64() {
+            return chrono::DateTime::from_timestamp(seconds, 0).ok_or_else(invalid);
+        }
+        value
+            .as_str()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+            .ok_or_else(invalid)
+    }
+
+    fn get_chain_source(
+        &self,
+        default_commitment: Commitment,
+    ) -> Result<Option<ChainSource>, NormalizationError> {
+        if self.get("slot").is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(ChainSource {
+            slot: self.get_u64("slot")?,
+            signature: self.get_optional_string("signature"),
+            commitment: self
+                .get_optional_string("commitment")
+                .map(|commitment| Commitment::from_config(&commitment))
+                .unwrap_or(default_commitment),
+        }))
     }
 }
 
-// Step 7: Normalization error types
+// Step 17: Normalization error types
 #[derive(Debug)]
 pub enum NormalizationError {
     UnknownEventType,
     MissingField(String),
+    InvalidField(String),
+    UnsupportedSchemaVersion(u32),
+    InvalidNotification(String),
+    UnknownProgram(String),
+    DecodeFailed(String),
 }
 
 impl std::fmt::Display for NormalizationError {
@@ -108,8 +496,175 @@ impl std::fmt::Display for NormalizationError {
         match self {
             Self::UnknownEventType => write!(f, "Unknown event type"),
             Self::MissingField(field) => write!(f, "Missing field: {}", field),
+            Self::InvalidField(field) => write!(f, "Invalid field: {}", field),
+            Self::UnsupportedSchemaVersion(version) => {
+                write!(f, "Unsupported schema version: {}", version)
+            }
+            Self::InvalidNotification(reason) => write!(f, "Invalid notification: {}", reason),
+            Self::UnknownProgram(program_id) => write!(f, "Unknown program: {}", program_id),
+            Self::DecodeFailed(reason) => write!(f, "Decode failed: {}", reason),
         }
     }
 }
 
 impl std::error::Error for NormalizationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(mut raw_event: RawEvent, data: serde_json::Value) -> RawEvent {
+        let serde_json::Value::Object(fields) = data else {
+            panic!("event data must be an object");
+        };
+        raw_event.data.extend(fields);
+        raw_event
+    }
+
+    fn normalize(raw_event: RawEvent) -> Result<PortfolioEvent, NormalizationError> {
+        EventNormalizer::new().normalize_event(raw_event)
+    }
+
+    #[test]
+    fn v1_and_v2_swaps_normalize_alike() {
+        let v1 = raw(
+            RawEvent::new("swap_executed".to_string()),
+            serde_json::json!({
+                "wallet": "wallet",
+                "input_mint": "mint_in",
+                "output_mint": "mint_out",
+                "amount": 100,
+                "amount_out": 95,
+                "timestamp": "2024-01-01T00:00:00Z",
+            }),
+        );
+        let v2 = raw(
+            RawEvent::new_v2("swap_executed".to_string()),
+            serde_json::json!({
+                "owner": "wallet",
+                "token_in": "mint_in",
+                "token_out": "mint_out",
+                "amount": "100",
+                "amount_out": 95,
+                "block_time": 1_704_067_200,
+            }),
+        );
+
+        for raw_event in [v1, v2] {
+            let PortfolioEvent::SwapExecuted {
+                wallet,
+                input_mint,
+                output_mint,
+                amount,
+                amount_out,
+                timestamp,
+                ..
+            } = normalize(raw_event).unwrap()
+            else {
+                panic!("expected a swap");
+            };
+            assert_eq!(wallet, "wallet");
+            assert_eq!(
+                (input_mint.as_str(), output_mint.as_str()),
+                ("mint_in", "mint_out")
+            );
+            assert_eq!((amount, amount_out), (100, 95));
+            assert_eq!(timestamp.timestamp(), 1_704_067_200);
+        }
+    }
+
+    #[test]
+    fn v2_maps_token_mint_and_reads_numbers_from_strings() {
+        let transfer = raw(
+            RawEvent::new_v2("transfer".to_string()),
+            serde_json::json!({
+                "owner": "wallet",
+                "token_mint": "mint",
+                "amount": "18446744073709551615",
+                "direction": "in",
+            }),
+        );
+        let PortfolioEvent::Transfer {
+            wallet,
+            mint,
+            amount,
+            ..
+        } = normalize(transfer).unwrap()
+        else {
+            panic!("expected a transfer");
+        };
+        assert_eq!((wallet.as_str(), mint.as_str()), ("wallet", "mint"));
+        assert_eq!(amount, u64::MAX);
+
+        let stake = raw(
+            RawEvent::new_v2("stake_changed".to_string()),
+            serde_json::json!({
+                "owner": "wallet",
+                "stake_account": "stake",
+                "delta_lamports": "-5000",
+            }),
+        );
+        let PortfolioEvent::StakeChanged { delta_lamports, .. } = normalize(stake).unwrap() else {
+            panic!("expected a stake change");
+        };
+        assert_eq!(delta_lamports, -5000);
+
+        let garbled = raw(
+            RawEvent::new_v2("transfer".to_string()),
+            serde_json::json!({
+                "owner": "wallet",
+                "token_mint": "mint",
+                "amount": "lots",
+                "direction": "in",
+            }),
+        );
+        assert!(matches!(
+            normalize(garbled),
+            Err(NormalizationError::InvalidField(field)) if field == "amount"
+        ));
+    }
+
+    #[test]
+    fn events_without_a_timestamp_are_stamped_on_arrival() {
+        let before = chrono::Utc::now();
+        let event = normalize(raw(
+            RawEvent::new_v2("position_update".to_string()),
+            serde_json::json!({ "owner": "wallet", "token_mint": "mint", "pnl_delta": 1.5 }),
+        ))
+        .unwrap();
+        assert!(*event.timestamp() >= before);
+        assert!(*event.timestamp() <= chrono::Utc::now());
+    }
+
+    #[test]
+    fn constructors_pick_the_schema_their_field_names_belong_to() {
+        let v2_names =
+            serde_json::json!({ "owner": "wallet", "token_mint": "mint", "pnl_delta": 1.0 });
+
+        assert_eq!(
+            RawEvent::new("position_update".to_string()).schema_version,
+            1
+        );
+        assert!(matches!(
+            normalize(raw(RawEvent::new("position_update".to_string()), v2_names.clone())),
+            Err(NormalizationError::MissingField(field)) if field == "wallet"
+        ));
+        assert!(normalize(raw(
+            RawEvent::new_v2("position_update".to_string()),
+            v2_names
+        ))
+        .is_ok());
+
+        // Producers from before versioning send no schema_version
+        let legacy: RawEvent =
+            serde_json::from_str(r#"{"event_type":"position_update","data":{}}"#).unwrap();
+        assert_eq!(legacy.schema_version, 1);
+
+        let mut future = RawEvent::new_v2("position_update".to_string());
+        future.schema_version = CURRENT_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            normalize(future),
+            Err(NormalizationError::UnsupportedSchemaVersion(_))
+        ));
+    }
+}
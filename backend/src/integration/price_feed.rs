@@ -0,0 +1,98 @@
+// backend/src/integration/price_feed.rs
+use std::collections::HashMap;
+
+use reqwest::Client;
+
+use crate::config::Config;
+use crate::models::event::PortfolioEvent;
+use crate::pipeline::mpsc_queue::{EventQueue, QueueError};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Step 1: Polls USD prices of the configured mints and publishes them as `PriceUpdated`
+pub struct PriceFeed {
+    client: Client,
+    url: String,
+    mints: Vec<String>,
+    poll_interval: std::time::Duration,
+    event_tx: EventQueue,
+}
+
+impl PriceFeed {
+    pub fn new(config: &Config, event_tx: EventQueue) -> Self {
+        Self {
+            client: Client::new(),
+            url: config.price_feed.url.clone(),
+            mints: config.price_feed.mints.clone(),
+            poll_interval: config.price_feed.poll_interval(),
+            event_tx,
+        }
+    }
+
+    // Step 2: Poll on a fixed interval until the pipeline closes
+    pub async fn run(self) {
+        tracing::info!(
+            "💲 Starting price feed for {} mint(s) (interval: {:?})",
+            self.mints.len(),
+            self.poll_interval
+        );
+
+        let mut interval = tokio::time::interval(self.poll_interval);
+        loop {
+            interval.tick().await;
+            let prices = match self.fetch_prices().await {
+                Ok(prices) => prices,
+                Err(e) => {
+                    tracing::warn!("⚠️ Price feed request failed: {}", e);
+                    continue;
+                }
+            };
+
+            let timestamp = chrono::Utc::now();
+            for (mint, price_usd) in prices {
+                let event = PortfolioEvent::PriceUpdated {
+                    mint,
+                    price_usd,
+                    timestamp,
+                };
+                match self.event_tx.send(event).await {
+                    Ok(()) => {}
+                    Err(QueueError::Closed) => {
+                        tracing::info!("💲 Pipeline closed, stopping price feed");
+                        return;
+                    }
+                    Err(e) => tracing::warn!("⚠️ Dropped price update: {}", e),
+                }
+            }
+        }
+    }
+
+    // Step 3: Fetch one price per mint. The endpoint lowercases addresses in its response, so
+    // they are matched back to the configured mints case-insensitively.
+    async fn fetch_prices(&self) -> Result<Vec<(String, f64)>, ThreadSafeError> {
+        let response = self
+            .client
+            .get(&self.url)
+            .query(&[
+                ("contract_addresses", self.mints.join(",")),
+                ("vs_currencies", "usd".to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+        let body: HashMap<String, HashMap<String, f64>> = response.json().await?;
+
+        let prices = body
+            .into_iter()
+            .filter_map(|(address, quotes)| {
+                let mint = self
+                    .mints
+                    .iter()
+                    .find(|mint| mint.eq_ignore_ascii_case(&address))?;
+                let price = quotes.get("usd").copied().filter(|price| *price > 0.0)?;
+                Some((mint.clone(), price))
+            })
+            .collect();
+        Ok(prices)
+    }
+}
@@ -0,0 +1,735 @@
+// backend/src/ingestion/program_accounts.rs
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+// Step 1: Anchor discriminator for an account/event name (sha256("<namespace>:<name>")[..8])
+pub fn anchor_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = solana_program::hash::hash(format!("{}:{}", namespace, name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+    discriminator
+}
+
+// Step 2: Decoded portfolio-program `Position`, normalized across layout versions
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionAccount {
+    pub version: u8,
+    pub owner: String,
+    pub pnl: i64,
+    pub trade_count: u64,
+    pub mint: Option<String>, // Only recorded from v2
+}
+
+// Step 3: Decoded amm-pool `PoolState`, normalized across layout versions
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStateAccount {
+    pub version: u8,
+    pub token_a: String,
+    pub token_b: String,
+    pub lp_mint: String,
+    pub fee_rate_bps: u64,
+    pub total_liquidity: u64,
+    pub authority_bump: u8,
+    pub twap: Option<TwapOracle>, // Only present from v2
+    pub vault_a: Option<String>,  // Only recorded from v3
+    pub vault_b: Option<String>,
+}
+
+impl PoolStateAccount {
+    // The pool authority PDA owns both vaults; seeds are just the pool address
+    pub fn authority_address(pool: &Pubkey, amm_pool_program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[pool.as_ref()], amm_pool_program_id).0
+    }
+}
+
+// Step 4: amm-pool TWAP oracle (programs/amm-pool/src/twap.rs)
+pub const TWAP_WINDOW_SECS: i64 = 600;
+
+#[derive(Debug, Clone, Copy, Serialize, BorshDeserialize)]
+pub struct TwapCheckpoint {
+    pub cumulative: u128,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, BorshDeserialize)]
+pub struct TwapOracle {
+    pub last_price: u64, // Q32.32 token B per token A
+    pub price_cumulative: u128,
+    pub last_update_ts: i64,
+    pub checkpoints: [TwapCheckpoint; 2],
+}
+
+impl TwapOracle {
+    // Same computation the program runs when executing trigger orders
+    pub fn twap(&self, now: i64) -> Option<u64> {
+        let start = self.checkpoints[0];
+        let elapsed = now.checked_sub(start.timestamp)?;
+        if self.last_update_ts == 0 || elapsed < TWAP_WINDOW_SECS {
+            return None;
+        }
+        let held = now.saturating_sub(self.last_update_ts).max(0) as u128;
+        let cumulative = self
+            .price_cumulative
+            .saturating_add((self.last_price as u128).saturating_mul(held));
+        let delta = cumulative.checked_sub(start.cumulative)?;
+        Some((delta / elapsed as u128).min(u64::MAX as u128) as u64)
+    }
+}
+
+// Step 5: Decoded portfolio-program `TriggerOrder`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, BorshDeserialize)]
+pub enum TriggerKind {
+    StopLoss,
+    TakeProfit,
+}
+
+impl TriggerKind {
+    pub fn is_triggered(&self, twap: u64, trigger_price: u64) -> bool {
+        match self {
+            TriggerKind::StopLoss => twap <= trigger_price,
+            TriggerKind::TakeProfit => twap >= trigger_price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerOrderAccount {
+    pub version: u8,
+    pub owner: String,
+    pub order_id: u64,
+    pub pool: String,
+    pub mint: String,
+    pub destination: String,
+    pub kind: TriggerKind,
+    pub trigger_price: u64,
+    pub size: u64,
+    pub min_out: u64,
+    pub bounty_lamports: u64,
+}
+
+// Step 6: On-chain layouts, mirrored from programs/*/src (borsh, after the discriminator)
+#[derive(BorshDeserialize)]
+struct PositionV0 {
+    owner: [u8; 32],
+    pnl: i64,
+    trade_count: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct PositionV1 {
+    _version: u8,
+    owner: [u8; 32],
+    pnl: i64,
+    trade_count: u64,
+    _reserved: [u8; 64],
+}
+
+#[derive(BorshDeserialize)]
+struct PositionV2 {
+    _version: u8,
+    owner: [u8; 32],
+    pnl: i64,
+    trade_count: u64,
+    mint: [u8; 32],
+    _reserved: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct PoolStateV0 {
+    token_a: [u8; 32],
+    token_b: [u8; 32],
+    lp_mint: [u8; 32],
+    fee_rate_bps: u64,
+    total_liquidity: u64,
+    authority_bump: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct PoolStateV1 {
+    _version: u8,
+    token_a: [u8; 32],
+    token_b: [u8; 32],
+    lp_mint: [u8; 32],
+    fee_rate_bps: u64,
+    total_liquidity: u64,
+    authority_bump: u8,
+    _reserved: [u8; 64],
+}
+
+#[derive(BorshDeserialize)]
+struct PoolStateV2 {
+    _version: u8,
+    token_a: [u8; 32],
+    token_b: [u8; 32],
+    lp_mint: [u8; 32],
+    fee_rate_bps: u64,
+    total_liquidity: u64,
+    authority_bump: u8,
+    twap: TwapOracle,
+    _reserved: [u8; 64],
+}
+
+#[derive(BorshDeserialize)]
+struct PoolStateV3 {
+    _version: u8,
+    token_a: [u8; 32],
+    token_b: [u8; 32],
+    lp_mint: [u8; 32],
+    fee_rate_bps: u64,
+    total_liquidity: u64,
+    authority_bump: u8,
+    twap: TwapOracle,
+    vault_a: [u8; 32],
+    vault_b: [u8; 32],
+    _reserved: [u8; 64],
+}
+
+#[derive(BorshDeserialize)]
+struct TriggerOrderV1 {
+    _version: u8,
+    owner: [u8; 32],
+    order_id: u64,
+    pool: [u8; 32],
+    mint: [u8; 32],
+    destination: [u8; 32],
+    kind: TriggerKind,
+    trigger_price: u64,
+    size: u64,
+    min_out: u64,
+    bounty_lamports: u64,
+    _bump: u8,
+    _escrow_bump: u8,
+    _reserved: [u8; 32],
+}
+
+impl PositionAccount {
+    // Unversioned accounts are recognised by their exact allocation size
+    pub const V0_LEN: usize = 8 + 32 + 8 + 8;
+
+    // Step 7: Decode raw account data of any known Position version
+    pub fn decode(data: &[u8]) -> Result<Self, AccountDecodeError> {
+        let body = strip_discriminator(data, "Position")?;
+
+        if data.len() == Self::V0_LEN {
+            let v0 = PositionV0::deserialize(&mut &body[..])?;
+            return Ok(Self {
+                version: 0,
+                owner: pubkey_string(v0.owner),
+                pnl: v0.pnl,
+                trade_count: v0.trade_count,
+                mint: None,
+            });
+        }
+
+        match body.first().copied() {
+            Some(1) => {
+                let v1 = PositionV1::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 1,
+                    owner: pubkey_string(v1.owner),
+                    pnl: v1.pnl,
+                    trade_count: v1.trade_count,
+                    mint: None,
+                })
+            }
+            Some(2) => {
+                let v2 = PositionV2::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 2,
+                    owner: pubkey_string(v2.owner),
+                    pnl: v2.pnl,
+                    trade_count: v2.trade_count,
+                    mint: Some(pubkey_string(v2.mint)),
+                })
+            }
+            Some(version) => Err(AccountDecodeError::UnsupportedVersion(version)),
+            None => Err(AccountDecodeError::InvalidData(
+                "empty account body".to_string(),
+            )),
+        }
+    }
+}
+
+impl PoolStateAccount {
+    // The unversioned layout over-allocated one pubkey; its size is still unique
+    pub const V0_LEN: usize = 8 + (32 * 4) + (8 * 2) + 1;
+
+    // Step 8: Decode raw account data of any known PoolState version
+    pub fn decode(data: &[u8]) -> Result<Self, AccountDecodeError> {
+        let body = strip_discriminator(data, "PoolState")?;
+
+        if data.len() == Self::V0_LEN {
+            let v0 = PoolStateV0::deserialize(&mut &body[..])?;
+            return Ok(Self {
+                version: 0,
+                token_a: pubkey_string(v0.token_a),
+                token_b: pubkey_string(v0.token_b),
+                lp_mint: pubkey_string(v0.lp_mint),
+                fee_rate_bps: v0.fee_rate_bps,
+                total_liquidity: v0.total_liquidity,
+                authority_bump: v0.authority_bump,
+                twap: None,
+                vault_a: None,
+                vault_b: None,
+            });
+        }
+
+        match body.first().copied() {
+            Some(1) => {
+                let v1 = PoolStateV1::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 1,
+                    token_a: pubkey_string(v1.token_a),
+                    token_b: pubkey_string(v1.token_b),
+                    lp_mint: pubkey_string(v1.lp_mint),
+                    fee_rate_bps: v1.fee_rate_bps,
+                    total_liquidity: v1.total_liquidity,
+                    authority_bump: v1.authority_bump,
+                    twap: None,
+                    vault_a: None,
+                    vault_b: None,
+                })
+            }
+            Some(2) => {
+                let v2 = PoolStateV2::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 2,
+                    token_a: pubkey_string(v2.token_a),
+                    token_b: pubkey_string(v2.token_b),
+                    lp_mint: pubkey_string(v2.lp_mint),
+                    fee_rate_bps: v2.fee_rate_bps,
+                    total_liquidity: v2.total_liquidity,
+                    authority_bump: v2.authority_bump,
+                    twap: Some(v2.twap),
+                    vault_a: None,
+                    vault_b: None,
+                })
+            }
+            Some(3) => {
+                let v3 = PoolStateV3::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 3,
+                    token_a: pubkey_string(v3.token_a),
+                    token_b: pubkey_string(v3.token_b),
+                    lp_mint: pubkey_string(v3.lp_mint),
+                    fee_rate_bps: v3.fee_rate_bps,
+                    total_liquidity: v3.total_liquidity,
+                    authority_bump: v3.authority_bump,
+                    twap: Some(v3.twap),
+                    vault_a: Some(pubkey_string(v3.vault_a)),
+                    vault_b: Some(pubkey_string(v3.vault_b)),
+                })
+            }
+            Some(version) => Err(AccountDecodeError::UnsupportedVersion(version)),
+            None => Err(AccountDecodeError::InvalidData(
+                "empty account body".to_string(),
+            )),
+        }
+    }
+}
+
+impl TriggerOrderAccount {
+    // Step 9: Decode raw TriggerOrder account data
+    pub fn decode(data: &[u8]) -> Result<Self, AccountDecodeError> {
+        let body = strip_discriminator(data, "TriggerOrder")?;
+
+        match body.first().copied() {
+            Some(1) => {
+                let v1 = TriggerOrderV1::deserialize(&mut &body[..])?;
+                Ok(Self {
+                    version: 1,
+                    owner: pubkey_string(v1.owner),
+                    order_id: v1.order_id,
+                    pool: pubkey_string(v1.pool),
+                    mint: pubkey_string(v1.mint),
+                    destination: pubkey_string(v1.destination),
+                    kind: v1.kind,
+                    trigger_price: v1.trigger_price,
+                    size: v1.size,
+                    min_out: v1.min_out,
+                    bounty_lamports: v1.bounty_lamports,
+                })
+            }
+            Some(version) => Err(AccountDecodeError::UnsupportedVersion(version)),
+            None => Err(AccountDecodeError::InvalidData(
+                "empty account body".to_string(),
+            )),
+        }
+    }
+}
+
+// Step 10: Helpers
+fn strip_discriminator<'a>(
+    data: &'a [u8],
+    account_name: &str,
+) -> Result<&'a [u8], AccountDecodeError> {
+    if data.len() < 8 || data[..8] != anchor_discriminator("account", account_name) {
+        return Err(AccountDecodeError::DiscriminatorMismatch(
+            account_name.to_string(),
+        ));
+    }
+    Ok(&data[8..])
+}
+
+fn pubkey_string(bytes: [u8; 32]) -> String {
+    Pubkey::new_from_array(bytes).to_string()
+}
+
+// Step 11: Account decoding error types
+#[derive(Debug)]
+pub enum AccountDecodeError {
+    DiscriminatorMismatch(String),
+    UnsupportedVersion(u8),
+    InvalidData(String),
+}
+
+impl std::fmt::Display for AccountDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DiscriminatorMismatch(name) => write!(f, "Account is not a {}", name),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported account version: {}", version)
+            }
+            Self::InvalidData(reason) => write!(f, "Invalid account data: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AccountDecodeError {}
+
+impl From<std::io::Error> for AccountDecodeError {
+    fn from(e: std::io::Error) -> Self {
+        Self::InvalidData(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    // Program-side layouts (programs/*/src), serialized the way Anchor writes them
+    #[derive(BorshSerialize)]
+    struct PositionV0Layout {
+        owner: [u8; 32],
+        pnl: i64,
+        trade_count: u64,
+    }
+
+    // v1 and v2 share the current `Position`; v1 leaves the mint at its default
+    #[derive(BorshSerialize)]
+    struct PositionLayout {
+        version: u8,
+        owner: [u8; 32],
+        pnl: i64,
+        trade_count: u64,
+        mint: [u8; 32],
+        reserved: [u8; 32],
+    }
+
+    #[derive(BorshSerialize)]
+    struct PoolStateV0Layout {
+        token_a: [u8; 32],
+        token_b: [u8; 32],
+        lp_mint: [u8; 32],
+        fee_rate_bps: u64,
+        total_liquidity: u64,
+        authority_bump: u8,
+    }
+
+    #[derive(BorshSerialize)]
+    struct PoolStateV1Layout {
+        version: u8,
+        token_a: [u8; 32],
+        token_b: [u8; 32],
+        lp_mint: [u8; 32],
+        fee_rate_bps: u64,
+        total_liquidity: u64,
+        authority_bump: u8,
+        reserved: [u8; 64],
+    }
+
+    #[derive(BorshSerialize)]
+    struct PoolStateV2Layout {
+        version: u8,
+        token_a: [u8; 32],
+        token_b: [u8; 32],
+        lp_mint: [u8; 32],
+        fee_rate_bps: u64,
+        total_liquidity: u64,
+        authority_bump: u8,
+        twap: TwapLayout,
+        reserved: [u8; 64],
+    }
+
+    #[derive(BorshSerialize)]
+    struct PoolStateV3Layout {
+        version: u8,
+        token_a: [u8; 32],
+        token_b: [u8; 32],
+        lp_mint: [u8; 32],
+        fee_rate_bps: u64,
+        total_liquidity: u64,
+        authority_bump: u8,
+        twap: TwapLayout,
+        vault_a: [u8; 32],
+        vault_b: [u8; 32],
+        reserved: [u8; 64],
+    }
+
+    #[derive(BorshSerialize)]
+    struct TwapLayout {
+        last_price: u64,
+        price_cumulative: u128,
+        last_update_ts: i64,
+        checkpoints: [(u128, i64); 2],
+    }
+
+    #[derive(BorshSerialize)]
+    struct TriggerOrderLayout {
+        version: u8,
+        owner: [u8; 32],
+        order_id: u64,
+        pool: [u8; 32],
+        mint: [u8; 32],
+        destination: [u8; 32],
+        kind: u8,
+        trigger_price: u64,
+        size: u64,
+        min_out: u64,
+        bounty_lamports: u64,
+        bump: u8,
+        escrow_bump: u8,
+        reserved: [u8; 32],
+    }
+
+    fn account(name: &str, layout: &impl BorshSerialize) -> Vec<u8> {
+        let mut data = anchor_discriminator("account", name).to_vec();
+        layout.serialize(&mut data).unwrap();
+        data
+    }
+
+    fn key(byte: u8) -> String {
+        pubkey_string([byte; 32])
+    }
+
+    fn position(version: u8, mint: [u8; 32]) -> Vec<u8> {
+        account(
+            "Position",
+            &PositionLayout {
+                version,
+                owner: [1; 32],
+                pnl: -42,
+                trade_count: 7,
+                mint,
+                reserved: [0; 32],
+            },
+        )
+    }
+
+    fn twap() -> TwapLayout {
+        TwapLayout {
+            last_price: 3 << 32,
+            price_cumulative: 1_000,
+            last_update_ts: 1_700_000_600,
+            checkpoints: [(0, 1_700_000_000), (500, 1_700_000_300)],
+        }
+    }
+
+    fn assert_pool_fields(pool: &PoolStateAccount, version: u8) {
+        assert_eq!(pool.version, version);
+        assert_eq!(
+            (
+                pool.token_a.as_str(),
+                pool.token_b.as_str(),
+                pool.lp_mint.as_str()
+            ),
+            (key(1).as_str(), key(2).as_str(), key(3).as_str())
+        );
+        assert_eq!((pool.fee_rate_bps, pool.total_liquidity), (30, 1_000_000));
+        assert_eq!(pool.authority_bump, 254);
+    }
+
+    #[test]
+    fn positions_decode_in_every_version() {
+        let mut v0 = account(
+            "Position",
+            &PositionV0Layout {
+                owner: [1; 32],
+                pnl: -42,
+                trade_count: 7,
+            },
+        );
+        assert_eq!(v0.len(), PositionAccount::V0_LEN);
+        let v1 = position(1, [0; 32]);
+        let v2 = position(2, [5; 32]);
+
+        for (data, version, mint) in [(&v0, 0, None), (&v1, 1, None), (&v2, 2, Some(key(5)))] {
+            let position = PositionAccount::decode(data).unwrap();
+            assert_eq!(position.version, version);
+            assert_eq!(position.owner, key(1));
+            assert_eq!((position.pnl, position.trade_count), (-42, 7));
+            assert_eq!(position.mint, mint);
+        }
+
+        // One byte more and the unversioned layout no longer matches by length
+        v0.push(0);
+        assert!(PositionAccount::decode(&v0).is_err());
+    }
+
+    #[test]
+    fn pool_states_decode_in_every_version() {
+        // The unversioned layout was allocated one pubkey larger than it holds
+        let mut v0 = account(
+            "PoolState",
+            &PoolStateV0Layout {
+                token_a: [1; 32],
+                token_b: [2; 32],
+                lp_mint: [3; 32],
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+            },
+        );
+        v0.resize(PoolStateAccount::V0_LEN, 0);
+        let pool = PoolStateAccount::decode(&v0).unwrap();
+        assert_pool_fields(&pool, 0);
+        assert!(pool.twap.is_none() && pool.vault_a.is_none());
+
+        let v1 = account(
+            "PoolState",
+            &PoolStateV1Layout {
+                version: 1,
+                token_a: [1; 32],
+                token_b: [2; 32],
+                lp_mint: [3; 32],
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+                reserved: [0; 64],
+            },
+        );
+        let pool = PoolStateAccount::decode(&v1).unwrap();
+        assert_pool_fields(&pool, 1);
+        assert!(pool.twap.is_none() && pool.vault_a.is_none());
+
+        let v2 = account(
+            "PoolState",
+            &PoolStateV2Layout {
+                version: 2,
+                token_a: [1; 32],
+                token_b: [2; 32],
+                lp_mint: [3; 32],
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+                twap: twap(),
+                reserved: [0; 64],
+            },
+        );
+        let pool = PoolStateAccount::decode(&v2).unwrap();
+        assert_pool_fields(&pool, 2);
+        let oracle = pool.twap.unwrap();
+        assert_eq!(oracle.last_price, 3 << 32);
+        assert_eq!(oracle.checkpoints[1].cumulative, 500);
+        assert!(pool.vault_a.is_none());
+
+        let v3 = account(
+            "PoolState",
+            &PoolStateV3Layout {
+                version: 3,
+                token_a: [1; 32],
+                token_b: [2; 32],
+                lp_mint: [3; 32],
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+                twap: twap(),
+                vault_a: [6; 32],
+                vault_b: [7; 32],
+                reserved: [0; 64],
+            },
+        );
+        let pool = PoolStateAccount::decode(&v3).unwrap();
+        assert_pool_fields(&pool, 3);
+        assert_eq!(pool.twap.unwrap().last_update_ts, 1_700_000_600);
+        assert_eq!(pool.vault_a, Some(key(6)));
+        assert_eq!(pool.vault_b, Some(key(7)));
+    }
+
+    #[test]
+    fn trigger_orders_decode() {
+        let layout = TriggerOrderLayout {
+            version: 1,
+            owner: [1; 32],
+            order_id: 9,
+            pool: [2; 32],
+            mint: [3; 32],
+            destination: [4; 32],
+            kind: 1,
+            trigger_price: 5 << 32,
+            size: 1_000,
+            min_out: 900,
+            bounty_lamports: 5_000,
+            bump: 255,
+            escrow_bump: 254,
+            reserved: [0; 32],
+        };
+        let order = TriggerOrderAccount::decode(&account("TriggerOrder", &layout)).unwrap();
+        assert_eq!(order.version, 1);
+        assert_eq!(order.owner, key(1));
+        assert_eq!(order.order_id, 9);
+        assert_eq!(
+            (order.pool, order.mint, order.destination),
+            (key(2), key(3), key(4))
+        );
+        assert_eq!(order.kind, TriggerKind::TakeProfit);
+        assert_eq!(order.trigger_price, 5 << 32);
+        assert_eq!(
+            (order.size, order.min_out, order.bounty_lamports),
+            (1_000, 900, 5_000)
+        );
+    }
+
+    #[test]
+    fn unknown_versions_and_foreign_accounts_are_refused() {
+        assert!(matches!(
+            PositionAccount::decode(&position(9, [0; 32])),
+            Err(AccountDecodeError::UnsupportedVersion(9))
+        ));
+
+        let mut pool = position(3, [0; 32]);
+        pool[..8].copy_from_slice(&anchor_discriminator("account", "PoolState"));
+        pool[8] = 4;
+        assert!(matches!(
+            PoolStateAccount::decode(&pool),
+            Err(AccountDecodeError::UnsupportedVersion(4))
+        ));
+
+        let mut order = anchor_discriminator("account", "TriggerOrder").to_vec();
+        order.push(2);
+        assert!(matches!(
+            TriggerOrderAccount::decode(&order),
+            Err(AccountDecodeError::UnsupportedVersion(2))
+        ));
+
+        // A Position is not a PoolState, and too little data is no account at all
+        assert!(matches!(
+            PoolStateAccount::decode(&position(2, [0; 32])),
+            Err(AccountDecodeError::DiscriminatorMismatch(name)) if name == "PoolState"
+        ));
+        assert!(matches!(
+            TriggerOrderAccount::decode(&[0; 4]),
+            Err(AccountDecodeError::DiscriminatorMismatch(_))
+        ));
+
+        // Right discriminator and version, body cut short
+        let mut truncated = position(2, [5; 32]);
+        truncated.truncate(40);
+        assert!(matches!(
+            PositionAccount::decode(&truncated),
+            Err(AccountDecodeError::InvalidData(_))
+        ));
+    }
+}
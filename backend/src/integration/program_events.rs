@@ -0,0 +1,408 @@
+// backend/src/ingestion/program_events.rs
+use base64::engine::general_purpose::STANDARD as base64_standard;
+use base64::Engine as _;
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::integration::program_accounts::{anchor_discriminator, TriggerKind};
+use crate::models::event::PortfolioEvent;
+
+// Anchor writes `emit!` payloads as base64 after this prefix
+pub const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+// Step 1: portfolio-program events, mirrored from programs/portfolio-program/src/processor.rs
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct PositionOpened {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub position: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub mint: [u8; 32],
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct PositionClosed {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub position: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub mint: [u8; 32],
+    pub final_pnl: i64,
+    pub trade_count: u64,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct PnlUpdated {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub position: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub mint: [u8; 32],
+    pub pnl_delta: i64,
+    pub new_pnl: i64,
+    pub portfolio_pnl: i64,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct TradeRecorded {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub position: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub mint: [u8; 32],
+    pub trade_id: u64,
+    pub trade_number: u64,
+    pub amount: u64,
+    pub notional: u64,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct TriggerOrderPlaced {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub order: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub pool: [u8; 32],
+    pub kind: TriggerKind,
+    pub trigger_price: u64,
+    pub size: u64,
+    pub bounty_lamports: u64,
+}
+
+#[derive(Debug, Clone, Serialize, BorshDeserialize)]
+pub struct TriggerOrderExecuted {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub order: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub keeper: [u8; 32],
+    pub kind: TriggerKind,
+    pub twap: u64,
+    pub size: u64,
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub input_mint: [u8; 32],
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub output_mint: [u8; 32],
+    pub amount_out: u64,
+}
+
+// Step 2: Any decoded program event
+#[derive(Debug, Clone, Serialize)]
+pub enum ProgramEvent {
+    PositionOpened(PositionOpened),
+    PositionClosed(PositionClosed),
+    PnlUpdated(PnlUpdated),
+    TradeRecorded(TradeRecorded),
+    TriggerOrderPlaced(TriggerOrderPlaced),
+    TriggerOrderExecuted(TriggerOrderExecuted),
+}
+
+impl ProgramEvent {
+    // Step 3: Decode a single log line; lines that are not `Program data:` are ignored
+    pub fn decode_log(log: &str) -> Result<Option<Self>, EventDecodeError> {
+        let Some(encoded) = log.strip_prefix(PROGRAM_DATA_PREFIX) else {
+            return Ok(None);
+        };
+        let payload = base64_standard
+            .decode(encoded.trim())
+            .map_err(|e| EventDecodeError::InvalidBase64(e.to_string()))?;
+        Self::decode(&payload)
+    }
+
+    // Step 4: Match the 8-byte event discriminator and deserialize the body.
+    // Events from other programs in the same transaction yield `None`.
+    pub fn decode(payload: &[u8]) -> Result<Option<Self>, EventDecodeError> {
+        if payload.len() < 8 {
+            return Err(EventDecodeError::PayloadTooShort(payload.len()));
+        }
+        let (discriminator, mut body) = payload.split_at(8);
+
+        let event = if discriminator == event_discriminator("PositionOpened") {
+            Self::PositionOpened(deserialize("PositionOpened", &mut body)?)
+        } else if discriminator == event_discriminator("PositionClosed") {
+            Self::PositionClosed(deserialize("PositionClosed", &mut body)?)
+        } else if discriminator == event_discriminator("PnlUpdated") {
+            Self::PnlUpdated(deserialize("PnlUpdated", &mut body)?)
+        } else if discriminator == event_discriminator("TradeRecorded") {
+            Self::TradeRecorded(deserialize("TradeRecorded", &mut body)?)
+        } else if discriminator == event_discriminator("TriggerOrderPlaced") {
+            Self::TriggerOrderPlaced(deserialize("TriggerOrderPlaced", &mut body)?)
+        } else if discriminator == event_discriminator("TriggerOrderExecuted") {
+            Self::TriggerOrderExecuted(deserialize("TriggerOrderExecuted", &mut body)?)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(event))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PositionOpened(_) => "PositionOpened",
+            Self::PositionClosed(_) => "PositionClosed",
+            Self::PnlUpdated(_) => "PnlUpdated",
+            Self::TradeRecorded(_) => "TradeRecorded",
+            Self::TriggerOrderPlaced(_) => "TriggerOrderPlaced",
+            Self::TriggerOrderExecuted(_) => "TriggerOrderExecuted",
+        }
+    }
+
+    // Step 5: Map into the pipeline's event model; lifecycle-only events have no counterpart
+    pub fn to_portfolio_event(&self) -> Option<PortfolioEvent> {
+        let timestamp = chrono::Utc::now();
+        match self {
+            Self::PnlUpdated(e) => Some(PortfolioEvent::PositionUpdate {
+                wallet: pubkey_string(e.owner),
+                mint: pubkey_string(e.mint),
+                pnl_delta: e.pnl_delta as f64,
+                timestamp,
+                source: None,
+                reversal: false,
+            }),
+            Self::TradeRecorded(e) => Some(PortfolioEvent::TradeRecorded {
+                wallet: pubkey_string(e.owner),
+                mint: pubkey_string(e.mint),
+                amount: e.amount,
+                notional: e.notional,
+                timestamp,
+                source: None,
+                reversal: false,
+            }),
+            Self::TriggerOrderExecuted(e) => Some(PortfolioEvent::SwapExecuted {
+                wallet: pubkey_string(e.owner),
+                input_mint: pubkey_string(e.input_mint),
+                output_mint: pubkey_string(e.output_mint),
+                amount: e.size,
+                amount_out: e.amount_out,
+                timestamp,
+                source: None,
+                reversal: false,
+            }),
+            Self::PositionOpened(_) | Self::PositionClosed(_) | Self::TriggerOrderPlaced(_) => None,
+        }
+    }
+}
+
+// Step 6: Helpers
+fn event_discriminator(name: &str) -> [u8; 8] {
+    anchor_discriminator("event", name)
+}
+
+fn deserialize<T: BorshDeserialize>(
+    event: &'static str,
+    body: &mut &[u8],
+) -> Result<T, EventDecodeError> {
+    T::deserialize(body).map_err(|e| EventDecodeError::InvalidData {
+        event,
+        reason: e.to_string(),
+    })
+}
+
+fn pubkey_string(bytes: [u8; 32]) -> String {
+    Pubkey::new_from_array(bytes).to_string()
+}
+
+fn serialize_pubkey<S: serde::Serializer>(
+    bytes: &[u8; 32],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&pubkey_string(*bytes))
+}
+
+// Step 7: Event decoding error types
+#[derive(Debug)]
+pub enum EventDecodeError {
+    InvalidBase64(String),
+    PayloadTooShort(usize),
+    InvalidData { event: &'static str, reason: String },
+    Idl(String),
+}
+
+impl std::fmt::Display for EventDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBase64(reason) => write!(f, "Invalid base64 event payload: {}", reason),
+            Self::PayloadTooShort(len) => {
+                write!(
+                    f,
+                    "Event payload too short for a discriminator: {} bytes",
+                    len
+                )
+            }
+            Self::InvalidData { event, reason } => {
+                write!(f, "Invalid {} event data: {}", event, reason)
+            }
+            Self::Idl(reason) => write!(f, "IDL event decoding failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EventDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: [u8; 32] = [1; 32];
+    const MINT: [u8; 32] = [2; 32];
+
+    // Borsh layout: fields in declaration order, integers little-endian, enums as one byte
+    fn payload(name: &str, fields: &[&[u8]]) -> Vec<u8> {
+        let mut payload = event_discriminator(name).to_vec();
+        for field in fields {
+            payload.extend_from_slice(field);
+        }
+        payload
+    }
+
+    fn log(payload: &[u8]) -> String {
+        let encoded = base64::Engine::encode(&base64_standard, payload);
+        format!("{}{}", PROGRAM_DATA_PREFIX, encoded)
+    }
+
+    fn decode_event(payload: &[u8]) -> PortfolioEvent {
+        ProgramEvent::decode_log(&log(payload))
+            .unwrap()
+            .expect("known discriminator")
+            .to_portfolio_event()
+            .expect("mapped event")
+    }
+
+    #[test]
+    fn trade_recorded_maps_to_a_trade() {
+        let payload = payload(
+            "TradeRecorded",
+            &[
+                &OWNER,
+                &[3; 32],
+                &MINT,
+                &7u64.to_le_bytes(),
+                &2u64.to_le_bytes(),
+                &1_500u64.to_le_bytes(),
+                &30_000u64.to_le_bytes(),
+                &99u64.to_le_bytes(),
+            ],
+        );
+
+        let PortfolioEvent::TradeRecorded {
+            wallet,
+            mint,
+            amount,
+            notional,
+            ..
+        } = decode_event(&payload)
+        else {
+            panic!("expected a trade");
+        };
+        assert_eq!(wallet, pubkey_string(OWNER));
+        assert_eq!(mint, pubkey_string(MINT));
+        assert_eq!((amount, notional), (1_500, 30_000));
+    }
+
+    #[test]
+    fn pnl_updated_maps_to_a_position_update() {
+        let payload = payload(
+            "PnlUpdated",
+            &[
+                &OWNER,
+                &[3; 32],
+                &MINT,
+                &(-250i64).to_le_bytes(),
+                &(-100i64).to_le_bytes(),
+                &400i64.to_le_bytes(),
+                &99u64.to_le_bytes(),
+            ],
+        );
+
+        let PortfolioEvent::PositionUpdate {
+            wallet,
+            mint,
+            pnl_delta,
+            ..
+        } = decode_event(&payload)
+        else {
+            panic!("expected a position update");
+        };
+        assert_eq!(wallet, pubkey_string(OWNER));
+        assert_eq!(mint, pubkey_string(MINT));
+        assert_eq!(pnl_delta, -250.0);
+    }
+
+    #[test]
+    fn trigger_order_executed_maps_to_a_swap() {
+        let payload = payload(
+            "TriggerOrderExecuted",
+            &[
+                &OWNER,
+                &[3; 32],
+                &[4; 32],
+                &[1], // TriggerKind::TakeProfit
+                &120u64.to_le_bytes(),
+                &5_000u64.to_le_bytes(),
+                &MINT,
+                &[5; 32],
+                &4_900u64.to_le_bytes(),
+            ],
+        );
+
+        let PortfolioEvent::SwapExecuted {
+            wallet,
+            input_mint,
+            output_mint,
+            amount,
+            amount_out,
+            ..
+        } = decode_event(&payload)
+        else {
+            panic!("expected a swap");
+        };
+        assert_eq!(wallet, pubkey_string(OWNER));
+        assert_eq!(input_mint, pubkey_string(MINT));
+        assert_eq!(output_mint, pubkey_string([5; 32]));
+        assert_eq!((amount, amount_out), (5_000, 4_900));
+    }
+
+    #[test]
+    fn malformed_payloads_are_errors() {
+        assert!(matches!(
+            ProgramEvent::decode_log("Program data: not base64!"),
+            Err(EventDecodeError::InvalidBase64(_))
+        ));
+        assert!(matches!(
+            ProgramEvent::decode(&[1, 2, 3]),
+            Err(EventDecodeError::PayloadTooShort(3))
+        ));
+
+        // Known discriminator, body cut short
+        let mut truncated = payload("TradeRecorded", &[&OWNER, &[3; 32], &MINT]);
+        truncated.extend(7u64.to_le_bytes());
+        assert!(matches!(
+            ProgramEvent::decode(&truncated),
+            Err(EventDecodeError::InvalidData {
+                event: "TradeRecorded",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_discriminators_and_other_logs_are_skipped() {
+        let foreign = payload("SomeoneElsesEvent", &[&[0; 16]]);
+        assert!(ProgramEvent::decode(&foreign).unwrap().is_none());
+        assert!(ProgramEvent::decode_log(&log(&foreign)).unwrap().is_none());
+        assert!(ProgramEvent::decode_log("Program log: Instruction: Swap")
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,447 @@
+// backend/src/integration/replay.rs
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+use crate::config::{Config, ReplaySpeed};
+use crate::integration::idl::IdlRegistry;
+use crate::integration::normalizer::{EventNormalizer, RawEvent};
+use crate::models::event::PortfolioEvent;
+use crate::pipeline::dead_letter::{DeadLetterPayload, DeadLetterQueue, DeadLetterStage};
+use crate::pipeline::mpsc_queue::{EventQueue, QueueError};
+use crate::{BackendError, BackendResult};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Key added to every recorded line; used to pace replays and to date recorded notifications
+pub(crate) const RECORDED_AT_KEY: &str = "recorded_at";
+
+// Step 1: One line of a recording: a raw JSON-RPC notification, a `RawEvent`, or an already
+// normalized `PortfolioEvent` such as the lines of a queue spill file
+enum RecordedPayload {
+    Notification(serde_json::Value),
+    RawEvent(RawEvent),
+    Event(PortfolioEvent),
+}
+
+struct RecordedLine {
+    recorded_at: Option<chrono::DateTime<chrono::Utc>>,
+    payload: RecordedPayload,
+}
+
+impl RecordedLine {
+    fn parse(line: &str) -> Result<Self, ThreadSafeError> {
+        let mut value: serde_json::Value = serde_json::from_str(line)?;
+        let recorded_at = value
+            .get(RECORDED_AT_KEY)
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?;
+
+        // Notifications keep the key: they carry no time of their own, so the normalizer
+        // stamps their events with it
+        if value.get("method").is_some() {
+            return Ok(Self {
+                recorded_at,
+                payload: RecordedPayload::Notification(value),
+            });
+        }
+        value
+            .as_object_mut()
+            .ok_or("recorded line is not a JSON object")?
+            .remove(RECORDED_AT_KEY);
+
+        let payload = if value.get("event_type").is_some() {
+            RecordedPayload::RawEvent(serde_json::from_value(value)?)
+        } else if let Ok(event) = serde_json::from_value(value) {
+            RecordedPayload::Event(event)
+        } else {
+            return Err(
+                "recorded line is neither a notification, a RawEvent nor a PortfolioEvent".into(),
+            );
+        };
+
+        Ok(Self {
+            recorded_at,
+            payload,
+        })
+    }
+}
+
+// Step 2: Appends live notifications and raw events to a JSONL file for later replay
+#[derive(Clone)]
+pub struct EventRecorder {
+    path: String,
+    file: Arc<Mutex<tokio::fs::File>>,
+}
+
+impl EventRecorder {
+    pub async fn open(path: &str) -> Result<Self, ThreadSafeError> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        tracing::info!("⏺️ Recording notifications to {}", path);
+
+        Ok(Self {
+            path: path.to_string(),
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    pub async fn record_notification(&self, notification: &serde_json::Value) {
+        self.write(notification.clone()).await;
+    }
+
+    pub async fn record_raw_event(&self, event: &RawEvent) {
+        self.write(json!(event)).await;
+    }
+
+    // Recording is best effort: a full disk must not stop live ingestion
+    async fn write(&self, mut value: serde_json::Value) {
+        if let Some(object) = value.as_object_mut() {
+            object.insert(RECORDED_AT_KEY.to_string(), json!(chrono::Utc::now()));
+        }
+        let mut line = value.to_string();
+        line.push('\n');
+
+        if let Err(e) = self.file.lock().await.write_all(line.as_bytes()).await {
+            tracing::warn!("⚠️ Failed to record to {}: {}", self.path, e);
+        }
+    }
+}
+
+// Step 3: Outcome of a replay run
+#[derive(Debug, Default, Clone)]
+pub struct ReplayStats {
+    pub lines: usize,
+    pub events: usize,
+    pub skipped: usize,    // Lines that could not be parsed or normalized
+    pub duplicates: usize, // Notifications for an already replayed transaction
+}
+
+// Step 4: Feeds a recording through `EventNormalizer` into the pipeline, fully offline
+pub struct ReplaySource {
+    path: String,
+    speed: ReplaySpeed,
+    normalizer: EventNormalizer,
+    event_tx: EventQueue,
+    dead_letters: Option<DeadLetterQueue>,
+}
+
+impl ReplaySource {
+    pub fn new(
+        config: &Config,
+        idls: Arc<IdlRegistry>,
+        event_tx: EventQueue,
+    ) -> BackendResult<Self> {
+        let speed = config.replay.speed().ok_or_else(|| {
+            BackendError::ConfigError(format!("Invalid REPLAY_SPEED '{}'", config.replay.speed))
+        })?;
+
+        Ok(Self {
+            path: config.replay.path.clone(),
+            speed,
+            normalizer: EventNormalizer::from_config(&config.solana, idls),
+            event_tx,
+            dead_letters: None,
+        })
+    }
+
+    // Park lines that fail normalization instead of only logging them
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    // Replay another file than `REPLAY_PATH`, e.g. for a rules backtest
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    // Step 5: Replay the file once, pacing events by their recorded timestamps
+    pub async fn run(&self) -> Result<ReplayStats, ThreadSafeError> {
+        tracing::info!("⏯️ Replaying {} at {:?} speed", self.path, self.speed);
+
+        let file = tokio::fs::File::open(&self.path).await?;
+        let mut lines = BufReader::new(file).lines();
+        let mut stats = ReplayStats::default();
+        let mut previous_at: Option<chrono::DateTime<chrono::Utc>> = None;
+        // A transaction is recorded once per subscription that saw it
+        let mut replayed_signatures: HashSet<String> = HashSet::new();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            stats.lines += 1;
+
+            let recorded = match RecordedLine::parse(&line) {
+                Ok(recorded) => recorded,
+                Err(e) => {
+                    tracing::warn!("⚠️ Skipping line {} of {}: {}", stats.lines, self.path, e);
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+
+            if let Some(recorded_at) = recorded.recorded_at {
+                if let Some(previous_at) = previous_at {
+                    self.pace(recorded_at - previous_at).await;
+                }
+                previous_at = Some(recorded_at);
+            }
+
+            let (events, payload) = match recorded.payload {
+                RecordedPayload::RawEvent(raw_event) => (
+                    self.normalizer
+                        .normalize_event(raw_event.clone())
+                        .map(|event| vec![event]),
+                    DeadLetterPayload::RawEvent(raw_event),
+                ),
+                RecordedPayload::Event(event) => {
+                    (Ok(vec![event.clone()]), DeadLetterPayload::Event(event))
+                }
+                RecordedPayload::Notification(notification) => {
+                    let signature = notification["params"]["result"]["value"]["signature"]
+                        .as_str()
+                        .map(str::to_string);
+                    if let Some(signature) = signature {
+                        if !replayed_signatures.insert(signature) {
+                            stats.duplicates += 1;
+                            continue;
+                        }
+                    }
+                    (
+                        self.normalizer.normalize_notification(&notification),
+                        DeadLetterPayload::Notification(notification),
+                    )
+                }
+            };
+
+            match events {
+                Ok(events) => {
+                    for event in events {
+                        match self.event_tx.send(event).await {
+                            Ok(()) => stats.events += 1,
+                            Err(QueueError::Closed) => {
+                                return Err("pipeline closed during replay".into())
+                            }
+                            // Shed by the queue's overflow policy
+                            Err(e) => {
+                                tracing::warn!(
+                                    "⚠️ Event from line {} not queued: {}",
+                                    stats.lines,
+                                    e
+                                );
+                                stats.skipped += 1;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("⚠️ Skipping line {} of {}: {}", stats.lines, self.path, e);
+                    stats.skipped += 1;
+                    if let Some(dead_letters) = &self.dead_letters {
+                        dead_letters
+                            .push(DeadLetterStage::Normalization, payload, e.to_string())
+                            .await;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            "✅ Replay complete: {} line(s), {} event(s), {} skipped, {} duplicate(s)",
+            stats.lines,
+            stats.events,
+            stats.skipped,
+            stats.duplicates
+        );
+        Ok(stats)
+    }
+
+    // Step 6: Sleep for the recorded gap, scaled by the replay speed
+    async fn pace(&self, gap: chrono::Duration) {
+        let Ok(gap) = gap.to_std() else {
+            return; // Out-of-order timestamps replay immediately
+        };
+        let delay = match self.speed {
+            ReplaySpeed::Original => gap,
+            ReplaySpeed::Accelerated(factor) => gap.div_f64(factor),
+            ReplaySpeed::Max => return,
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::metrics::MetricsService;
+
+    const NOTIFICATION: &str = r#"{"jsonrpc":"2.0","method":"logsNotification","params":{"result":{"context":{"slot":42},"value":{"signature":"sig1","err":null,"logs":["Program log: hello"]}}},"recorded_at":"2024-03-01T10:00:00Z"}"#;
+    const RAW_EVENT: &str = r#"{"event_type":"position_update","schema_version":1,"data":{"wallet":"alice","mint":"sol","pnl_delta":-5.0,"timestamp":"2024-03-01T10:00:10Z"},"recorded_at":"2024-03-01T10:00:10Z"}"#;
+    const EVENT: &str = r#"{"PositionUpdate":{"wallet":"bob","mint":"sol","pnl_delta":2.5,"timestamp":"2024-03-01T10:00:40Z"},"recorded_at":"2024-03-01T10:00:40Z"}"#;
+
+    fn recording(name: &str, lines: &[&str]) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("replay_{}_{}.jsonl", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn replay(path: &str, speed: ReplaySpeed, event_tx: EventQueue) -> ReplaySource {
+        ReplaySource::new(&Config::load(), Arc::new(IdlRegistry::default()), event_tx)
+            .unwrap()
+            .with_path(path)
+            .with_speed(speed)
+    }
+
+    fn at(timestamp: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        Some(timestamp.parse().unwrap())
+    }
+
+    #[test]
+    fn each_line_kind_is_parsed_and_only_notifications_keep_their_timestamp() {
+        let notification = RecordedLine::parse(NOTIFICATION).unwrap();
+        assert_eq!(notification.recorded_at, at("2024-03-01T10:00:00Z"));
+        let RecordedPayload::Notification(value) = notification.payload else {
+            panic!("expected a notification");
+        };
+        assert_eq!(value[RECORDED_AT_KEY], "2024-03-01T10:00:00Z");
+
+        let raw_event = RecordedLine::parse(RAW_EVENT).unwrap();
+        assert_eq!(raw_event.recorded_at, at("2024-03-01T10:00:10Z"));
+        let RecordedPayload::RawEvent(raw_event) = raw_event.payload else {
+            panic!("expected a raw event");
+        };
+        assert_eq!(raw_event.event_type, "position_update");
+
+        // The extra key would otherwise break the externally tagged enum
+        let event = RecordedLine::parse(EVENT).unwrap();
+        assert_eq!(event.recorded_at, at("2024-03-01T10:00:40Z"));
+        assert!(matches!(
+            event.payload,
+            RecordedPayload::Event(PortfolioEvent::PositionUpdate { ref wallet, .. }) if wallet == "bob"
+        ));
+
+        assert!(RecordedLine::parse("not json").is_err());
+        assert!(RecordedLine::parse("[1, 2]").is_err());
+        assert!(RecordedLine::parse(r#"{"unknown":true}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn a_transaction_seen_by_several_subscriptions_is_replayed_once() {
+        let other = NOTIFICATION.replace("sig1", "sig2");
+        let path = recording("duplicates", &[NOTIFICATION, NOTIFICATION, &other]);
+
+        let stats = replay(&path, ReplaySpeed::Max, EventQueue::new(8))
+            .run()
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn skipped_lines_are_counted_and_normalization_failures_dead_lettered() {
+        let unknown = RAW_EVENT.replace("position_update", "airdrop");
+        let no_slot = NOTIFICATION.replace(r#""slot":42"#, r#""slot":null"#);
+        let path = recording(
+            "skipped",
+            &["not json", &unknown, &no_slot, RAW_EVENT, EVENT],
+        );
+        let mut pipeline = Config::load().pipeline;
+        pipeline.dead_letter_path = None;
+        let dead_letters = DeadLetterQueue::open(&pipeline, MetricsService::new())
+            .await
+            .unwrap();
+        let queue = EventQueue::new(8);
+
+        let stats = replay(&path, ReplaySpeed::Max, queue.clone())
+            .with_dead_letters(dead_letters.clone())
+            .run()
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(stats.lines, 5);
+        assert_eq!(stats.events, 2);
+        assert_eq!(stats.skipped, 3);
+
+        // Unparseable lines have no payload to park
+        let parked = dead_letters.list(None, 10).await;
+        assert_eq!(parked.len(), 2);
+        assert!(parked
+            .iter()
+            .all(|entry| entry.stage == DeadLetterStage::Normalization));
+        assert!(parked.iter().any(|entry| matches!(
+            &entry.payload,
+            DeadLetterPayload::RawEvent(raw_event) if raw_event.event_type == "airdrop"
+        )));
+        assert!(parked
+            .iter()
+            .any(|entry| matches!(entry.payload, DeadLetterPayload::Notification(_))));
+
+        assert_eq!(queue.recv().await.unwrap().wallet(), "alice");
+        assert_eq!(queue.recv().await.unwrap().wallet(), "bob");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lines_are_paced_by_their_recorded_gaps() {
+        let path = recording("paced", &[NOTIFICATION, RAW_EVENT, EVENT]);
+
+        // 40 seconds separate the first and last line
+        let start = tokio::time::Instant::now();
+        replay(&path, ReplaySpeed::Original, EventQueue::new(8))
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed().as_secs(), 40);
+
+        let start = tokio::time::Instant::now();
+        replay(&path, ReplaySpeed::Accelerated(10.0), EventQueue::new(8))
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed().as_secs(), 4);
+
+        let start = tokio::time::Instant::now();
+        replay(&path, ReplaySpeed::Max, EventQueue::new(8))
+            .run()
+            .await
+            .unwrap();
+        assert!(start.elapsed().is_zero());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn out_of_order_timestamps_are_not_waited_for() {
+        let source = replay("unused", ReplaySpeed::Original, EventQueue::new(1));
+        let start = tokio::time::Instant::now();
+
+        source.pace(chrono::Duration::seconds(-30)).await;
+        assert!(start.elapsed().is_zero());
+
+        source.pace(chrono::Duration::milliseconds(1500)).await;
+        assert_eq!(start.elapsed(), std::time::Duration::from_millis(1500));
+    }
+}
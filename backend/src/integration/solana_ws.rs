@@ -1,143 +1,1219 @@
 // backend/src/ingestion/solana_ws.rs
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use tokio::sync::{mpsc, Mutex, Notify, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
-use crate::models::event::PortfolioEvent;
+use crate::config::SolanaConfig;
+use crate::integration::backfill::collect_signatures;
+use crate::integration::idl::IdlRegistry;
+use crate::integration::normalizer::EventNormalizer;
+use crate::integration::program_events::{EventDecodeError, ProgramEvent};
+use crate::integration::replay::EventRecorder;
+use crate::models::event::{ChainSource, Commitment, PortfolioEvent};
+use crate::pipeline::dead_letter::{DeadLetterPayload, DeadLetterQueue, DeadLetterStage};
+use crate::pipeline::finality::FinalityTracker;
+use crate::pipeline::mpsc_queue::EventQueue;
+use crate::services::metrics::MetricsService;
+use crate::services::solana_client::{SolanaClient, TransactionInstruction};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Reconnect backoff bounds
+const RECONNECT_BASE_DELAY_MS: u64 = 500;
+const RECONNECT_MAX_DELAY_MS: u64 = 60_000;
+
+// Recently delivered (signature, instruction index) keys kept for deduplication
+const DEDUPE_CAPACITY: usize = 10_000;
+
+// Outages longer than this many transactions are left to the backfill worker
+const GAP_FILL_PAGE_SIZE: usize = 1000;
+const GAP_FILL_MAX_TRANSACTIONS: usize = 10_000;
+
+// Transactions waiting for their instructions; the read loop only waits once this is full
+const INSTRUCTION_FETCH_QUEUE_SIZE: usize = 1024;
+const INSTRUCTION_FETCH_ATTEMPTS: u32 = 5;
+
+// Cursors held by a transaction that could not be fetched are replayed this often, instead of
+// waiting for the next reconnect
+const HELD_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Step 1: Decoder for the `Program data:` lines emitted by one subscribed program
+pub trait ProgramLogDecoder: Send + Sync {
+    fn decode_log(&self, log: &str) -> Result<Vec<PortfolioEvent>, EventDecodeError>;
+}
+
+// Our Anchor programs (portfolio-program, amm-pool)
+pub struct AnchorEventDecoder;
+
+impl ProgramLogDecoder for AnchorEventDecoder {
+    fn decode_log(&self, log: &str) -> Result<Vec<PortfolioEvent>, EventDecodeError> {
+        let Some(program_event) = ProgramEvent::decode_log(log)? else {
+            return Ok(Vec::new());
+        };
+        let event = program_event.to_portfolio_event();
+        if event.is_none() {
+            tracing::debug!("📭 Ignoring {} event", program_event.name());
+        }
+        Ok(event.into_iter().collect())
+    }
+}
+
+// Any program with a loaded Anchor IDL; events become `PortfolioEvent`s through the
+// registry's mappings
+pub struct IdlEventDecoder {
+    program_id: String,
+    registry: Arc<IdlRegistry>,
+    normalizer: EventNormalizer,
+}
+
+impl IdlEventDecoder {
+    pub fn new(program_id: impl Into<String>, registry: Arc<IdlRegistry>) -> Self {
+        Self {
+            program_id: program_id.into(),
+            normalizer: EventNormalizer::with_idls(registry.clone()),
+            registry,
+        }
+    }
+}
+
+impl ProgramLogDecoder for IdlEventDecoder {
+    fn decode_log(&self, log: &str) -> Result<Vec<PortfolioEvent>, EventDecodeError> {
+        let Some(item) = self
+            .registry
+            .decode_log(&self.program_id, log)
+            .map_err(|e| EventDecodeError::Idl(e.to_string()))?
+        else {
+            return Ok(Vec::new());
+        };
+        let events = self
+            .normalizer
+            .normalize_decoded(&item)
+            .map_err(|e| EventDecodeError::Idl(e.to_string()))?;
+        if events.is_empty() {
+            tracing::debug!("📭 No mapping for {} event {}", item.program, item.name);
+        }
+        Ok(events)
+    }
+}
+
+// Third-party programs whose event layouts we do not know yet
+pub struct IgnoreEventsDecoder;
+
+impl ProgramLogDecoder for IgnoreEventsDecoder {
+    fn decode_log(&self, _log: &str) -> Result<Vec<PortfolioEvent>, EventDecodeError> {
+        Ok(Vec::new())
+    }
+}
+
+// Step 2: A program to subscribe to and the decoder its logs are routed to
+#[derive(Clone)]
+pub struct ProgramSubscription {
+    pub program_id: String,
+    pub decoder: Arc<dyn ProgramLogDecoder>,
+}
+
+impl ProgramSubscription {
+    pub fn new(program_id: impl Into<String>, decoder: Arc<dyn ProgramLogDecoder>) -> Self {
+        Self {
+            program_id: program_id.into(),
+            decoder,
+        }
+    }
+
+    // Every configured program with its default decoder: ours are decoded natively, others
+    // through their IDL when one is loaded
+    pub fn from_config(config: &SolanaConfig, idls: &Arc<IdlRegistry>) -> Vec<Self> {
+        let own_programs = [&config.portfolio_program_id, &config.amm_pool_program_id];
+        config
+            .subscribed_program_ids()
+            .into_iter()
+            .map(|program_id| {
+                let decoder: Arc<dyn ProgramLogDecoder> = if own_programs.contains(&&program_id) {
+                    Arc::new(AnchorEventDecoder)
+                } else if idls.has_idl(&program_id) {
+                    Arc::new(IdlEventDecoder::new(program_id.clone(), idls.clone()))
+                } else {
+                    Arc::new(IgnoreEventsDecoder)
+                };
+                Self::new(program_id, decoder)
+            })
+            .collect()
+    }
+}
+
+// A `Program data:` line its program's decoder rejected
+#[derive(Debug)]
+pub struct LogDecodeFailure {
+    pub instruction_index: usize,
+    pub program_id: String,
+    pub log: String,
+    pub error: EventDecodeError,
+}
+
+// Events of one transaction, each paired with the index of the top-level instruction that
+// emitted it, and the lines that failed to decode
+#[derive(Debug, Default)]
+pub struct DecodedLogs {
+    pub events: Vec<(usize, PortfolioEvent)>,
+    pub failures: Vec<LogDecodeFailure>,
+}
+
+// Decode the `Program data:` lines of one transaction, routing each line to the decoder of
+// the program that emitted it. The invoke stack is tracked so CPI'd programs' events only
+// reach their own decoder; programs not in `programs` are skipped.
+pub fn decode_program_logs(programs: &[ProgramSubscription], logs: &[&str]) -> DecodedLogs {
+    let mut decoded = DecodedLogs::default();
+    let mut invoke_stack: Vec<&str> = Vec::new();
+    let mut instruction_index = 0;
+    let mut next_instruction_index = 0;
+
+    for log in logs {
+        let mut parts = log.split_whitespace();
+        if parts.next() != Some("Program") {
+            continue;
+        }
+        match (parts.next(), parts.next()) {
+            (Some("data:"), _) => {
+                let Some(program) = invoke_stack
+                    .last()
+                    .and_then(|id| programs.iter().find(|p| p.program_id == *id))
+                else {
+                    continue;
+                };
+                match program.decoder.decode_log(log) {
+                    Ok(events) => decoded
+                        .events
+                        .extend(events.into_iter().map(|event| (instruction_index, event))),
+                    // One bad payload must not drop the rest of the transaction
+                    Err(error) => decoded.failures.push(LogDecodeFailure {
+                        instruction_index,
+                        program_id: program.program_id.clone(),
+                        log: log.to_string(),
+                        error,
+                    }),
+                }
+            }
+            (Some(program_id), Some("invoke")) => {
+                // Depth [1] marks a new top-level instruction
+                if parts.next() == Some("[1]") {
+                    instruction_index = next_instruction_index;
+                    next_instruction_index += 1;
+                }
+                invoke_stack.push(program_id);
+            }
+            (Some(_), Some(status)) if status == "success" || status.starts_with("failed") => {
+                invoke_stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    decoded
+}
+
+// Step 3: Newest processed transaction of a subscribed program
+#[derive(Debug, Clone)]
+pub struct SlotCursor {
+    pub slot: u64,
+    pub signature: String,
+}
+
+// Bounded set of recently delivered (signature, instruction index) keys
+struct RecentEvents {
+    order: VecDeque<(String, usize)>,
+    keys: HashSet<(String, usize)>,
+}
+
+impl RecentEvents {
+    fn new() -> Self {
+        Self {
+            order: VecDeque::with_capacity(DEDUPE_CAPACITY),
+            keys: HashSet::with_capacity(DEDUPE_CAPACITY),
+        }
+    }
+
+    // Returns false if the key was already delivered
+    fn insert(&mut self, key: (String, usize)) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > DEDUPE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+// A transaction missed during an outage and the subscribed programs it mentions
+struct GapTransaction {
+    slot: u64,
+    position: usize, // Index in the newest-first listing, orders transactions within a slot
+    succeeded: bool,
+    program_ids: Vec<String>,
+}
+
+// A transaction of an instruction-mapped program, queued for the instruction fetcher
+struct InstructionFetch {
+    program_id: String,
+    slot: u64,
+    signature: String,
+    succeeded: bool, // Failed transactions only advance the cursor
+}
 
-// Step 1: Solana WebSocket client for real-time on-chain data
+// Step 4: Solana WebSocket client for real-time on-chain data
 pub struct SolanaWebSocket {
-    event_tx: tokio::sync::mpsc::Sender<PortfolioEvent>,
-    rpc_url: String,
+    event_tx: EventQueue,
+    solana_client: SolanaClient,
+    metrics: MetricsService,
+    ws_url: String,
+    commitment: String,
+    programs: Vec<ProgramSubscription>,
+    idls: Arc<IdlRegistry>,
+    instruction_normalizer: EventNormalizer, // Maps IDL-decoded instructions
+    subscriptions: Arc<RwLock<HashMap<u64, usize>>>, // Subscription ID -> index into `programs`
+    cursors: RwLock<HashMap<String, SlotCursor>>, // Program ID -> newest processed transaction
+    stalled: RwLock<HashMap<String, u64>>,   // Held program ID -> hold number
+    holds: AtomicU64,                        // Numbers holds, so a replay only releases its own
+    gap_fill: Mutex<()>,                     // One replay at a time
+    fetch_tx: mpsc::Sender<InstructionFetch>,
+    fetch_rx: Mutex<mpsc::Receiver<InstructionFetch>>,
+    recent: Mutex<RecentEvents>,
+    finality: Option<FinalityTracker>,
+    dead_letters: Option<DeadLetterQueue>,
+    recorder: Option<EventRecorder>,
+    shutdown: Arc<Notify>,
 }
 
 impl SolanaWebSocket {
-    pub fn new(event_tx: tokio::sync::mpsc::Sender<PortfolioEvent>) -> Self {
-        let rpc_url = std::env::var("SOLANA_WS_URL")
-            .unwrap_or_else(|_| "wss://api.devnet.solana.com".to_string());
+    pub fn new(
+        config: &SolanaConfig,
+        programs: Vec<ProgramSubscription>,
+        solana_client: SolanaClient,
+        metrics: MetricsService,
+        event_tx: EventQueue,
+    ) -> Self {
+        let (fetch_tx, fetch_rx) = mpsc::channel(INSTRUCTION_FETCH_QUEUE_SIZE);
+        Self {
+            event_tx,
+            solana_client,
+            metrics,
+            ws_url: config.ws_url.clone(),
+            commitment: config.commitment.to_lowercase(),
+            programs,
+            idls: Arc::new(IdlRegistry::default()),
+            instruction_normalizer: EventNormalizer::new(),
+            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            cursors: RwLock::new(HashMap::new()),
+            stalled: RwLock::new(HashMap::new()),
+            holds: AtomicU64::new(0),
+            gap_fill: Mutex::new(()),
+            fetch_tx,
+            fetch_rx: Mutex::new(fetch_rx),
+            recent: Mutex::new(RecentEvents::new()),
+            finality: None,
+            dead_letters: None,
+            recorder: None,
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    // Step 5: Subscribe to every configured program with its default decoder
+    pub fn from_config(
+        config: &SolanaConfig,
+        idls: Arc<IdlRegistry>,
+        solana_client: SolanaClient,
+        metrics: MetricsService,
+        event_tx: EventQueue,
+    ) -> Self {
+        let programs = ProgramSubscription::from_config(config, &idls);
+        Self::new(config, programs, solana_client, metrics, event_tx).with_idls(idls)
+    }
+
+    // Also decode the instructions of programs that have IDL instruction mappings. Their
+    // transactions are fetched, since log notifications carry no instruction data.
+    pub fn with_idls(mut self, idls: Arc<IdlRegistry>) -> Self {
+        self.instruction_normalizer = EventNormalizer::with_idls(idls.clone());
+        self.idls = idls;
+        self
+    }
+
+    // Hand every delivered event to `tracker` so it is reconciled once its slot finalizes
+    pub fn with_finality_tracker(mut self, tracker: FinalityTracker) -> Self {
+        self.finality = Some(tracker);
+        self
+    }
 
-        Self { event_tx, rpc_url }
+    // Park log lines and instructions that fail to decode instead of only logging them
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
     }
 
-    // Step 2: Start listening to Solana events
+    // Append every raw logs notification to a recording for offline replay
+    pub fn with_recorder(mut self, recorder: EventRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    // Step 6: Start listening to Solana events until `stop` is called
     pub async fn start(&self) {
-        tracing::info!("🌐 Starting Solana WebSocket ingestion");
+        tracing::info!(
+            "🌐 Starting Solana WebSocket ingestion for {} program(s)",
+            self.programs.len()
+        );
+
+        // Instruction fetches run beside the connection, so a slow RPC never stalls its reads
+        tokio::select! {
+            _ = self.run_connection() => {}
+            _ = self.fetch_instructions() => {}
+            _ = self.retry_held() => {}
+        }
+    }
 
+    async fn run_connection(&self) {
+        let mut attempt: u32 = 0;
         loop {
             match self.connect().await {
-                Ok(_) => tracing::info!("✅ Solana WebSocket connected"),
-                Err(e) => {
-                    tracing::error!("❌ Solana WebSocket error: {}, reconnecting...", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                Ok(true) => {
+                    tracing::info!("🛑 Solana WebSocket stopped");
+                    break;
+                }
+                Ok(false) => {
+                    // The connection was healthy, so start backing off from scratch
+                    attempt = 0;
+                    tracing::warn!("⚠️ Solana WebSocket closed, reconnecting...");
+                }
+                Err(e) => tracing::error!("❌ Solana WebSocket error: {}, reconnecting...", e),
+            }
+
+            attempt = attempt.saturating_add(1);
+            self.metrics.record_ws_reconnect().await;
+            let delay = reconnect_delay(attempt);
+            tracing::debug!("⏳ Reconnect attempt {} in {:?}", attempt, delay);
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = self.shutdown.notified() => {
+                    tracing::info!("🛑 Solana WebSocket stopped");
+                    break;
                 }
             }
         }
     }
 
-    // Step 3: Establish WebSocket connection
-    async fn connect(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let (mut ws_stream, _) = connect_async(&self.rpc_url).await?;
+    // Step 7: Unsubscribe from all programs and close the connection
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    // Step 8: Active subscriptions as (subscription ID, program ID)
+    pub async fn active_subscriptions(&self) -> Vec<(u64, String)> {
+        self.subscriptions
+            .read()
+            .await
+            .iter()
+            .map(|(id, index)| (*id, self.programs[*index].program_id.clone()))
+            .collect()
+    }
 
-        // Step 4: Subscribe to program logs
-        let subscribe_message = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "logsSubscribe",
-            "params": [
-                {
-                    "mentions": [
-                        "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS" // Your program ID
-                    ]
+    // Newest processed transaction per program ID
+    pub async fn cursors(&self) -> HashMap<String, SlotCursor> {
+        self.cursors.read().await.clone()
+    }
+
+    // Step 9: Establish the connection; returns `true` once stopped deliberately
+    async fn connect(&self) -> Result<bool, ThreadSafeError> {
+        let (mut ws_stream, _) = connect_async(&self.ws_url).await?;
+        tracing::info!("✅ Solana WebSocket connected");
+
+        // Subscription IDs are per connection
+        self.subscriptions.write().await.clear();
+
+        // Step 10: One logsSubscribe per program; the request ID is the program's index
+        for (index, program) in self.programs.iter().enumerate() {
+            let subscribe_message = json!({
+                "jsonrpc": "2.0",
+                "id": index,
+                "method": "logsSubscribe",
+                "params": [
+                    { "mentions": [program.program_id] },
+                    { "commitment": self.commitment }
+                ]
+            });
+            ws_stream
+                .send(Message::Text(subscribe_message.to_string()))
+                .await?;
+        }
+
+        // Step 11: Replay whatever was missed since the last connection; notifications
+        // arriving meanwhile queue up and are deduplicated against the replay
+        self.fill_gap(None).await?;
+
+        // Step 12: Process incoming messages until closed or stopped
+        loop {
+            tokio::select! {
+                message = ws_stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(e) = self.handle_message(&text).await {
+                            tracing::error!("Error handling message: {}", e);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("WebSocket error: {}", e);
+                        return Ok(false);
+                    }
+                    None => return Ok(false),
+                    _ => {}
                 },
-                {
-                    "commitment": "confirmed"
+                _ = self.shutdown.notified() => break,
+            }
+        }
+
+        // Step 13: Clean unsubscribe before closing
+        let subscriptions: Vec<u64> = self
+            .subscriptions
+            .write()
+            .await
+            .drain()
+            .map(|(id, _)| id)
+            .collect();
+        for (request_id, subscription_id) in subscriptions.into_iter().enumerate() {
+            let unsubscribe_message = json!({
+                "jsonrpc": "2.0",
+                "id": self.programs.len() + request_id,
+                "method": "logsUnsubscribe",
+                "params": [subscription_id]
+            });
+            ws_stream
+                .send(Message::Text(unsubscribe_message.to_string()))
+                .await?;
+        }
+        ws_stream.close(None).await?;
+
+        Ok(true)
+    }
+
+    // Step 14: Route subscription confirmations and log notifications
+    async fn handle_message(&self, message: &str) -> Result<(), ThreadSafeError> {
+        let value: serde_json::Value = serde_json::from_str(message)?;
+
+        // Subscription confirmation: {"id": <program index>, "result": <subscription ID>}
+        if let (Some(index), Some(subscription_id)) = (
+            value.get("id").and_then(|id| id.as_u64()),
+            value.get("result").and_then(|result| result.as_u64()),
+        ) {
+            if let Some(program) = self.programs.get(index as usize) {
+                tracing::info!(
+                    "📡 Subscribed to logs of {} (subscription {})",
+                    program.program_id,
+                    subscription_id
+                );
+                self.subscriptions
+                    .write()
+                    .await
+                    .insert(subscription_id, index as usize);
+            }
+            return Ok(());
+        }
+
+        if value.get("method").and_then(|m| m.as_str()) != Some("logsNotification") {
+            return Ok(());
+        }
+        if let Some(recorder) = &self.recorder {
+            recorder.record_notification(&value).await;
+        }
+        let Some(params) = value.get("params") else {
+            return Ok(());
+        };
+        let Some(subscription_id) = params.get("subscription").and_then(|id| id.as_u64()) else {
+            return Ok(());
+        };
+        let Some(index) = self.subscription_index(subscription_id).await else {
+            return Ok(());
+        };
+
+        let Some(result) = params.get("result") else {
+            return Ok(());
+        };
+        let slot = result["context"]["slot"].as_u64().unwrap_or_default();
+        let value = &result["value"];
+        let Some(signature) = value["signature"].as_str() else {
+            return Ok(());
+        };
+
+        // Step 15: Failed transactions have no on-chain effect, but still advance the cursor
+        let succeeded = value["err"].is_null();
+        if succeeded {
+            if let Some(logs) = value["logs"].as_array() {
+                let logs: Vec<&str> = logs.iter().filter_map(|log| log.as_str()).collect();
+                let commitment = Commitment::from_config(&self.commitment);
+                self.deliver(signature, slot, commitment, None, &logs).await;
+            }
+        }
+
+        // Instruction-mapped programs advance their cursor from the fetcher, once the
+        // transaction's instructions are delivered
+        let program_id = &self.programs[index].program_id;
+        if self.idls.has_instruction_mappings(program_id) {
+            let fetch = InstructionFetch {
+                program_id: program_id.clone(),
+                slot,
+                signature: signature.to_string(),
+                succeeded,
+            };
+            self.fetch_tx.send(fetch).await?;
+        } else {
+            self.advance_cursor(program_id, slot, signature).await;
+        }
+
+        Ok(())
+    }
+
+    // Step 16: Fetch and deliver the instructions of queued transactions in arrival order,
+    // retrying with backoff. A transaction that still fails holds its program's cursor, so
+    // the held-cursor retry replays it.
+    async fn fetch_instructions(&self) {
+        let mut fetch_rx = self.fetch_rx.lock().await;
+        while let Some(fetch) = fetch_rx.recv().await {
+            if fetch.succeeded && !self.fetch_and_deliver(&fetch).await {
+                self.hold([fetch.program_id]).await;
+                continue;
+            }
+            self.advance_cursor(&fetch.program_id, fetch.slot, &fetch.signature)
+                .await;
+        }
+    }
+
+    async fn fetch_and_deliver(&self, fetch: &InstructionFetch) -> bool {
+        for attempt in 1..=INSTRUCTION_FETCH_ATTEMPTS {
+            match self
+                .solana_client
+                .get_transaction_logs(&fetch.signature)
+                .await
+            {
+                Ok(transaction) => {
+                    // History endpoints answer at `confirmed` or above
+                    let commitment =
+                        Commitment::from_config(&self.commitment).max(Commitment::Confirmed);
+                    self.deliver_instructions(
+                        &fetch.signature,
+                        fetch.slot,
+                        commitment,
+                        transaction.timestamp(),
+                        &transaction.instructions,
+                    )
+                    .await;
+                    return true;
                 }
-            ]
-        });
+                Err(e) => {
+                    tracing::warn!(
+                        "⚠️ Failed to fetch instructions of {} (attempt {}/{}): {}",
+                        fetch.signature,
+                        attempt,
+                        INSTRUCTION_FETCH_ATTEMPTS,
+                        e
+                    );
+                    if attempt < INSTRUCTION_FETCH_ATTEMPTS {
+                        tokio::time::sleep(reconnect_delay(attempt)).await;
+                    }
+                }
+            }
+        }
+        tracing::error!(
+            "❌ Gave up fetching instructions of {}; the {} cursor is held for a retry",
+            fetch.signature,
+            fetch.program_id
+        );
+        false
+    }
 
-        ws_stream
-            .send(Message::Text(subscribe_message.to_string()))
-            .await?;
+    // Replay the programs whose cursor is held, without waiting for a reconnect
+    async fn retry_held(&self) {
+        let mut interval = tokio::time::interval(HELD_RETRY_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let held: HashSet<String> = self.stalled.read().await.keys().cloned().collect();
+            if held.is_empty() {
+                continue;
+            }
+            tracing::info!("🔁 Retrying held cursors of {} program(s)", held.len());
+            if let Err(e) = self.fill_gap(Some(&held)).await {
+                tracing::warn!("⚠️ Retry of held cursors failed: {}", e);
+            }
+        }
+    }
+
+    async fn subscription_index(&self, subscription_id: u64) -> Option<usize> {
+        self.subscriptions
+            .read()
+            .await
+            .get(&subscription_id)
+            .copied()
+    }
 
-        // Step 5: Process incoming messages
-        while let Some(message) = ws_stream.next().await {
-            match message {
-                Ok(Message::Text(text)) => {
-                    if let Err(e) = self.handle_message(&text).await {
-                        tracing::error!("Error handling message: {}", e);
+    // Step 17: Decode every known program's events, delivering each instruction once even
+    // when the transaction reaches us through several subscriptions or a gap fill
+    async fn deliver(
+        &self,
+        signature: &str,
+        slot: u64,
+        commitment: Commitment,
+        block_time: Option<DateTime<Utc>>,
+        logs: &[&str],
+    ) {
+        let source = ChainSource {
+            slot,
+            signature: Some(signature.to_string()),
+            commitment,
+        };
+        let decoded = decode_program_logs(&self.programs, logs);
+        let mut fresh = Vec::new();
+        let mut failed = Vec::new();
+        let mut duplicates = 0;
+        {
+            let mut recent = self.recent.lock().await;
+            let mut verdicts: HashMap<usize, bool> = HashMap::new();
+            let mut is_new = |instruction_index: usize| {
+                *verdicts
+                    .entry(instruction_index)
+                    .or_insert_with(|| recent.insert((signature.to_string(), instruction_index)))
+            };
+            for (instruction_index, mut event) in decoded.events {
+                if is_new(instruction_index) {
+                    event.set_source(source.clone());
+                    if let Some(at) = block_time {
+                        event.set_timestamp(at);
                     }
+                    fresh.push(event);
+                } else {
+                    duplicates += 1;
+                }
+            }
+            // A failed line is parked once, like the events of its instruction
+            for failure in decoded.failures {
+                if is_new(failure.instruction_index) {
+                    failed.push(failure);
                 }
+            }
+        }
+
+        for failure in failed {
+            let payload = DeadLetterPayload::ProgramLog {
+                program_id: failure.program_id,
+                log: failure.log,
+                source: source.clone(),
+            };
+            self.park(signature, payload, failure.error.to_string())
+                .await;
+        }
+        self.publish(fresh, duplicates).await;
+    }
+
+    // Step 18: Decode the top-level instructions of IDL-described programs. Instruction keys
+    // are kept apart from log event keys, since one instruction can yield both.
+    async fn deliver_instructions(
+        &self,
+        signature: &str,
+        slot: u64,
+        commitment: Commitment,
+        block_time: Option<DateTime<Utc>>,
+        instructions: &[TransactionInstruction],
+    ) {
+        let source = ChainSource {
+            slot,
+            signature: Some(signature.to_string()),
+            commitment,
+        };
+        let mut fresh = Vec::new();
+        let mut duplicates = 0;
+        for (instruction_index, instruction) in instructions.iter().enumerate() {
+            if !self.idls.has_instruction_mappings(&instruction.program_id) {
+                continue;
+            }
+            let events = self
+                .instruction_normalizer
+                .normalize_instruction(instruction, &source);
+            if matches!(&events, Ok(events) if events.is_empty()) {
+                continue;
+            }
+
+            let key = (format!("{}#ix", signature), instruction_index);
+            if !self.recent.lock().await.insert(key) {
+                duplicates += events.map_or(0, |events| events.len() as u64);
+                continue;
+            }
+            match events {
+                Ok(events) => fresh.extend(events.into_iter().map(|mut event| {
+                    if let Some(at) = block_time {
+                        event.set_timestamp(at);
+                    }
+                    event
+                })),
                 Err(e) => {
-                    tracing::error!("WebSocket error: {}", e);
-                    break;
+                    let payload = DeadLetterPayload::Instruction {
+                        instruction: instruction.clone(),
+                        source: source.clone(),
+                    };
+                    self.park(signature, payload, e.to_string()).await;
                 }
-                _ => {}
             }
         }
 
-        Ok(())
+        self.publish(fresh, duplicates).await;
     }
 
-    // Step 6: Parse and handle incoming Solana events
-    async fn handle_message(&self, message: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let value: serde_json::Value = serde_json::from_str(message)?;
+    async fn park(&self, signature: &str, payload: DeadLetterPayload, error: String) {
+        tracing::warn!("⚠️ Failed to decode event of {}: {}", signature, error);
+        if let Some(dead_letters) = &self.dead_letters {
+            dead_letters
+                .push(DeadLetterStage::Normalization, payload, error)
+                .await;
+        }
+    }
 
-        // Step 7: Extract transaction logs
-        if let Some(params) = value.get("params") {
-            if let Some(result) = params.get("result") {
-                if let Some(logs) = result.get("value").and_then(|v| v.get("logs")) {
-                    if let Some(logs_array) = logs.as_array() {
-                        // Step 8: Process each log entry
-                        for log in logs_array {
-                            if let Some(log_str) = log.as_str() {
-                                self.process_log_entry(log_str).await?;
-                            }
-                        }
+    async fn publish(&self, events: Vec<PortfolioEvent>, duplicates: u64) {
+        if duplicates > 0 {
+            self.metrics.record_ws_duplicates(duplicates).await;
+        }
+        for event in events {
+            // Only events that reached the pipeline need compensating if reverted
+            match self.event_tx.send(event.clone()).await {
+                Ok(()) => {
+                    if let Some(finality) = &self.finality {
+                        finality.track(&event).await;
                     }
                 }
+                Err(e) => tracing::warn!("⚠️ Dropped live event: {}", e),
             }
         }
+    }
 
-        Ok(())
+    async fn advance_cursor(&self, program_id: &str, slot: u64, signature: &str) {
+        if self.stalled.read().await.contains_key(program_id) {
+            return;
+        }
+        self.move_cursor(program_id, slot, signature).await;
     }
 
-    // Step 9: Process individual log entries
-    async fn process_log_entry(&self, log: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Step 10: Look for our program's event signatures
-        if log.contains("Program log: Instruction: UpdatePosition") {
-            self.handle_position_update(log).await?;
-        } else if log.contains("Program log: Instruction: ExecuteSwap") {
-            self.handle_swap_executed(log).await?;
+    // Advance a cursor even while it is held; only replays may, as they go through the held
+    // transaction
+    async fn move_cursor(&self, program_id: &str, slot: u64, signature: &str) {
+        let mut cursors = self.cursors.write().await;
+        let cursor = cursors
+            .entry(program_id.to_string())
+            .or_insert_with(|| SlotCursor {
+                slot,
+                signature: signature.to_string(),
+            });
+        if slot >= cursor.slot {
+            cursor.slot = slot;
+            cursor.signature = signature.to_string();
         }
+    }
 
-        Ok(())
+    // Hold the cursors of `program_ids` until a replay gets past the current transaction
+    async fn hold(&self, program_ids: impl IntoIterator<Item = String>) {
+        let mut stalled = self.stalled.write().await;
+        for program_id in program_ids {
+            stalled.insert(program_id, self.holds.fetch_add(1, Ordering::Relaxed));
+        }
     }
 
-    // Step 11: Handle position update events
-    async fn handle_position_update(&self, _log: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Extract data from logs (simplified)
-        let event = PortfolioEvent::PositionUpdate {
-            wallet: "extracted_wallet".to_string(),
-            mint: "SOL".to_string(),
-            pnl_delta: 150.0,
-            timestamp: chrono::Utc::now(),
-        };
+    // Release the holds a replay started with on the programs it replayed. Holds taken since,
+    // by the replay itself or by the instruction fetcher, stay.
+    async fn release_holds(&self, started_with: &HashMap<String, u64>, replayed: &HashSet<String>) {
+        self.stalled.write().await.retain(|program_id, hold| {
+            !replayed.contains(program_id) || started_with.get(program_id) != Some(hold)
+        });
+    }
+
+    // Step 19: Replay transactions between each program's cursor and now, oldest first;
+    // `only` limits the replay to some programs. Programs without a cursor yet start from
+    // their newest transaction. Held cursors are advanced by the replay itself and released
+    // once it succeeds, so live notifications cannot move them past a held transaction.
+    async fn fill_gap(&self, only: Option<&HashSet<String>>) -> Result<(), ThreadSafeError> {
+        let _replaying = self.gap_fill.lock().await;
+        let started_with = self.stalled.read().await.clone();
+        let cursors = self.cursors().await;
+        let mut pending: HashMap<String, GapTransaction> = HashMap::new();
+        let mut replayed: HashSet<String> = HashSet::new();
+
+        let programs = self
+            .programs
+            .iter()
+            .filter(|program| only.is_none_or(|only| only.contains(&program.program_id)));
+        for program in programs {
+            let program_id = &program.program_id;
+            replayed.insert(program_id.clone());
+            let Some(cursor) = cursors.get(program_id) else {
+                let (latest, _) =
+                    collect_signatures(&self.solana_client, program_id, None, 1, Some(1)).await?;
+                if let Some(status) = latest.first() {
+                    self.move_cursor(program_id, status.slot, &status.signature)
+                        .await;
+                }
+                continue;
+            };
+
+            let (statuses, truncated) = collect_signatures(
+                &self.solana_client,
+                program_id,
+                Some(&cursor.signature),
+                GAP_FILL_PAGE_SIZE,
+                Some(GAP_FILL_MAX_TRANSACTIONS),
+            )
+            .await?;
+            if truncated {
+                tracing::warn!(
+                    "⚠️ Gap for {} exceeds {} transactions; older ones are left to the backfill worker",
+                    program_id,
+                    GAP_FILL_MAX_TRANSACTIONS
+                );
+            }
+
+            add_gap_statuses(&mut pending, program_id, statuses);
+        }
+
+        if cursors.is_empty() {
+            self.release_holds(&started_with, &replayed).await;
+            return Ok(());
+        }
+
+        let oldest_slot = cursors
+            .values()
+            .map(|cursor| cursor.slot)
+            .min()
+            .unwrap_or_default();
+        let current_slot = self.solana_client.get_slot().await?;
+        self.metrics
+            .record_ws_gap(
+                pending.len() as u64,
+                current_slot.saturating_sub(oldest_slot),
+            )
+            .await;
+        tracing::info!(
+            "🩹 Filling gap of {} transaction(s) since slot {}",
+            pending.len(),
+            oldest_slot
+        );
+
+        let mut failed: HashSet<String> = HashSet::new();
+        for (signature, tx) in gap_replay_order(pending) {
+            if tx.succeeded {
+                // One unreadable transaction must not abort the replay; its programs' cursors
+                // are held so the held-cursor retry replays it
+                let transaction = match self.solana_client.get_transaction_logs(&signature).await {
+                    Ok(transaction) => transaction,
+                    Err(e) => {
+                        tracing::error!(
+                            "❌ Skipped gap transaction {}; its cursors are held for a retry: {}",
+                            signature,
+                            e
+                        );
+                        self.hold(tx.program_ids.iter().cloned()).await;
+                        failed.extend(tx.program_ids);
+                        continue;
+                    }
+                };
+                let logs: Vec<&str> = transaction.logs.iter().map(String::as_str).collect();
+                // History endpoints answer at `confirmed` or above
+                let commitment =
+                    Commitment::from_config(&self.commitment).max(Commitment::Confirmed);
+                // Replayed events are dated by their block, not by when the gap was filled
+                let block_time = transaction.timestamp();
+                self.deliver(&signature, transaction.slot, commitment, block_time, &logs)
+                    .await;
+                self.deliver_instructions(
+                    &signature,
+                    transaction.slot,
+                    commitment,
+                    block_time,
+                    &transaction.instructions,
+                )
+                .await;
+            }
+            for program_id in tx.program_ids {
+                if !failed.contains(&program_id) {
+                    self.move_cursor(&program_id, tx.slot, &signature).await;
+                }
+            }
+        }
 
-        let _ = self.event_tx.send(event).await;
+        self.release_holds(&started_with, &replayed).await;
         Ok(())
     }
+}
+
+// Merge one program's newest-first listing into the gap; a transaction mentioning several
+// subscribed programs is replayed once, for all of them
+fn add_gap_statuses(
+    pending: &mut HashMap<String, GapTransaction>,
+    program_id: &str,
+    statuses: Vec<RpcConfirmedTransactionStatusWithSignature>,
+) {
+    for (position, status) in statuses.into_iter().enumerate() {
+        pending
+            .entry(status.signature)
+            .or_insert_with(|| GapTransaction {
+                slot: status.slot,
+                position,
+                succeeded: status.err.is_none(),
+                program_ids: Vec::new(),
+            })
+            .program_ids
+            .push(program_id.to_string());
+    }
+}
+
+// Oldest first: by slot, then within a slot by listing position, which is newest first
+fn gap_replay_order(pending: HashMap<String, GapTransaction>) -> Vec<(String, GapTransaction)> {
+    let mut ordered: Vec<(String, GapTransaction)> = pending.into_iter().collect();
+    ordered.sort_by_key(|(_, tx)| (tx.slot, Reverse(tx.position)));
+    ordered
+}
+
+// Step 20: Exponential backoff with jitter, drawn from [delay / 2, delay]; shared with
+// the account subscriber
+pub(crate) fn reconnect_delay(attempt: u32) -> std::time::Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let delay = RECONNECT_BASE_DELAY_MS
+        .saturating_mul(1 << exponent)
+        .min(RECONNECT_MAX_DELAY_MS);
+    let jitter = RandomState::new().build_hasher().finish() % (delay / 2 + 1);
+    std::time::Duration::from_millis(delay / 2 + jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    // Turns `Program data: <wallet>` into one event for that wallet; `bad` fails to decode
+    struct WalletDecoder;
+
+    impl ProgramLogDecoder for WalletDecoder {
+        fn decode_log(&self, log: &str) -> Result<Vec<PortfolioEvent>, EventDecodeError> {
+            match log.trim_start_matches("Program data: ") {
+                "bad" => Err(EventDecodeError::PayloadTooShort(3)),
+                wallet => Ok(vec![create_position_update_event(
+                    wallet.to_string(),
+                    "mint".to_string(),
+                    1.0,
+                )]),
+            }
+        }
+    }
+
+    fn ours() -> Vec<ProgramSubscription> {
+        vec![ProgramSubscription::new("Ours", Arc::new(WalletDecoder))]
+    }
 
-    // Step 12: Handle swap execution events
-    async fn handle_swap_executed(&self, _log: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let event = PortfolioEvent::SwapExecuted {
-            wallet: "extracted_wallet".to_string(),
-            input_mint: "SOL".to_string(),
-            output_mint: "USDC".to_string(),
-            amount: 1000000,
-            timestamp: chrono::Utc::now(),
+    fn socket() -> SolanaWebSocket {
+        // Nothing listens at the RPC address, so replays fail on their first request
+        let config = SolanaConfig {
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            ws_url: "ws://127.0.0.1:1".to_string(),
+            program_id: "Ours".to_string(),
+            amm_pool_program_id: "Ours".to_string(),
+            portfolio_program_id: "Ours".to_string(),
+            extra_program_ids: Vec::new(),
+            idl_dir: None,
+            commitment: "confirmed".to_string(),
+            logs_ws_enabled: true,
+            accounts_ws_enabled: false,
         };
+        SolanaWebSocket::new(
+            &config,
+            ours(),
+            SolanaClient::new(&config),
+            MetricsService::new(),
+            EventQueue::new(8),
+        )
+    }
 
-        let _ = self.event_tx.send(event).await;
-        Ok(())
+    fn status(signature: &str, slot: u64) -> RpcConfirmedTransactionStatusWithSignature {
+        RpcConfirmedTransactionStatusWithSignature {
+            signature: signature.to_string(),
+            slot,
+            err: None,
+            memo: None,
+            block_time: None,
+            confirmation_status: None,
+        }
+    }
+
+    fn programs(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn wallets(decoded: &DecodedLogs) -> Vec<(usize, &str)> {
+        decoded
+            .events
+            .iter()
+            .map(|(index, event)| (*index, event.wallet()))
+            .collect()
+    }
+
+    #[test]
+    fn events_from_a_cpi_are_not_attributed_to_the_caller() {
+        let logs = [
+            "Program Ours invoke [1]",
+            "Program data: before",
+            "Program Spoofer invoke [2]",
+            "Program data: spoofed",
+            "Program log: not an event",
+            "Program Spoofer success",
+            "Program data: after",
+            "Program Ours success",
+        ];
+
+        let decoded = decode_program_logs(&ours(), &logs);
+        assert_eq!(wallets(&decoded), vec![(0, "before"), (0, "after")]);
+        assert!(decoded.failures.is_empty());
+    }
+
+    #[test]
+    fn unsubscribed_programs_calling_us_only_yield_our_events() {
+        let logs = [
+            "Program Spoofer invoke [1]",
+            "Program data: spoofed",
+            "Program Ours invoke [2]",
+            "Program data: nested",
+            "Program Ours success",
+            "Program Spoofer failed: custom program error: 0x1",
+            "Program Ours invoke [1]",
+            "Program data: top",
+            "Program Ours success",
+        ];
+
+        let decoded = decode_program_logs(&ours(), &logs);
+        assert_eq!(wallets(&decoded), vec![(0, "nested"), (1, "top")]);
+    }
+
+    #[test]
+    fn a_bad_payload_does_not_drop_the_rest() {
+        let logs = [
+            "Program Ours invoke [1]",
+            "Program data: bad",
+            "Program data: good",
+            "Program Ours success",
+        ];
+
+        let decoded = decode_program_logs(&ours(), &logs);
+        assert_eq!(wallets(&decoded), vec![(0, "good")]);
+        assert_eq!(decoded.failures.len(), 1);
+        assert_eq!(decoded.failures[0].program_id, "Ours");
+        assert_eq!(decoded.failures[0].log, "Program data: bad");
+    }
+
+    #[test]
+    fn dedupe_forgets_the_oldest_keys_first() {
+        let key = |i: usize| (format!("sig-{}", i), 0);
+        let mut recent = RecentEvents::new();
+        for i in 0..DEDUPE_CAPACITY {
+            assert!(recent.insert(key(i)));
+        }
+        assert!(!recent.insert(key(0)));
+
+        // One more key evicts the oldest, which then counts as new again
+        assert!(recent.insert(key(DEDUPE_CAPACITY)));
+        assert!(recent.insert(key(0)));
+        assert!(!recent.insert(key(2)));
+        assert!(recent.insert(key(1)));
+        assert_eq!(recent.order.len(), DEDUPE_CAPACITY);
+        assert_eq!(recent.keys.len(), DEDUPE_CAPACITY);
+
+        // Instructions of one transaction are told apart
+        assert!(recent.insert(("sig-3".to_string(), 1)));
+    }
+
+    #[test]
+    fn gap_transactions_replay_oldest_first_and_once() {
+        let mut pending = HashMap::new();
+        // Listings are newest first
+        add_gap_statuses(
+            &mut pending,
+            "Ours",
+            vec![
+                status("c", 12),
+                status("b2", 11),
+                status("b1", 11),
+                status("a", 10),
+            ],
+        );
+        add_gap_statuses(
+            &mut pending,
+            "Theirs",
+            vec![status("d", 13), status("b1", 11)],
+        );
+
+        let ordered = gap_replay_order(pending);
+        let signatures: Vec<&str> = ordered.iter().map(|(sig, _)| sig.as_str()).collect();
+        assert_eq!(signatures, vec!["a", "b1", "b2", "c", "d"]);
+        assert_eq!(ordered[1].1.program_ids, vec!["Ours", "Theirs"]);
+        assert_eq!(ordered[4].1.program_ids, vec!["Theirs"]);
+    }
+
+    #[tokio::test]
+    async fn a_held_cursor_only_moves_through_a_replay() {
+        let socket = socket();
+        socket.advance_cursor("Ours", 10, "a").await;
+        socket.advance_cursor("Ours", 9, "older").await;
+        assert_eq!(socket.cursors().await["Ours"].signature, "a");
+
+        socket.hold(["Ours".to_string()]).await;
+        socket.advance_cursor("Ours", 12, "live").await;
+        assert_eq!(socket.cursors().await["Ours"].signature, "a");
+        socket.move_cursor("Ours", 11, "replayed").await;
+        assert_eq!(socket.cursors().await["Ours"].signature, "replayed");
+
+        let started_with = socket.stalled.read().await.clone();
+        socket
+            .release_holds(&started_with, &programs(&["Ours"]))
+            .await;
+        socket.advance_cursor("Ours", 12, "live").await;
+        assert_eq!(socket.cursors().await["Ours"].signature, "live");
+    }
+
+    #[tokio::test]
+    async fn a_replay_only_releases_the_holds_it_started_with() {
+        let socket = socket();
+        socket
+            .hold(["Ours", "Theirs", "Other"].map(String::from))
+            .await;
+        let started_with = socket.stalled.read().await.clone();
+        // The instruction fetcher gives up on another Theirs transaction meanwhile
+        socket.hold(["Theirs".to_string()]).await;
+
+        socket
+            .release_holds(&started_with, &programs(&["Ours", "Theirs"]))
+            .await;
+        let held: HashSet<String> = socket.stalled.read().await.keys().cloned().collect();
+        assert_eq!(held, programs(&["Theirs", "Other"]));
+    }
+
+    #[tokio::test]
+    async fn a_failed_retry_keeps_the_cursor_held() {
+        let socket = socket();
+        socket.advance_cursor("Ours", 10, "a").await;
+        socket.hold(["Ours".to_string()]).await;
+
+        assert!(socket.fill_gap(Some(&programs(&["Ours"]))).await.is_err());
+        assert!(socket.stalled.read().await.contains_key("Ours"));
+        assert_eq!(socket.cursors().await["Ours"].slot, 10);
     }
 }
@@ -27,8 +27,13 @@ pub struct BackendAppState {
     pub solana_client: services::solana_client::SolanaClient,
     pub ai_client: services::ai_client::AIClient,
     pub metrics: services::metrics::MetricsService,
-    pub event_tx: tokio::sync::mpsc::Sender<models::event::PortfolioEvent>,
+    pub event_tx: pipeline::mpsc_queue::EventQueue,
     pub ws_hub: ws::hub::WsHub,
+    pub dead_letters: pipeline::dead_letter::DeadLetterQueue,
+    pub alerts: pipeline::alert_store::AlertStore, // Alerts raised by `rules_engine`
+    pub idl_registry: std::sync::Arc<integration::idl::IdlRegistry>,
+    pub rules_engine: std::sync::Arc<pipeline::rules_engine::RulesEngine>,
+    pub pipeline: Option<pipeline::runtime::PipelineHandle>, // Consumer of `event_tx`
 }
 
 // Step 4: Implement helper methods for BackendAppState
@@ -39,9 +44,15 @@ impl BackendAppState {
         solana_client: services::solana_client::SolanaClient,
         ai_client: services::ai_client::AIClient,
         metrics: services::metrics::MetricsService,
-        event_tx: tokio::sync::mpsc::Sender<models::event::PortfolioEvent>,
+        event_tx: pipeline::mpsc_queue::EventQueue,
         ws_hub: ws::hub::WsHub,
+        dead_letters: pipeline::dead_letter::DeadLetterQueue,
     ) -> Self {
+        let alerts = pipeline::alert_store::AlertStore::new(&config.risk, ws_hub.clone());
+        let rules_engine =
+            pipeline::rules_engine::RulesEngine::new(&config.risk, ws_hub.clone(), metrics.clone())
+                .with_alert_store(alerts.clone());
+
         Self {
             config,
             solana_client,
@@ -49,7 +60,48 @@ impl BackendAppState {
             metrics,
             event_tx,
             ws_hub,
+            dead_letters,
+            alerts,
+            idl_registry: std::sync::Arc::new(integration::idl::IdlRegistry::default()),
+            rules_engine: std::sync::Arc::new(rules_engine),
+            pipeline: None,
+        }
+    }
+
+    /// Use the Anchor IDLs loaded from `IDL_DIR`
+    pub fn with_idl_registry(mut self, idl_registry: integration::idl::IdlRegistry) -> Self {
+        self.idl_registry = std::sync::Arc::new(idl_registry);
+        self
+    }
+
+    /// Use the risk rules loaded from `RULES_PATH`
+    pub fn with_rule_set(mut self, rule_set: pipeline::rule_set::RuleSet) -> Self {
+        let rules_engine = pipeline::rules_engine::RulesEngine::new(
+            &self.config.risk,
+            self.ws_hub.clone(),
+            self.metrics.clone(),
+        )
+        .with_alert_store(self.alerts.clone())
+        .with_rule_set(rule_set);
+        self.rules_engine = std::sync::Arc::new(rules_engine);
+        self
+    }
+
+    /// Attach the runtime consuming `event_tx`, so it can be drained on shutdown
+    pub fn with_pipeline(mut self, pipeline: pipeline::runtime::PipelineHandle) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// Drain the event pipeline, if one is running, then save the rule windows, alerts and
+    /// dead letters it updated
+    pub async fn shutdown(&self) {
+        if let Some(pipeline) = &self.pipeline {
+            pipeline.shutdown().await;
         }
+        self.rules_engine.snapshot_state().await;
+        self.alerts.flush().await;
+        self.dead_letters.flush().await;
     }
 
     /// Get a reference to the configuration
@@ -81,7 +133,7 @@ impl BackendAppState {
     pub async fn send_event(
         &self,
         event: models::event::PortfolioEvent,
-    ) -> Result<(), tokio::sync::mpsc::error::SendError<models::event::PortfolioEvent>> {
+    ) -> Result<(), pipeline::mpsc_queue::QueueError> {
         self.event_tx.send(event).await
     }
 
@@ -143,19 +195,74 @@ pub async fn create_backend_app_state() -> BackendResult<BackendAppState> {
     let metrics = services::metrics::MetricsService::new();
 
     // Create event processing pipeline
-    let (event_tx, _event_rx) = tokio::sync::mpsc::channel(config.pipeline.max_queue_size);
+    let event_tx = pipeline::mpsc_queue::EventQueue::from_config(&config.pipeline);
+    event_tx.spawn_metrics_reporter(metrics.clone());
 
     // Initialize WebSocket hub
     let ws_hub = ws::hub::WsHub::new();
 
-    Ok(BackendAppState::new(
+    // Events that failed normalization or processing, kept across restarts
+    let dead_letters =
+        pipeline::dead_letter::DeadLetterQueue::open(&config.pipeline, metrics.clone()).await?;
+
+    // Anchor IDLs of third-party programs, decoded generically
+    let idl_registry = match &config.solana.idl_dir {
+        Some(dir) => integration::idl::IdlRegistry::load_dir(dir)
+            .map_err(|e| BackendError::ConfigError(format!("Failed to load IDLs: {}", e)))?,
+        None => integration::idl::IdlRegistry::default(),
+    };
+
+    // Risk rules: an invalid file fails startup, a missing one falls back to the built-ins
+    let rule_set = match &config.risk.rules_path {
+        Some(path) if std::path::Path::new(path).exists() => {
+            pipeline::rule_set::RuleSet::load(path)
+                .map_err(|e| BackendError::ConfigError(format!("{}: {}", path, e)))?
+        }
+        Some(path) => {
+            tracing::warn!("⚠️ Rules file {} not found, using built-in rules", path);
+            pipeline::rule_set::RuleSet::default()
+        }
+        None => pipeline::rule_set::RuleSet::default(),
+    };
+    tracing::info!("📏 {} risk rule(s) active", rule_set.enabled().count());
+
+    let state = BackendAppState::new(
         config,
         solana_client,
         ai_client,
         metrics,
         event_tx,
         ws_hub,
-    ))
+        dead_letters,
+    )
+    .with_idl_registry(idl_registry)
+    .with_rule_set(rule_set);
+
+    // Per-wallet rule windows from the last run; a bad snapshot only costs the history
+    match state.rules_engine.restore_state() {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("💾 Restored rule state for {} wallet(s)", count),
+        Err(e) => tracing::warn!("⚠️ Ignoring unreadable rule state snapshot: {}", e),
+    }
+    state.rules_engine.spawn_state_snapshots();
+
+    // Raised alerts and their lifecycle status from the last run
+    match state.alerts.restore().await? {
+        0 => {}
+        count => tracing::info!("🚨 Restored {} risk alert(s)", count),
+    }
+    state.alerts.spawn_maintenance();
+
+    // Batcher and rules engine, supervised; alerts go out on the shared hub
+    let pipeline = pipeline::runtime::PipelineRuntime::new(
+        &state.config.pipeline,
+        state.metrics.clone(),
+        state.rules_engine.clone(),
+        state.dead_letters.clone(),
+    )
+    .start(state.event_tx.clone());
+
+    Ok(state.with_pipeline(pipeline))
 }
 
 // Step 8: Health check response
@@ -283,6 +390,8 @@ pub mod backend_utils {
             mint,
             pnl_delta,
             timestamp: chrono::Utc::now(),
+            source: None,
+            reversal: false,
         }
     }
 
@@ -292,13 +401,17 @@ pub mod backend_utils {
         input_mint: String,
         output_mint: String,
         amount: u64,
+        amount_out: u64,
     ) -> PortfolioEvent {
         PortfolioEvent::SwapExecuted {
             wallet,
             input_mint,
             output_mint,
             amount,
+            amount_out,
             timestamp: chrono::Utc::now(),
+            source: None,
+            reversal: false,
         }
     }
 }
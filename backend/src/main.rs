@@ -4,15 +4,30 @@ use axum::{
 };
 use solana_defi_backend::{
     create_backend_app_state,
+    integration::{
+        account_ws::AccountSubscriber, backfill::BackfillWorker, price_feed::PriceFeed,
+        replay::{EventRecorder, ReplaySource},
+        solana_ws::SolanaWebSocket,
+    },
+    pipeline::finality::FinalityTracker,
+    services::keeper::TriggerKeeper,
     server_functions::{
+        admin::{
+            backtest_rules, delete_dead_letter, get_dead_letter, list_dead_letters,
+            purge_dead_letters, list_rules, reload_rules, retry_dead_letter,
+        },
         portfolio::{get_portfolio, update_position},
-        risk::{analyze_position, get_risk_alerts},
+        risk::{
+            acknowledge_alert, analyze_position, get_risk_alert, get_risk_alerts, resolve_alert,
+            snooze_alert,
+        },
         swap::{execute_swap, get_swap_quote},
     },
     ws::client::ws_handler,
     BackendAppInfo, BackendHealthCheck,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use axum::http::HeaderValue;
 
@@ -29,6 +44,115 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("🚀 Starting Solana DeFi Portfolio Backend");
 
+    // Follows log-stream and backfilled events until their slot finalizes
+    let finality = app_state.config.pipeline.finality_enabled.then(|| {
+        FinalityTracker::new(
+            &app_state.config.pipeline,
+            app_state.solana_client.clone(),
+            app_state.ws_hub.clone(),
+            app_state.metrics.clone(),
+            app_state.event_tx.clone(),
+        )
+    });
+    if let Some(tracker) = finality.clone() {
+        tokio::spawn(async move { tracker.start().await });
+    }
+
+    // Live program-log ingestion (opt-in; connects to SOLANA_WS_URL)
+    let log_stream = if app_state.config.solana.logs_ws_enabled {
+        let mut log_stream = SolanaWebSocket::from_config(
+            &app_state.config.solana,
+            app_state.idl_registry.clone(),
+            app_state.solana_client.clone(),
+            app_state.metrics.clone(),
+            app_state.event_tx.clone(),
+        )
+        .with_dead_letters(app_state.dead_letters.clone());
+        if let Some(tracker) = &finality {
+            log_stream = log_stream.with_finality_tracker(tracker.clone());
+        }
+        // Recording for later REPLAY_PATH runs and rule backtests
+        if let Some(path) = &app_state.config.replay.record_path {
+            let recorder = EventRecorder::open(path)
+                .await
+                .map_err(|e| e as Box<dyn std::error::Error>)?;
+            log_stream = log_stream.with_recorder(recorder);
+        }
+        let log_stream = Arc::new(log_stream);
+        let stream = log_stream.clone();
+        tokio::spawn(async move { stream.start().await });
+        Some(log_stream)
+    } else {
+        if app_state.config.replay.record_path.is_some() {
+            tracing::warn!("⚠️ RECORD_PATH is set but SOLANA_WS_ENABLED is off; nothing is recorded");
+        }
+        None
+    };
+
+    // Live PoolState/Position account changes (opt-in)
+    let account_stream = if app_state.config.solana.accounts_ws_enabled {
+        let account_stream = Arc::new(AccountSubscriber::new(
+            &app_state.config.solana,
+            app_state.solana_client.clone(),
+            app_state.event_tx.clone(),
+        )?);
+        let stream = account_stream.clone();
+        tokio::spawn(async move { stream.start().await });
+        Some(account_stream)
+    } else {
+        None
+    };
+
+    // Trigger-order keeper (opt-in; needs a funded keypair)
+    if app_state.config.keeper.enabled {
+        let keeper = TriggerKeeper::new(
+            &app_state.config,
+            app_state.solana_client.clone(),
+            app_state.metrics.clone(),
+        )?;
+        tokio::spawn(keeper.run());
+    }
+
+    // Historical backfill (opt-in; resumes from the persisted cursor)
+    if app_state.config.backfill.enabled {
+        let mut backfill = BackfillWorker::new(
+            &app_state.config,
+            app_state.idl_registry.clone(),
+            app_state.solana_client.clone(),
+            app_state.event_tx.clone(),
+        )
+        .with_dead_letters(app_state.dead_letters.clone());
+        if let Some(tracker) = &finality {
+            backfill = backfill.with_finality_tracker(tracker.clone());
+        }
+        tokio::spawn(async move {
+            if let Err(e) = backfill.run().await {
+                tracing::error!("❌ Backfill failed: {}", e);
+            }
+        });
+    }
+
+    // Offline replay of a recorded stream (demos and load tests)
+    if app_state.config.replay.enabled {
+        let replay = ReplaySource::new(
+            &app_state.config,
+            app_state.idl_registry.clone(),
+            app_state.event_tx.clone(),
+        )?
+        .with_dead_letters(app_state.dead_letters.clone());
+        tokio::spawn(async move {
+            if let Err(e) = replay.run().await {
+                tracing::error!("❌ Replay failed: {}", e);
+            }
+        });
+    }
+
+    // Off-chain USD prices for the market-wide risk rules (opt-in)
+    if app_state.config.price_feed.enabled {
+        let price_feed = PriceFeed::new(&app_state.config, app_state.event_tx.clone());
+        tokio::spawn(price_feed.run());
+    }
+
     // CORS: comma-separated origins; default permissive for hackathon/demo
     let origins = std::env::var("CORS_ORIGINS").unwrap_or_default();
     let allow_origins: Vec<HeaderValue> = origins
@@ -45,6 +169,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .allow_headers(Any)
     };
 
+    // Kept for draining the pipeline once the server stops
+    let shutdown_state = app_state.clone();
+
     // Router
     let app = Router::new()
         .route("/health", get(health_check))
@@ -55,7 +182,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/swap/quote", post(get_swap_quote))
         .route("/api/swap/execute", post(execute_swap))
         .route("/api/risk/alerts", get(get_risk_alerts))
+        .route("/api/risk/alerts/:id", get(get_risk_alert))
+        .route("/api/risk/alerts/:id/acknowledge", post(acknowledge_alert))
+        .route("/api/risk/alerts/:id/resolve", post(resolve_alert))
+        .route("/api/risk/alerts/:id/snooze", post(snooze_alert))
         .route("/api/risk/analyze", post(analyze_position))
+        .route(
+            "/api/admin/dlq",
+            get(list_dead_letters).delete(purge_dead_letters),
+        )
+        .route(
+            "/api/admin/dlq/:id",
+            get(get_dead_letter).delete(delete_dead_letter),
+        )
+        .route("/api/admin/dlq/:id/retry", post(retry_dead_letter))
+        .route("/api/admin/rules", get(list_rules))
+        .route("/api/admin/rules/reload", post(reload_rules))
+        .route("/api/admin/rules/backtest", post(backtest_rules))
         .route("/ws", get(ws_handler))
         .layer(cors)
         .with_state(app_state);
@@ -70,10 +213,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
     tracing::info!("📡 Server running on http://{addr}");
 
-    axum::serve(tokio::net::TcpListener::bind(addr).await?, app).await?;
+    axum::serve(tokio::net::TcpListener::bind(addr).await?, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    // Stop ingesting, then let in-flight events reach the batcher and rules engine
+    if let Some(log_stream) = &log_stream {
+        log_stream.stop();
+    }
+    if let Some(account_stream) = &account_stream {
+        account_stream.stop();
+    }
+    if let Some(tracker) = &finality {
+        tracker.stop();
+    }
+    // Also writes alerts and dead letters still waiting out their debounce window
+    shutdown_state.shutdown().await;
     Ok(())
 }
 
+// Resolves on Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("❌ Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("🛑 Shutdown signal received");
+}
+
 // Health + info endpoints
 async fn health_check() -> Json<BackendHealthCheck> {
     Json(BackendHealthCheck::new())
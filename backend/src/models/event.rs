@@ -1,21 +1,187 @@
 // backend/src/models/event.rs
 use serde::{Deserialize, Serialize};
 
-// Step 1: Portfolio event types for processing
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Step 1: Commitment level an event was observed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    // Parse a Solana commitment string, defaulting to `confirmed` for unknown values
+    pub fn from_config(commitment: &str) -> Self {
+        match commitment {
+            "processed" => Commitment::Processed,
+            "finalized" => Commitment::Finalized,
+            _ => Commitment::Confirmed,
+        }
+    }
+}
+
+// Where on chain an event came from; `None` for events raised off-chain (API, rules)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSource {
+    pub slot: u64,
+    pub signature: Option<String>, // Account-change events have no transaction signature
+    pub commitment: Commitment,
+}
+
+// Finality outcome of a transaction previously delivered at a lower commitment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FinalityStatus {
+    Finalized,
+    Reverted,
+}
+
+// Direction of a token transfer relative to the wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+// Step 2: Portfolio event types for processing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PortfolioEvent {
     PositionUpdate {
         wallet: String,
         mint: String,
         pnl_delta: f64,
         timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier PnL update whose transaction was reverted
     },
     SwapExecuted {
         wallet: String,
         input_mint: String,
         output_mint: String,
+        amount: u64, // Of `input_mint`
+        #[serde(default)]
+        amount_out: u64, // Of `output_mint`; 0 when the producer did not report it
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier swap whose transaction was reverted
+    },
+    TradeRecorded {
+        wallet: String,
+        mint: String,
+        amount: u64,
+        notional: u64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier trade whose transaction was reverted
+    },
+    LiquidityAdded {
+        wallet: String,
+        pool: String,
+        token_a: String,
+        token_b: String,
+        amount_a: u64,
+        amount_b: u64,
+        lp_tokens: u64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier withdrawal whose transaction was reverted
+    },
+    LiquidityRemoved {
+        wallet: String,
+        pool: String,
+        token_a: String,
+        token_b: String,
+        amount_a: u64,
+        amount_b: u64,
+        lp_tokens: u64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier deposit whose transaction was reverted
+    },
+    Transfer {
+        wallet: String,
+        mint: String,
+        amount: u64,
+        direction: TransferDirection,
+        counterparty: Option<String>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier transfer whose transaction was reverted
+    },
+    StakeChanged {
+        wallet: String,
+        stake_account: String,
+        validator: Option<String>,
+        delta_lamports: i64, // Positive when stake was added
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier stake change whose transaction was reverted
+    },
+    FeesCollected {
+        wallet: String,
+        pool: String,
+        mint: String,
         amount: u64,
         timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+        #[serde(default)]
+        reversal: bool, // Undoes an earlier fee claim whose transaction was reverted
+    },
+    // Vault balances of an amm-pool pool after an on-chain change
+    PoolReservesChanged {
+        pool: String,
+        token_a: String,
+        token_b: String,
+        #[serde(default)]
+        lp_mint: String, // Empty in recordings made before it was carried
+        reserve_a: u64,
+        reserve_b: u64,
+        total_liquidity: u64, // LP token supply
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+    },
+    // USD price of a mint from the off-chain price feed
+    PriceUpdated {
+        mint: String,
+        price_usd: f64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    // Full state of a portfolio-program `Position` after an on-chain change
+    PositionSnapshot {
+        wallet: String,
+        position: String,
+        mint: Option<String>, // Unknown for positions not yet migrated to v2
+        pnl: i64,
+        trade_count: u64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        source: Option<ChainSource>,
+    },
+    // Raised once per wallet when a tracked transaction finalizes or drops off the chain
+    TransactionStatusChanged {
+        wallet: String,
+        signature: String,
+        slot: u64,
+        status: FinalityStatus,
+        timestamp: chrono::DateTime<chrono::Utc>,
     },
     RiskAlertTriggered {
         wallet: String,
@@ -26,12 +192,23 @@ pub enum PortfolioEvent {
     },
 }
 
-// Step 2: Implement helper methods for events
+// Step 3: Implement helper methods for events
 impl PortfolioEvent {
     pub fn wallet(&self) -> &str {
         match self {
             PortfolioEvent::PositionUpdate { wallet, .. } => wallet,
             PortfolioEvent::SwapExecuted { wallet, .. } => wallet,
+            PortfolioEvent::TradeRecorded { wallet, .. } => wallet,
+            PortfolioEvent::LiquidityAdded { wallet, .. } => wallet,
+            PortfolioEvent::LiquidityRemoved { wallet, .. } => wallet,
+            PortfolioEvent::Transfer { wallet, .. } => wallet,
+            PortfolioEvent::StakeChanged { wallet, .. } => wallet,
+            PortfolioEvent::FeesCollected { wallet, .. } => wallet,
+            // Market-level events are keyed by the pool or mint they describe
+            PortfolioEvent::PoolReservesChanged { pool, .. } => pool,
+            PortfolioEvent::PriceUpdated { mint, .. } => mint,
+            PortfolioEvent::PositionSnapshot { wallet, .. } => wallet,
+            PortfolioEvent::TransactionStatusChanged { wallet, .. } => wallet,
             PortfolioEvent::RiskAlertTriggered { wallet, .. } => wallet,
         }
     }
@@ -40,7 +217,259 @@ impl PortfolioEvent {
         match self {
             PortfolioEvent::PositionUpdate { timestamp, .. } => timestamp,
             PortfolioEvent::SwapExecuted { timestamp, .. } => timestamp,
+            PortfolioEvent::TradeRecorded { timestamp, .. } => timestamp,
+            PortfolioEvent::LiquidityAdded { timestamp, .. } => timestamp,
+            PortfolioEvent::LiquidityRemoved { timestamp, .. } => timestamp,
+            PortfolioEvent::Transfer { timestamp, .. } => timestamp,
+            PortfolioEvent::StakeChanged { timestamp, .. } => timestamp,
+            PortfolioEvent::FeesCollected { timestamp, .. } => timestamp,
+            PortfolioEvent::PoolReservesChanged { timestamp, .. } => timestamp,
+            PortfolioEvent::PriceUpdated { timestamp, .. } => timestamp,
+            PortfolioEvent::PositionSnapshot { timestamp, .. } => timestamp,
+            PortfolioEvent::TransactionStatusChanged { timestamp, .. } => timestamp,
             PortfolioEvent::RiskAlertTriggered { timestamp, .. } => timestamp,
         }
     }
+
+    pub fn source(&self) -> Option<&ChainSource> {
+        match self {
+            PortfolioEvent::PositionUpdate { source, .. }
+            | PortfolioEvent::SwapExecuted { source, .. }
+            | PortfolioEvent::TradeRecorded { source, .. }
+            | PortfolioEvent::LiquidityAdded { source, .. }
+            | PortfolioEvent::LiquidityRemoved { source, .. }
+            | PortfolioEvent::Transfer { source, .. }
+            | PortfolioEvent::StakeChanged { source, .. }
+            | PortfolioEvent::FeesCollected { source, .. }
+            | PortfolioEvent::PoolReservesChanged { source, .. }
+            | PortfolioEvent::PositionSnapshot { source, .. } => source.as_ref(),
+            PortfolioEvent::PriceUpdated { .. }
+            | PortfolioEvent::TransactionStatusChanged { .. }
+            | PortfolioEvent::RiskAlertTriggered { .. } => None,
+        }
+    }
+
+    // Tag a chain-derived event with its origin; derived events are left untouched
+    pub fn set_source(&mut self, chain_source: ChainSource) {
+        match self {
+            PortfolioEvent::PositionUpdate { source, .. }
+            | PortfolioEvent::SwapExecuted { source, .. }
+            | PortfolioEvent::TradeRecorded { source, .. }
+            | PortfolioEvent::LiquidityAdded { source, .. }
+            | PortfolioEvent::LiquidityRemoved { source, .. }
+            | PortfolioEvent::Transfer { source, .. }
+            | PortfolioEvent::StakeChanged { source, .. }
+            | PortfolioEvent::FeesCollected { source, .. }
+            | PortfolioEvent::PoolReservesChanged { source, .. }
+            | PortfolioEvent::PositionSnapshot { source, .. } => *source = Some(chain_source),
+            PortfolioEvent::PriceUpdated { .. }
+            | PortfolioEvent::TransactionStatusChanged { .. }
+            | PortfolioEvent::RiskAlertTriggered { .. } => {}
+        }
+    }
+
+    // Re-stamp an event decoded after the fact, e.g. from a recording, with when it happened
+    pub fn set_timestamp(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        match self {
+            PortfolioEvent::PositionUpdate { timestamp, .. }
+            | PortfolioEvent::SwapExecuted { timestamp, .. }
+            | PortfolioEvent::TradeRecorded { timestamp, .. }
+            | PortfolioEvent::LiquidityAdded { timestamp, .. }
+            | PortfolioEvent::LiquidityRemoved { timestamp, .. }
+            | PortfolioEvent::Transfer { timestamp, .. }
+            | PortfolioEvent::StakeChanged { timestamp, .. }
+            | PortfolioEvent::FeesCollected { timestamp, .. }
+            | PortfolioEvent::PoolReservesChanged { timestamp, .. }
+            | PortfolioEvent::PriceUpdated { timestamp, .. }
+            | PortfolioEvent::PositionSnapshot { timestamp, .. }
+            | PortfolioEvent::TransactionStatusChanged { timestamp, .. }
+            | PortfolioEvent::RiskAlertTriggered { timestamp, .. } => *timestamp = at,
+        }
+    }
+
+    // Whether this event undoes an earlier one rather than reporting new activity
+    pub fn is_reversal(&self) -> bool {
+        match self {
+            PortfolioEvent::PositionUpdate { reversal, .. }
+            | PortfolioEvent::SwapExecuted { reversal, .. }
+            | PortfolioEvent::TradeRecorded { reversal, .. }
+            | PortfolioEvent::LiquidityAdded { reversal, .. }
+            | PortfolioEvent::LiquidityRemoved { reversal, .. }
+            | PortfolioEvent::Transfer { reversal, .. }
+            | PortfolioEvent::StakeChanged { reversal, .. }
+            | PortfolioEvent::FeesCollected { reversal, .. } => *reversal,
+            PortfolioEvent::PoolReservesChanged { .. }
+            | PortfolioEvent::PriceUpdated { .. }
+            | PortfolioEvent::PositionSnapshot { .. }
+            | PortfolioEvent::TransactionStatusChanged { .. }
+            | PortfolioEvent::RiskAlertTriggered { .. } => false,
+        }
+    }
+
+    // Step 4: Event that undoes this one's effect downstream if its transaction is reverted.
+    // It is marked as a reversal and keeps the original's timestamp, so rule state can find
+    // the sample it undoes. Snapshots and reserve changes are superseded by the next account
+    // update instead, and events raised off-chain are never reverted.
+    pub fn compensating_event(&self) -> Option<PortfolioEvent> {
+        match self {
+            PortfolioEvent::PositionUpdate {
+                wallet,
+                mint,
+                pnl_delta,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::PositionUpdate {
+                wallet: wallet.clone(),
+                mint: mint.clone(),
+                pnl_delta: -pnl_delta,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::SwapExecuted {
+                wallet,
+                input_mint,
+                output_mint,
+                amount,
+                amount_out,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::SwapExecuted {
+                wallet: wallet.clone(),
+                input_mint: output_mint.clone(),
+                output_mint: input_mint.clone(),
+                amount: *amount_out,
+                amount_out: *amount,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::TradeRecorded {
+                wallet,
+                mint,
+                amount,
+                notional,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::TradeRecorded {
+                wallet: wallet.clone(),
+                mint: mint.clone(),
+                amount: *amount,
+                notional: *notional,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::LiquidityAdded {
+                wallet,
+                pool,
+                token_a,
+                token_b,
+                amount_a,
+                amount_b,
+                lp_tokens,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::LiquidityRemoved {
+                wallet: wallet.clone(),
+                pool: pool.clone(),
+                token_a: token_a.clone(),
+                token_b: token_b.clone(),
+                amount_a: *amount_a,
+                amount_b: *amount_b,
+                lp_tokens: *lp_tokens,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::LiquidityRemoved {
+                wallet,
+                pool,
+                token_a,
+                token_b,
+                amount_a,
+                amount_b,
+                lp_tokens,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::LiquidityAdded {
+                wallet: wallet.clone(),
+                pool: pool.clone(),
+                token_a: token_a.clone(),
+                token_b: token_b.clone(),
+                amount_a: *amount_a,
+                amount_b: *amount_b,
+                lp_tokens: *lp_tokens,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::Transfer {
+                wallet,
+                mint,
+                amount,
+                direction,
+                counterparty,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::Transfer {
+                wallet: wallet.clone(),
+                mint: mint.clone(),
+                amount: *amount,
+                direction: match direction {
+                    TransferDirection::Incoming => TransferDirection::Outgoing,
+                    TransferDirection::Outgoing => TransferDirection::Incoming,
+                },
+                counterparty: counterparty.clone(),
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::StakeChanged {
+                wallet,
+                stake_account,
+                validator,
+                delta_lamports,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::StakeChanged {
+                wallet: wallet.clone(),
+                stake_account: stake_account.clone(),
+                validator: validator.clone(),
+                delta_lamports: delta_lamports.saturating_neg(),
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::FeesCollected {
+                wallet,
+                pool,
+                mint,
+                amount,
+                timestamp,
+                source,
+                reversal,
+            } => Some(PortfolioEvent::FeesCollected {
+                wallet: wallet.clone(),
+                pool: pool.clone(),
+                mint: mint.clone(),
+                amount: *amount,
+                timestamp: *timestamp,
+                source: source.clone(),
+                reversal: !reversal,
+            }),
+            PortfolioEvent::PoolReservesChanged { .. }
+            | PortfolioEvent::PositionSnapshot { .. } => None,
+            PortfolioEvent::PriceUpdated { .. }
+            | PortfolioEvent::TransactionStatusChanged { .. }
+            | PortfolioEvent::RiskAlertTriggered { .. } => None,
+        }
+    }
 }
@@ -10,10 +10,20 @@ pub struct RiskAlert {
     pub message: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub metadata: serde_json::Value,
+    #[serde(default)]
+    pub status: AlertStatus,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub resolution_notes: Option<String>,
+    #[serde(default)]
+    pub snoozed_until: Option<chrono::DateTime<chrono::Utc>>, // Set while snoozed
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>, // Last status change
 }
 
-// Step 2: Alert severity levels
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Step 2: Alert severity levels, ordered from least to most severe
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AlertSeverity {
     #[serde(rename = "low")]
     Low,
@@ -25,17 +35,62 @@ pub enum AlertSeverity {
     Critical,
 }
 
+impl AlertSeverity {
+    // Next level up, saturating at critical
+    pub fn escalate(&self) -> Self {
+        match self {
+            AlertSeverity::Low => AlertSeverity::Medium,
+            AlertSeverity::Medium => AlertSeverity::High,
+            AlertSeverity::High | AlertSeverity::Critical => AlertSeverity::Critical,
+        }
+    }
+}
+
 // Step 3: Alert status for management
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AlertStatus {
     #[serde(rename = "active")]
+    #[default]
     Active,
     #[serde(rename = "acknowledged")]
     Acknowledged,
+    #[serde(rename = "snoozed")]
+    Snoozed,
     #[serde(rename = "resolved")]
     Resolved,
 }
 
+impl AlertStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertStatus::Active => "active",
+            AlertStatus::Acknowledged => "acknowledged",
+            AlertStatus::Snoozed => "snoozed",
+            AlertStatus::Resolved => "resolved",
+        }
+    }
+}
+
+// Why a status change was refused
+#[derive(Debug)]
+pub enum AlertTransitionError {
+    AlreadyResolved,
+    SnoozeInPast(chrono::DateTime<chrono::Utc>),
+}
+
+impl std::fmt::Display for AlertTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyResolved => write!(f, "Alert is already resolved"),
+            Self::SnoozeInPast(until) => {
+                write!(f, "Cannot snooze until {}: time is in the past", until)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlertTransitionError {}
+
 // Step 4: Implement helper methods for RiskAlert
 impl RiskAlert {
     pub fn new(
@@ -51,6 +106,11 @@ impl RiskAlert {
             message,
             timestamp: chrono::Utc::now(),
             metadata: metadata.unwrap_or_else(|| serde_json::json!({})),
+            status: AlertStatus::Active,
+            assignee: None,
+            resolution_notes: None,
+            snoozed_until: None,
+            updated_at: None,
         }
     }
 
@@ -76,3 +136,70 @@ impl RiskAlert {
         }
     }
 }
+
+// Step 5: Lifecycle transitions. A resolved alert is final; the others can move freely.
+impl RiskAlert {
+    pub fn acknowledge(
+        &mut self,
+        assignee: Option<String>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AlertTransitionError> {
+        self.ensure_open()?;
+        self.status = AlertStatus::Acknowledged;
+        self.assignee = assignee.or(self.assignee.take());
+        self.snoozed_until = None;
+        self.updated_at = Some(now);
+        Ok(())
+    }
+
+    pub fn resolve(
+        &mut self,
+        notes: Option<String>,
+        assignee: Option<String>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AlertTransitionError> {
+        self.ensure_open()?;
+        self.status = AlertStatus::Resolved;
+        self.assignee = assignee.or(self.assignee.take());
+        self.resolution_notes = notes;
+        self.snoozed_until = None;
+        self.updated_at = Some(now);
+        Ok(())
+    }
+
+    pub fn snooze(
+        &mut self,
+        until: chrono::DateTime<chrono::Utc>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AlertTransitionError> {
+        self.ensure_open()?;
+        if until <= now {
+            return Err(AlertTransitionError::SnoozeInPast(until));
+        }
+        self.status = AlertStatus::Snoozed;
+        self.snoozed_until = Some(until);
+        self.updated_at = Some(now);
+        Ok(())
+    }
+
+    // Reactivate once the snooze has run out; true when the status changed
+    pub fn wake(&mut self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.snoozed_until {
+            Some(until) if self.status == AlertStatus::Snoozed && until <= now => {
+                self.status = AlertStatus::Active;
+                self.snoozed_until = None;
+                self.updated_at = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn ensure_open(&self) -> Result<(), AlertTransitionError> {
+        if self.status == AlertStatus::Resolved {
+            Err(AlertTransitionError::AlreadyResolved)
+        } else {
+            Ok(())
+        }
+    }
+}
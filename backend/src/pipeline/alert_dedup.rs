@@ -0,0 +1,244 @@
+// backend/src/pipeline/alert_dedup.rs
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::RiskConfig;
+use crate::models::risk_alert::{AlertSeverity, RiskAlert};
+
+// A repeat inside the cooldown is re-sent when its magnitude grows by this factor
+const ESCALATION_FACTOR: f64 = 1.5;
+// Expired entries are pruned once this many keys are tracked
+const PRUNE_THRESHOLD: usize = 10_000;
+
+// (wallet, rule_id, subject); the subject is the mint or pool of market and LP rules
+type AlertKey = (String, String, Option<String>);
+
+// Step 1: Last alert sent for a (wallet, rule_id, subject) key
+#[derive(Debug, Clone)]
+struct AlertRecord {
+    sent_at: chrono::DateTime<chrono::Utc>,
+    severity: AlertSeverity,
+    magnitude: f64,
+    suppressed: u64, // Repeats swallowed since `sent_at`
+}
+
+// Step 2: What to do with a freshly raised alert
+#[derive(Debug)]
+pub enum AlertDecision {
+    Emit(RiskAlert),
+    Escalate(RiskAlert), // Repeat inside the cooldown whose condition got worse
+    Suppress,
+}
+
+// Step 3: Suppresses repeats of the same rule for the same wallet within the cooldown. Market
+// and LP rules also key on their subject (mint or pool), so one mint's crash does not mute
+// the same rule for every other mint the wallet holds.
+pub struct AlertDeduplicator {
+    cooldown: chrono::Duration,
+    min_confidence: f64,
+    records: Mutex<HashMap<AlertKey, AlertRecord>>,
+}
+
+impl AlertDeduplicator {
+    pub fn new(config: &RiskConfig) -> Self {
+        Self {
+            cooldown: chrono::Duration::from_std(config.cooldown_duration())
+                .unwrap_or(chrono::Duration::MAX),
+            min_confidence: config.min_confidence,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Step 4: Decide on `alert`; `magnitude` is how far the rule's metric went (larger is worse)
+    pub fn admit(
+        &self,
+        mut alert: RiskAlert,
+        rule_id: &str,
+        subject: Option<&str>,
+        magnitude: f64,
+    ) -> AlertDecision {
+        let now = alert.timestamp;
+        let key = (
+            alert.wallet.clone(),
+            rule_id.to_string(),
+            subject.map(str::to_string),
+        );
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+
+        if records.len() >= PRUNE_THRESHOLD {
+            let cooldown = self.cooldown;
+            records.retain(|_, record| now - record.sent_at < cooldown);
+        }
+
+        let escalated = match records.get_mut(&key) {
+            Some(record) if now - record.sent_at < self.cooldown => {
+                let worse = alert.severity > record.severity
+                    || magnitude >= record.magnitude * ESCALATION_FACTOR;
+                if !worse {
+                    record.suppressed += 1;
+                    return AlertDecision::Suppress;
+                }
+
+                // Step 5: Raise severity at least one level above the last alert sent
+                let previous = record.severity.clone();
+                alert.severity = alert.severity.clone().max(previous.escalate());
+                annotate(&mut alert, "escalated_from", serde_json::json!(previous));
+                true
+            }
+            Some(record) => {
+                // Cooldown over: report what was swallowed in between
+                if record.suppressed > 0 {
+                    annotate(
+                        &mut alert,
+                        "suppressed_repeats",
+                        serde_json::json!(record.suppressed),
+                    );
+                }
+                false
+            }
+            None => false,
+        };
+
+        records.insert(
+            key,
+            AlertRecord {
+                sent_at: now,
+                severity: alert.severity.clone(),
+                magnitude,
+                suppressed: 0,
+            },
+        );
+
+        if escalated {
+            AlertDecision::Escalate(alert)
+        } else {
+            AlertDecision::Emit(alert)
+        }
+    }
+
+    // Step 6: AI-sourced alerts without enough confidence are dropped. Alerts that carry no
+    // confidence come from local heuristics and are kept.
+    pub fn meets_confidence(&self, confidence: Option<f64>) -> bool {
+        confidence.is_none_or(|confidence| confidence >= self.min_confidence)
+    }
+}
+
+fn annotate(alert: &mut RiskAlert, key: &str, value: serde_json::Value) {
+    if let serde_json::Value::Object(metadata) = &mut alert.metadata {
+        metadata.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deduplicator() -> AlertDeduplicator {
+        AlertDeduplicator {
+            cooldown: chrono::Duration::minutes(60),
+            min_confidence: 0.7,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn alert(severity: AlertSeverity, minutes: i64) -> RiskAlert {
+        let mut alert = RiskAlert::new("wallet".to_string(), severity, "drop".to_string(), None);
+        alert.timestamp = chrono::DateTime::UNIX_EPOCH + chrono::Duration::minutes(minutes);
+        alert
+    }
+
+    #[test]
+    fn repeats_inside_the_cooldown_are_suppressed_and_counted() {
+        let dedup = deduplicator();
+        let admit = |minutes| {
+            dedup.admit(
+                alert(AlertSeverity::Medium, minutes),
+                "drawdown",
+                None,
+                10.0,
+            )
+        };
+
+        assert!(matches!(admit(0), AlertDecision::Emit(_)));
+        assert!(matches!(admit(10), AlertDecision::Suppress));
+        assert!(matches!(admit(59), AlertDecision::Suppress));
+
+        let AlertDecision::Emit(alert) = admit(60) else {
+            panic!("expected the alert once the cooldown is over");
+        };
+        assert_eq!(alert.metadata["suppressed_repeats"], 2);
+        assert!(matches!(admit(61), AlertDecision::Suppress));
+    }
+
+    #[test]
+    fn a_worse_repeat_escalates_one_level() {
+        let dedup = deduplicator();
+        let admit = |minutes, magnitude| {
+            dedup.admit(
+                alert(AlertSeverity::Medium, minutes),
+                "drawdown",
+                None,
+                magnitude,
+            )
+        };
+
+        assert!(matches!(admit(0, 10.0), AlertDecision::Emit(_)));
+        assert!(matches!(admit(1, 14.9), AlertDecision::Suppress));
+
+        let AlertDecision::Escalate(alert) = admit(2, 15.0) else {
+            panic!("expected a 1.5x magnitude to escalate");
+        };
+        assert_eq!(alert.severity, AlertSeverity::High);
+        assert_eq!(alert.metadata["escalated_from"], "medium");
+
+        // The escalation becomes the new baseline
+        assert!(matches!(admit(3, 20.0), AlertDecision::Suppress));
+        let AlertDecision::Escalate(alert) = admit(4, 22.5) else {
+            panic!("expected a 1.5x magnitude to escalate");
+        };
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+    }
+
+    #[test]
+    fn a_higher_severity_escalates_regardless_of_magnitude() {
+        let dedup = deduplicator();
+        dedup.admit(alert(AlertSeverity::Low, 0), "drawdown", None, 10.0);
+
+        let AlertDecision::Escalate(alert) =
+            dedup.admit(alert(AlertSeverity::High, 1), "drawdown", None, 1.0)
+        else {
+            panic!("expected a higher severity to escalate");
+        };
+        assert_eq!(alert.severity, AlertSeverity::High);
+    }
+
+    #[test]
+    fn subjects_and_rules_are_deduplicated_separately() {
+        let dedup = deduplicator();
+        let admit =
+            |rule, subject| dedup.admit(alert(AlertSeverity::Medium, 0), rule, subject, 10.0);
+
+        assert!(matches!(
+            admit("crash", Some("mint-a")),
+            AlertDecision::Emit(_)
+        ));
+        assert!(matches!(
+            admit("crash", Some("mint-b")),
+            AlertDecision::Emit(_)
+        ));
+        assert!(matches!(
+            admit("crash", Some("mint-a")),
+            AlertDecision::Suppress
+        ));
+        assert!(matches!(admit("crash", None), AlertDecision::Emit(_)));
+        assert!(matches!(admit("drawdown", None), AlertDecision::Emit(_)));
+    }
+
+    #[test]
+    fn only_ai_alerts_need_confidence() {
+        let dedup = deduplicator();
+        assert!(dedup.meets_confidence(None));
+        assert!(dedup.meets_confidence(Some(0.7)));
+        assert!(!dedup.meets_confidence(Some(0.69)));
+    }
+}
@@ -0,0 +1,420 @@
+// backend/src/pipeline/alert_store.rs
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use crate::config::RiskConfig;
+use crate::models::risk_alert::{AlertSeverity, AlertStatus, AlertTransitionError, RiskAlert};
+use crate::pipeline::debounced_writer::DebouncedWriter;
+use crate::ws::hub::{WsHub, WsMessage};
+use crate::BackendResult;
+
+// How often expired snoozes are reactivated and alerts past retention dropped
+const MAINTENANCE_INTERVAL_SECS: u64 = 60;
+
+// Step 1: Listing filters; unset fields match every alert
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AlertFilter {
+    pub wallet: Option<String>,
+    pub severity: Option<AlertSeverity>,
+    pub status: Option<AlertStatus>,
+}
+
+impl AlertFilter {
+    fn matches(&self, alert: &RiskAlert) -> bool {
+        self.wallet
+            .as_ref()
+            .is_none_or(|wallet| &alert.wallet == wallet)
+            && self
+                .severity
+                .as_ref()
+                .is_none_or(|severity| &alert.severity == severity)
+            && self.status.is_none_or(|status| alert.status == status)
+    }
+}
+
+// Step 2: A status change requested through the API
+#[derive(Debug, Clone)]
+pub enum AlertAction {
+    Acknowledge {
+        assignee: Option<String>,
+    },
+    Resolve {
+        notes: Option<String>,
+        assignee: Option<String>,
+    },
+    Snooze {
+        until: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+// Why a status change failed
+#[derive(Debug)]
+pub enum AlertUpdateError {
+    NotFound(String),
+    Transition(AlertTransitionError),
+}
+
+impl std::fmt::Display for AlertUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "No alert {}", id),
+            Self::Transition(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AlertUpdateError {}
+
+// Step 3: Raised alerts with their lifecycle status, newest last. Bounded by count and age,
+// persisted as JSON in the background when a path is configured. Status changes go out on
+// the hub.
+#[derive(Clone)]
+pub struct AlertStore {
+    path: Option<String>,
+    max_entries: usize,
+    retention: chrono::Duration,
+    alerts: Arc<RwLock<VecDeque<RiskAlert>>>,
+    writer: DebouncedWriter,
+    ws_hub: WsHub,
+}
+
+impl AlertStore {
+    pub fn new(config: &RiskConfig, ws_hub: WsHub) -> Self {
+        Self {
+            path: config.alerts_path.clone(),
+            max_entries: config.alert_max_entries,
+            retention: chrono::Duration::from_std(config.alert_retention())
+                .unwrap_or(chrono::Duration::MAX),
+            alerts: Arc::new(RwLock::new(VecDeque::new())),
+            writer: DebouncedWriter::new(config.alerts_path.clone()),
+            ws_hub,
+        }
+    }
+
+    // Load the alerts saved by a previous run, replacing what is held; returns how many
+    pub async fn restore(&self) -> BackendResult<usize> {
+        let Some(path) = &self.path else {
+            return Ok(0);
+        };
+        let restored: VecDeque<RiskAlert> = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut alerts = self.alerts.write().await;
+        *alerts = restored;
+        self.prune(&mut alerts, chrono::Utc::now());
+        Ok(alerts.len())
+    }
+
+    // Step 4: Keep a freshly raised alert; the oldest are dropped once the store is full
+    pub async fn record(&self, alert: RiskAlert) {
+        let mut alerts = self.alerts.write().await;
+        self.prune(&mut alerts, alert.timestamp);
+        while alerts.len() >= self.max_entries {
+            alerts.pop_front();
+        }
+        alerts.push_back(alert);
+        self.writer.mark_dirty();
+    }
+
+    // Step 5: Newest first; returns the number of matches along with the requested page
+    pub async fn list(
+        &self,
+        filter: &AlertFilter,
+        offset: usize,
+        limit: usize,
+    ) -> (usize, Vec<RiskAlert>) {
+        let alerts = self.alerts.read().await;
+        let matching = alerts.iter().rev().filter(|alert| filter.matches(alert));
+        let total = matching.clone().count();
+        let page = matching.skip(offset).take(limit).cloned().collect();
+        (total, page)
+    }
+
+    pub async fn get(&self, id: &str) -> Option<RiskAlert> {
+        self.alerts
+            .read()
+            .await
+            .iter()
+            .find(|alert| alert.id == id)
+            .cloned()
+    }
+
+    // True while an alert of `rule_id` for `wallet` is snoozed; repeats stay quiet until then
+    pub async fn is_snoozed(&self, wallet: &str, rule_id: &str) -> bool {
+        let now = chrono::Utc::now();
+        self.alerts.read().await.iter().rev().any(|alert| {
+            alert.status == AlertStatus::Snoozed
+                && alert.snoozed_until.is_some_and(|until| until > now)
+                && alert.wallet == wallet
+                && alert.metadata.get("rule_id").and_then(|id| id.as_str()) == Some(rule_id)
+        })
+    }
+
+    // Step 6: Apply a status change and announce it
+    pub async fn update(
+        &self,
+        id: &str,
+        action: AlertAction,
+    ) -> Result<RiskAlert, AlertUpdateError> {
+        let now = chrono::Utc::now();
+        let mut alerts = self.alerts.write().await;
+        let alert = alerts
+            .iter_mut()
+            .find(|alert| alert.id == id)
+            .ok_or_else(|| AlertUpdateError::NotFound(id.to_string()))?;
+        match action {
+            AlertAction::Acknowledge { assignee } => alert.acknowledge(assignee, now),
+            AlertAction::Resolve { notes, assignee } => alert.resolve(notes, assignee, now),
+            AlertAction::Snooze { until } => alert.snooze(until, now),
+        }
+        .map_err(AlertUpdateError::Transition)?;
+
+        let alert = alert.clone();
+        self.writer.mark_dirty();
+        self.broadcast_status(&alert);
+        Ok(alert)
+    }
+
+    // Write alerts still inside the debounce window, e.g. on shutdown
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+
+    // Step 7: Background work: persistence, reactivating expired snoozes (only done here, so
+    // reads never need the write lock) and dropping alerts past retention
+    pub fn spawn_maintenance(&self) {
+        self.writer.spawn(self.alerts.clone(), "risk alerts");
+
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(MAINTENANCE_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now();
+                let mut alerts = store.alerts.write().await;
+                let before = alerts.len();
+                store.prune(&mut alerts, now);
+                if store.wake_expired(&mut alerts, now) || alerts.len() != before {
+                    store.writer.mark_dirty();
+                }
+            }
+        });
+    }
+
+    fn prune(&self, alerts: &mut VecDeque<RiskAlert>, now: chrono::DateTime<chrono::Utc>) {
+        let retention = self.retention;
+        alerts.retain(|alert| now - alert.timestamp < retention);
+    }
+
+    // Returns true when something woke up
+    fn wake_expired(
+        &self,
+        alerts: &mut VecDeque<RiskAlert>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        let mut woke = false;
+        for alert in alerts.iter_mut() {
+            if alert.wake(now) {
+                self.broadcast_status(alert);
+                woke = true;
+            }
+        }
+        woke
+    }
+
+    fn broadcast_status(&self, alert: &RiskAlert) {
+        let _ = self.ws_hub.broadcast(WsMessage {
+            message_type: "risk_alert_status".to_string(),
+            payload: serde_json::json!({
+                "id": alert.id,
+                "wallet": alert.wallet,
+                "status": alert.status,
+                "assignee": alert.assignee,
+                "resolution_notes": alert.resolution_notes,
+                "snoozed_until": alert.snoozed_until,
+            }),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> AlertStore {
+        let config = RiskConfig {
+            cooldown_minutes: 60,
+            alert_retention_days: 30,
+            alerts_path: None,
+            alert_max_entries: 100,
+            min_confidence: 0.7,
+            rules_path: None,
+            state_path: None,
+            state_snapshot_secs: 60,
+            state_max_wallets: 100,
+        };
+        AlertStore::new(&config, WsHub::new())
+    }
+
+    fn alert(wallet: &str, rule_id: &str) -> RiskAlert {
+        RiskAlert::new(
+            wallet.to_string(),
+            AlertSeverity::High,
+            format!("{} tripped", rule_id),
+            Some(serde_json::json!({ "rule_id": rule_id })),
+        )
+    }
+
+    async fn recorded(store: &AlertStore, wallet: &str, rule_id: &str) -> String {
+        let alert = alert(wallet, rule_id);
+        let id = alert.id.clone();
+        store.record(alert).await;
+        id
+    }
+
+    #[tokio::test]
+    async fn a_resolved_alert_is_final() {
+        let store = store();
+        let id = recorded(&store, "a", "drawdown").await;
+        let resolve = AlertAction::Resolve {
+            notes: Some("hedged".to_string()),
+            assignee: Some("ops".to_string()),
+        };
+        let resolved = store.update(&id, resolve).await.unwrap();
+        assert_eq!(resolved.status, AlertStatus::Resolved);
+        assert_eq!(resolved.resolution_notes.as_deref(), Some("hedged"));
+
+        let acknowledge = AlertAction::Acknowledge { assignee: None };
+        let snooze = AlertAction::Snooze {
+            until: chrono::Utc::now() + chrono::Duration::hours(1),
+        };
+        for action in [acknowledge, snooze] {
+            assert!(matches!(
+                store.update(&id, action).await,
+                Err(AlertUpdateError::Transition(
+                    AlertTransitionError::AlreadyResolved
+                ))
+            ));
+        }
+        assert_eq!(store.get(&id).await.unwrap().status, AlertStatus::Resolved);
+    }
+
+    #[tokio::test]
+    async fn snoozing_into_the_past_is_refused() {
+        let store = store();
+        let id = recorded(&store, "a", "drawdown").await;
+        let snooze = AlertAction::Snooze {
+            until: chrono::Utc::now() - chrono::Duration::minutes(1),
+        };
+
+        assert!(matches!(
+            store.update(&id, snooze).await,
+            Err(AlertUpdateError::Transition(
+                AlertTransitionError::SnoozeInPast(_)
+            ))
+        ));
+        assert_eq!(store.get(&id).await.unwrap().status, AlertStatus::Active);
+        assert!(!store.is_snoozed("a", "drawdown").await);
+    }
+
+    #[tokio::test]
+    async fn a_snoozed_alert_wakes_once_it_expires() {
+        let store = store();
+        let mut updates = store.ws_hub.subscribe();
+        let id = recorded(&store, "a", "drawdown").await;
+        let until = chrono::Utc::now() + chrono::Duration::minutes(10);
+        store
+            .update(&id, AlertAction::Snooze { until })
+            .await
+            .unwrap();
+        assert!(store.is_snoozed("a", "drawdown").await);
+        assert!(!store.is_snoozed("a", "leverage").await);
+        assert!(!store.is_snoozed("b", "drawdown").await);
+        assert_eq!(updates.try_recv().unwrap().payload["status"], "snoozed");
+
+        let mut alerts = store.alerts.write().await;
+        assert!(!store.wake_expired(&mut alerts, until - chrono::Duration::seconds(1)));
+        assert!(store.wake_expired(&mut alerts, until));
+        assert_eq!(alerts[0].status, AlertStatus::Active);
+        assert_eq!(alerts[0].snoozed_until, None);
+        drop(alerts);
+
+        assert!(!store.is_snoozed("a", "drawdown").await);
+        assert_eq!(updates.try_recv().unwrap().payload["status"], "active");
+    }
+
+    #[tokio::test]
+    async fn filters_combine_with_the_page() {
+        let store = store();
+        let mut ids = Vec::new();
+        for i in 0..6 {
+            let wallet = if i % 2 == 0 { "a" } else { "b" };
+            ids.push(recorded(&store, wallet, "drawdown").await);
+        }
+        // a: ids 0, 2, 4; acknowledge the middle one
+        store
+            .update(&ids[2], AlertAction::Acknowledge { assignee: None })
+            .await
+            .unwrap();
+
+        let filter = AlertFilter {
+            wallet: Some("a".to_string()),
+            status: Some(AlertStatus::Active),
+            ..AlertFilter::default()
+        };
+        let (total, page) = store.list(&filter, 0, 10).await;
+        assert_eq!(total, 2);
+        let listed: Vec<&str> = page.iter().map(|alert| alert.id.as_str()).collect();
+        assert_eq!(listed, vec![ids[4].as_str(), ids[0].as_str()]);
+
+        // The total counts every match, whatever the page
+        let (total, page) = store.list(&filter, 1, 1).await;
+        assert_eq!(total, 2);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, ids[0]);
+        let (total, page) = store.list(&filter, 2, 10).await;
+        assert_eq!(total, 2);
+        assert!(page.is_empty());
+
+        let (total, page) = store.list(&AlertFilter::default(), 1, 3).await;
+        assert_eq!(total, 6);
+        let listed: Vec<&str> = page.iter().map(|alert| alert.id.as_str()).collect();
+        assert_eq!(
+            listed,
+            vec![ids[4].as_str(), ids[3].as_str(), ids[2].as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn alerts_past_retention_are_pruned() {
+        let store = store();
+        let mut stale = alert("a", "drawdown");
+        stale.timestamp = chrono::Utc::now() - chrono::Duration::days(31);
+        let mut recent = alert("a", "drawdown");
+        recent.timestamp = chrono::Utc::now() - chrono::Duration::days(29);
+        let recent_id = recent.id.clone();
+        store.record(stale).await;
+        store.record(recent).await;
+
+        // Recording prunes relative to the new alert
+        let fresh_id = recorded(&store, "b", "leverage").await;
+        let (total, page) = store.list(&AlertFilter::default(), 0, 10).await;
+        assert_eq!(total, 2);
+        assert_eq!(page[0].id, fresh_id);
+        assert_eq!(page[1].id, recent_id);
+
+        // So does maintenance, once the recent one ages out too
+        let mut alerts = store.alerts.write().await;
+        store.prune(&mut alerts, chrono::Utc::now() + chrono::Duration::days(2));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, fresh_id);
+    }
+}
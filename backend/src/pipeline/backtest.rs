@@ -0,0 +1,402 @@
+// backend/src/pipeline/backtest.rs
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::config::{Config, ReplaySpeed};
+use crate::integration::idl::IdlRegistry;
+use crate::integration::replay::{ReplaySource, ReplayStats};
+use crate::models::event::PortfolioEvent;
+use crate::models::risk_alert::RiskAlert;
+use crate::pipeline::mpsc_queue::EventQueue;
+use crate::pipeline::rule_set::RuleSet;
+use crate::pipeline::rules_engine::RulesEngine;
+use crate::services::metrics::MetricsService;
+use crate::ws::hub::WsHub;
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Events buffered between the replay and the engines
+const LOAD_QUEUE_CAPACITY: usize = 1024;
+
+// Step 1: Alerts one rule set raised over the recording
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BacktestReport {
+    pub source: Option<String>, // Rules file, `None` for built-in or inline rules
+    pub alerts: usize,
+    pub by_rule: BTreeMap<String, usize>,
+    pub by_wallet: BTreeMap<String, usize>,
+    pub by_day: BTreeMap<String, usize>, // UTC day of the event that raised the alert
+}
+
+impl BacktestReport {
+    fn record(&mut self, alert: &RiskAlert) {
+        let rule_id = alert
+            .metadata
+            .get("rule_id")
+            .and_then(|id| id.as_str())
+            .unwrap_or("unknown");
+
+        self.alerts += 1;
+        *self.by_rule.entry(rule_id.to_string()).or_default() += 1;
+        *self.by_wallet.entry(alert.wallet.clone()).or_default() += 1;
+        *self
+            .by_day
+            .entry(alert.timestamp.format("%Y-%m-%d").to_string())
+            .or_default() += 1;
+    }
+}
+
+// Step 2: How a rule differs between the candidate and the active rule set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleChange {
+    Added,    // Only in the candidate
+    Removed,  // Only in the active rule set
+    Modified, // Same id, different definition
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleDiff {
+    pub rule_id: String,
+    pub change: RuleChange,
+    pub candidate_alerts: usize,
+    pub active_alerts: usize,
+    pub delta: i64, // Candidate minus active
+}
+
+// Step 3: Outcome of a backtest run
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestResult {
+    pub events_path: String,
+    pub events: usize,
+    pub skipped: usize,    // Lines that could not be parsed or normalized
+    pub duplicates: usize, // Notifications for an already replayed transaction
+    pub first_event_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_event_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub candidate: BacktestReport,
+    pub active: BacktestReport,
+    pub diff: Vec<RuleDiff>,
+    pub wallets_added: Vec<String>,   // Alerted by the candidate only
+    pub wallets_cleared: Vec<String>, // Alerted by the active rules only
+}
+
+// Step 4: Runs rule sets over a recorded event file. Each run gets its own engine, so
+// nothing is broadcast, stored or counted in the live metrics, and the live windows are
+// left untouched.
+pub struct Backtest {
+    config: Config,
+    idls: Arc<IdlRegistry>,
+    events_path: String,
+}
+
+impl Backtest {
+    pub fn new(config: &Config, idls: Arc<IdlRegistry>, events_path: impl Into<String>) -> Self {
+        Self {
+            config: config.clone(),
+            idls,
+            events_path: events_path.into(),
+        }
+    }
+
+    // Step 5: Evaluate both rule sets over the same events and compare them rule by rule
+    pub async fn run(
+        &self,
+        candidate: &RuleSet,
+        active: &RuleSet,
+    ) -> Result<BacktestResult, ThreadSafeError> {
+        let (span, stats, candidate_report, active_report) =
+            self.evaluate(candidate, active).await?;
+
+        let rule_ids: BTreeSet<&str> = candidate
+            .rules
+            .iter()
+            .chain(&active.rules)
+            .map(|rule| rule.id.as_str())
+            .collect();
+        let diff = rule_ids
+            .into_iter()
+            .map(|rule_id| {
+                let find = |rule_set: &RuleSet| {
+                    rule_set
+                        .rules
+                        .iter()
+                        .find(|rule| rule.id == rule_id)
+                        .map(serde_json::to_value)
+                        .and_then(Result::ok)
+                };
+                let change = match (find(candidate), find(active)) {
+                    (Some(_), None) => RuleChange::Added,
+                    (None, _) => RuleChange::Removed,
+                    (Some(candidate), Some(active)) if candidate != active => RuleChange::Modified,
+                    _ => RuleChange::Unchanged,
+                };
+                let candidate_alerts = candidate_report.by_rule.get(rule_id).copied().unwrap_or(0);
+                let active_alerts = active_report.by_rule.get(rule_id).copied().unwrap_or(0);
+
+                RuleDiff {
+                    rule_id: rule_id.to_string(),
+                    change,
+                    candidate_alerts,
+                    active_alerts,
+                    delta: candidate_alerts as i64 - active_alerts as i64,
+                }
+            })
+            .collect();
+
+        let wallets_added = candidate_report
+            .by_wallet
+            .keys()
+            .filter(|wallet| !active_report.by_wallet.contains_key(*wallet))
+            .cloned()
+            .collect();
+        let wallets_cleared = active_report
+            .by_wallet
+            .keys()
+            .filter(|wallet| !candidate_report.by_wallet.contains_key(*wallet))
+            .cloned()
+            .collect();
+
+        Ok(BacktestResult {
+            events_path: self.events_path.clone(),
+            events: span.events,
+            skipped: stats.skipped,
+            duplicates: stats.duplicates,
+            first_event_at: span.first,
+            last_event_at: span.last,
+            candidate: candidate_report,
+            active: active_report,
+            diff,
+            wallets_added,
+            wallets_cleared,
+        })
+    }
+
+    // Step 6: Replay the file at full speed into a private queue and hand each event to
+    // both engines as it arrives, so the recording is never held in memory
+    async fn evaluate(
+        &self,
+        candidate: &RuleSet,
+        active: &RuleSet,
+    ) -> Result<(EventSpan, ReplayStats, BacktestReport, BacktestReport), ThreadSafeError> {
+        let queue = EventQueue::new(LOAD_QUEUE_CAPACITY);
+        let replay = ReplaySource::new(&self.config, self.idls.clone(), queue.clone())?
+            .with_path(self.events_path.clone())
+            .with_speed(ReplaySpeed::Max);
+
+        let producer = async {
+            let stats = replay.run().await;
+            queue.close();
+            stats
+        };
+        let consumer = async {
+            let mut candidate = Evaluation::new(&self.config, candidate);
+            let mut active = Evaluation::new(&self.config, active);
+            let mut span = EventSpan::default();
+            while let Some(event) = queue.recv().await {
+                span.record(&event);
+                candidate.process(&event).await;
+                active.process(&event).await;
+            }
+            (span, candidate.report, active.report)
+        };
+        let (stats, (span, candidate, active)) = tokio::join!(producer, consumer);
+
+        Ok((span, stats?, candidate, active))
+    }
+}
+
+// How many events were replayed and the time they cover
+#[derive(Debug, Default)]
+struct EventSpan {
+    events: usize,
+    first: Option<chrono::DateTime<chrono::Utc>>,
+    last: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl EventSpan {
+    fn record(&mut self, event: &PortfolioEvent) {
+        let at = *event.timestamp();
+        self.events += 1;
+        self.first = Some(self.first.map_or(at, |first| first.min(at)));
+        self.last = Some(self.last.map_or(at, |last| last.max(at)));
+    }
+}
+
+// Step 7: A fresh engine per rule set; alerts carry their event's time so cooldowns
+// and daily counts follow the recording
+struct Evaluation {
+    engine: RulesEngine,
+    report: BacktestReport,
+}
+
+impl Evaluation {
+    fn new(config: &Config, rule_set: &RuleSet) -> Self {
+        Self {
+            engine: RulesEngine::new(&config.risk, WsHub::new(), MetricsService::new())
+                .with_rule_set(rule_set.clone())
+                .with_event_time(),
+            report: BacktestReport {
+                source: rule_set.source.clone(),
+                ..BacktestReport::default()
+            },
+        }
+    }
+
+    async fn process(&mut self, event: &PortfolioEvent) {
+        for alert in self.engine.process_event(event).await {
+            self.report.record(&alert);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::risk_alert::AlertSeverity;
+    use crate::pipeline::rule_set::{RuleCondition, RuleDefinition};
+
+    // Two days of activity; the unparseable line is counted as skipped
+    const RECORDING: &str = r#"{"PositionUpdate":{"wallet":"alice","mint":"sol","pnl_delta":-500.0,"timestamp":"2024-03-01T10:00:00Z"}}
+{"PositionUpdate":{"wallet":"bob","mint":"sol","pnl_delta":-150.0,"timestamp":"2024-03-01T11:00:00Z"}}
+not json
+{"PositionUpdate":{"wallet":"alice","mint":"sol","pnl_delta":-600.0,"timestamp":"2024-03-02T09:00:00Z"}}
+{"PositionUpdate":{"wallet":"carol","mint":"sol","pnl_delta":50.0,"timestamp":"2024-03-02T09:30:00Z"}}
+{"StakeChanged":{"wallet":"dave","stake_account":"stake","validator":null,"delta_lamports":5000,"timestamp":"2024-03-02T10:00:00Z"}}
+"#;
+
+    fn recording() -> String {
+        let path = std::env::temp_dir()
+            .join(format!("backtest_{}.jsonl", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        std::fs::write(&path, RECORDING).unwrap();
+        path
+    }
+
+    fn config() -> Config {
+        let mut config = Config::load();
+        config.risk.cooldown_minutes = 60;
+        config.risk.min_confidence = 0.0;
+        config.risk.alerts_path = None;
+        config.risk.state_path = None;
+        config
+    }
+
+    fn rule(id: &str, condition: RuleCondition) -> RuleDefinition {
+        RuleDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            enabled: true,
+            severity: AlertSeverity::High,
+            message: "{wallet} tripped {rule_id}".to_string(),
+            condition,
+        }
+    }
+
+    fn pnl(id: &str, min_abs: f64) -> RuleDefinition {
+        rule(id, RuleCondition::PnlDelta { min_abs })
+    }
+
+    fn counts(entries: &[(&str, usize)]) -> BTreeMap<String, usize> {
+        entries
+            .iter()
+            .map(|(key, count)| (key.to_string(), *count))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn candidate_and_active_rules_are_compared_over_one_recording() {
+        let active = RuleSet {
+            source: Some("rules.toml".to_string()),
+            rules: vec![
+                pnl("swing", 400.0),
+                pnl("big", 550.0),
+                rule(
+                    "stake",
+                    RuleCondition::StakeChange {
+                        min_abs_lamports: 1_000,
+                    },
+                ),
+            ],
+        };
+        let candidate = RuleSet {
+            source: None,
+            rules: vec![pnl("swing", 100.0), pnl("big", 550.0), pnl("small", 40.0)],
+        };
+        let path = recording();
+        let backtest = Backtest::new(&config(), Arc::new(IdlRegistry::default()), &path);
+
+        let result = backtest.run(&candidate, &active).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(result.events, 5);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(
+            result.first_event_at.unwrap().to_rfc3339(),
+            "2024-03-01T10:00:00+00:00"
+        );
+        assert_eq!(
+            result.last_event_at.unwrap().to_rfc3339(),
+            "2024-03-02T10:00:00+00:00"
+        );
+
+        // Alice's second swing lands a day later, past the cooldown
+        let candidate = &result.candidate;
+        assert_eq!(candidate.source, None);
+        assert_eq!(candidate.alerts, 8);
+        assert_eq!(
+            candidate.by_rule,
+            counts(&[("big", 1), ("small", 4), ("swing", 3)])
+        );
+        assert_eq!(
+            candidate.by_wallet,
+            counts(&[("alice", 5), ("bob", 2), ("carol", 1)])
+        );
+        assert_eq!(
+            candidate.by_day,
+            counts(&[("2024-03-01", 4), ("2024-03-02", 4)])
+        );
+
+        let active = &result.active;
+        assert_eq!(active.source.as_deref(), Some("rules.toml"));
+        assert_eq!(active.alerts, 4);
+        assert_eq!(
+            active.by_rule,
+            counts(&[("big", 1), ("stake", 1), ("swing", 2)])
+        );
+        assert_eq!(active.by_wallet, counts(&[("alice", 3), ("dave", 1)]));
+        assert_eq!(
+            active.by_day,
+            counts(&[("2024-03-01", 1), ("2024-03-02", 3)])
+        );
+
+        let diff: Vec<(&str, RuleChange, usize, usize, i64)> = result
+            .diff
+            .iter()
+            .map(|rule| {
+                (
+                    rule.rule_id.as_str(),
+                    rule.change,
+                    rule.candidate_alerts,
+                    rule.active_alerts,
+                    rule.delta,
+                )
+            })
+            .collect();
+        assert_eq!(
+            diff,
+            vec![
+                ("big", RuleChange::Unchanged, 1, 1, 0),
+                ("small", RuleChange::Added, 4, 0, 4),
+                ("stake", RuleChange::Removed, 0, 1, -1),
+                ("swing", RuleChange::Modified, 3, 2, 1),
+            ]
+        );
+
+        assert_eq!(result.wallets_added, vec!["bob", "carol"]);
+        assert_eq!(result.wallets_cleared, vec!["dave"]);
+    }
+}
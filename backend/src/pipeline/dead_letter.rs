@@ -0,0 +1,485 @@
+// backend/src/pipeline/dead_letter.rs
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::PipelineConfig;
+use crate::integration::normalizer::{EventNormalizer, RawEvent};
+use crate::models::event::{ChainSource, PortfolioEvent};
+use crate::pipeline::debounced_writer::DebouncedWriter;
+use crate::pipeline::mpsc_queue::EventQueue;
+use crate::services::metrics::MetricsService;
+use crate::services::solana_client::TransactionInstruction;
+use crate::BackendResult;
+
+// Step 1: Where in the pipeline an event failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterStage {
+    Normalization,
+    Processing,
+}
+
+impl DeadLetterStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normalization => "normalization",
+            Self::Processing => "processing",
+        }
+    }
+}
+
+// The payload exactly as it was when it failed, so a retry starts from the same input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum DeadLetterPayload {
+    RawEvent(RawEvent),
+    Notification(serde_json::Value),
+    // One `Program data:` line; the rest of its transaction was delivered
+    ProgramLog {
+        program_id: String,
+        log: String,
+        source: ChainSource,
+    },
+    // One top-level instruction of an IDL-described program
+    Instruction {
+        instruction: TransactionInstruction,
+        source: ChainSource,
+    },
+    Event(PortfolioEvent),
+}
+
+// Step 2: A parked event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    pub stage: DeadLetterStage,
+    pub payload: DeadLetterPayload,
+    pub error: String, // Most recent failure
+    pub attempts: u32,
+    pub first_failed_at: chrono::DateTime<chrono::Utc>,
+    pub last_failed_at: chrono::DateTime<chrono::Utc>,
+    // A processing failure back in the pipeline; kept until it is processed or fails again
+    #[serde(default)]
+    pub requeued: bool,
+}
+
+// Result of retrying one entry
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryOutcome {
+    pub id: Uuid,
+    pub succeeded: bool,
+    pub attempts: u32,
+    pub error: Option<String>,
+    pub events_emitted: usize,
+}
+
+// Step 3: Bounded store of failed events, persisted as JSON in the background when a path
+// is configured
+#[derive(Clone)]
+pub struct DeadLetterQueue {
+    max_entries: usize,
+    entries: Arc<RwLock<VecDeque<DeadLetter>>>, // Oldest first
+    requeued: Arc<AtomicUsize>, // Entries awaiting processing, to skip `resolve` lookups
+    writer: DebouncedWriter,
+    metrics: MetricsService,
+}
+
+impl DeadLetterQueue {
+    pub async fn open(config: &PipelineConfig, metrics: MetricsService) -> BackendResult<Self> {
+        let entries: VecDeque<DeadLetter> = match &config.dead_letter_path {
+            Some(path) => match tokio::fs::read_to_string(path).await {
+                Ok(contents) => serde_json::from_str(&contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+                Err(e) => return Err(e.into()),
+            },
+            None => VecDeque::new(),
+        };
+        if !entries.is_empty() {
+            tracing::warn!("📮 Loaded {} dead-lettered event(s)", entries.len());
+        }
+        metrics.set_dead_letter_depth(entries.len()).await;
+
+        let requeued = entries.iter().filter(|entry| entry.requeued).count();
+        let entries = Arc::new(RwLock::new(entries));
+        let writer = DebouncedWriter::new(config.dead_letter_path.clone());
+        writer.spawn(entries.clone(), "dead-letter queue");
+
+        Ok(Self {
+            max_entries: config.dead_letter_max_entries,
+            entries,
+            requeued: Arc::new(AtomicUsize::new(requeued)),
+            writer,
+            metrics,
+        })
+    }
+
+    // Step 4: Park a failed event; the oldest entry is evicted once the queue is full. A
+    // requeued event that fails processing again lands back on its own entry.
+    pub async fn push(
+        &self,
+        stage: DeadLetterStage,
+        payload: DeadLetterPayload,
+        error: impl Into<String>,
+    ) -> Uuid {
+        let now = chrono::Utc::now();
+        let error = error.into();
+
+        let (id, depth) = {
+            let mut entries = self.entries.write().await;
+            if let Some(entry) = Self::find_requeued(&mut entries, &payload) {
+                entry.requeued = false;
+                entry.error = error;
+                entry.last_failed_at = now;
+                self.requeued.fetch_sub(1, Ordering::Relaxed);
+                (entry.id, entries.len())
+            } else {
+                let entry = DeadLetter {
+                    id: Uuid::new_v4(),
+                    stage,
+                    payload,
+                    error,
+                    attempts: 1,
+                    first_failed_at: now,
+                    last_failed_at: now,
+                    requeued: false,
+                };
+                let id = entry.id;
+                while entries.len() >= self.max_entries {
+                    if let Some(evicted) = entries.pop_front() {
+                        if evicted.requeued {
+                            self.requeued.fetch_sub(1, Ordering::Relaxed);
+                        }
+                        tracing::warn!("⚠️ Dead-letter queue full, evicting {}", evicted.id);
+                    }
+                }
+                entries.push_back(entry);
+                (id, entries.len())
+            }
+        };
+        self.writer.mark_dirty();
+        self.metrics.record_dead_letter(stage.as_str(), depth).await;
+        id
+    }
+
+    // A processed event clears the entry it was requeued from
+    pub async fn resolve(&self, event: &PortfolioEvent) {
+        if self.requeued.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut entries = self.entries.write().await;
+        let Some(position) = entries.iter().position(|entry| {
+            entry.requeued && matches!(&entry.payload, DeadLetterPayload::Event(e) if e == event)
+        }) else {
+            return;
+        };
+        entries.remove(position);
+        self.requeued.fetch_sub(1, Ordering::Relaxed);
+        self.writer.mark_dirty();
+        self.metrics.set_dead_letter_depth(entries.len()).await;
+    }
+
+    fn find_requeued<'a>(
+        entries: &'a mut VecDeque<DeadLetter>,
+        payload: &DeadLetterPayload,
+    ) -> Option<&'a mut DeadLetter> {
+        let DeadLetterPayload::Event(event) = payload else {
+            return None;
+        };
+        entries.iter_mut().find(|entry| {
+            entry.requeued && matches!(&entry.payload, DeadLetterPayload::Event(e) if e == event)
+        })
+    }
+
+    // Step 5: Inspection
+    pub async fn list(&self, stage: Option<DeadLetterStage>, limit: usize) -> Vec<DeadLetter> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .filter(|entry| stage.is_none_or(|stage| entry.stage == stage))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<DeadLetter> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+    }
+
+    pub async fn depth(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    // Write entries still inside the debounce window, e.g. on shutdown
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+
+    // Step 6: Re-run the stage that failed and requeue the resulting events; an event that
+    // failed processing is requeued as is. A normalization entry leaves the queue once every
+    // event was accepted; a processing entry only once its event is processed. Otherwise it
+    // stays with the attempt count bumped. Returns `None` for unknown ids.
+    pub async fn retry(
+        &self,
+        id: Uuid,
+        normalizer: &EventNormalizer,
+        event_tx: &EventQueue,
+    ) -> Option<RetryOutcome> {
+        // A processing entry is marked before its event is sent, so the event cannot fail
+        // again before its entry expects it back
+        let entry = {
+            let mut entries = self.entries.write().await;
+            let entry = entries.iter_mut().find(|entry| entry.id == id)?;
+            if entry.requeued {
+                return Some(RetryOutcome {
+                    id,
+                    succeeded: false,
+                    attempts: entry.attempts,
+                    error: Some("Already requeued, awaiting processing".to_string()),
+                    events_emitted: 0,
+                });
+            }
+            if entry.stage == DeadLetterStage::Processing {
+                entry.attempts += 1;
+                entry.requeued = true;
+                self.requeued.fetch_add(1, Ordering::Relaxed);
+            }
+            entry.clone()
+        };
+
+        let events: Result<Vec<PortfolioEvent>, String> = match &entry.payload {
+            DeadLetterPayload::RawEvent(raw_event) => normalizer
+                .normalize_event(raw_event.clone())
+                .map(|event| vec![event])
+                .map_err(|e| e.to_string()),
+            DeadLetterPayload::Notification(notification) => normalizer
+                .normalize_notification(notification)
+                .map_err(|e| e.to_string()),
+            DeadLetterPayload::ProgramLog {
+                program_id,
+                log,
+                source,
+            } => normalizer
+                .normalize_program_log(program_id, log, source)
+                .map_err(|e| e.to_string()),
+            DeadLetterPayload::Instruction {
+                instruction,
+                source,
+            } => normalizer
+                .normalize_instruction(instruction, source)
+                .map_err(|e| e.to_string()),
+            DeadLetterPayload::Event(event) => Ok(vec![event.clone()]),
+        };
+
+        // Stop at the first rejected event; those already sent are counted either way
+        let mut events_emitted = 0;
+        let result = match events {
+            Ok(events) => {
+                let mut result = Ok(());
+                for event in events {
+                    if let Err(e) = event_tx.send(event).await {
+                        result = Err(format!("Failed to requeue event: {}", e));
+                        break;
+                    }
+                    events_emitted += 1;
+                }
+                result
+            }
+            Err(error) => Err(error),
+        };
+
+        // The entry may be gone by now: removed meanwhile, or its requeued event processed
+        let mut entries = self.entries.write().await;
+        let position = entries.iter().position(|entry| entry.id == id);
+        let outcome = match (result, position) {
+            (Ok(()), position) => {
+                let attempts = if entry.requeued {
+                    entry.attempts
+                } else {
+                    if let Some(position) = position {
+                        entries.remove(position);
+                    }
+                    entry.attempts + 1
+                };
+                RetryOutcome {
+                    id,
+                    succeeded: true,
+                    attempts,
+                    error: None,
+                    events_emitted,
+                }
+            }
+            (Err(error), None) => RetryOutcome {
+                id,
+                succeeded: false,
+                attempts: entry.attempts,
+                error: Some(error),
+                events_emitted,
+            },
+            (Err(error), Some(position)) => {
+                let failed = &mut entries[position];
+                if failed.requeued {
+                    failed.requeued = false;
+                    self.requeued.fetch_sub(1, Ordering::Relaxed);
+                } else {
+                    failed.attempts += 1;
+                }
+                failed.error = error.clone();
+                failed.last_failed_at = chrono::Utc::now();
+                RetryOutcome {
+                    id,
+                    succeeded: false,
+                    attempts: failed.attempts,
+                    error: Some(error),
+                    events_emitted,
+                }
+            }
+        };
+        self.writer.mark_dirty();
+        self.metrics.set_dead_letter_depth(entries.len()).await;
+        Some(outcome)
+    }
+
+    // Step 7: Purge one entry, or every entry of a stage (all stages when `None`)
+    pub async fn remove(&self, id: Uuid) -> Option<DeadLetter> {
+        let mut entries = self.entries.write().await;
+        let position = entries.iter().position(|entry| entry.id == id)?;
+        let removed = entries.remove(position);
+        self.count_requeued(&entries);
+        self.writer.mark_dirty();
+        self.metrics.set_dead_letter_depth(entries.len()).await;
+        removed
+    }
+
+    pub async fn purge(&self, stage: Option<DeadLetterStage>) -> usize {
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|entry| stage.is_some_and(|stage| entry.stage != stage));
+        self.count_requeued(&entries);
+        self.writer.mark_dirty();
+        self.metrics.set_dead_letter_depth(entries.len()).await;
+        before - entries.len()
+    }
+
+    fn count_requeued(&self, entries: &VecDeque<DeadLetter>) {
+        let requeued = entries.iter().filter(|entry| entry.requeued).count();
+        self.requeued.store(requeued, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    fn queue(max_entries: usize) -> DeadLetterQueue {
+        DeadLetterQueue {
+            max_entries,
+            entries: Arc::new(RwLock::new(VecDeque::new())),
+            requeued: Arc::new(AtomicUsize::new(0)),
+            writer: DebouncedWriter::new(None),
+            metrics: MetricsService::new(),
+        }
+    }
+
+    fn event() -> PortfolioEvent {
+        create_position_update_event("wallet".to_string(), "mint".to_string(), -5.0)
+    }
+
+    #[tokio::test]
+    async fn processing_retries_accumulate_on_one_entry() {
+        let dead_letters = queue(10);
+        let normalizer = EventNormalizer::new();
+        let event_tx = EventQueue::new(10);
+
+        let id = dead_letters
+            .push(
+                DeadLetterStage::Processing,
+                DeadLetterPayload::Event(event()),
+                "first",
+            )
+            .await;
+        let outcome = dead_letters
+            .retry(id, &normalizer, &event_tx)
+            .await
+            .unwrap();
+        assert!(outcome.succeeded);
+        assert_eq!(outcome.attempts, 2);
+        assert!(dead_letters.get(id).await.unwrap().requeued);
+
+        // A second retry while the event is in flight would process it twice
+        let outcome = dead_letters
+            .retry(id, &normalizer, &event_tx)
+            .await
+            .unwrap();
+        assert!(!outcome.succeeded);
+
+        // Failing again lands on the same entry
+        let requeued = event_tx.recv().await.unwrap();
+        let again = dead_letters
+            .push(
+                DeadLetterStage::Processing,
+                DeadLetterPayload::Event(requeued),
+                "second",
+            )
+            .await;
+        assert_eq!(again, id);
+        let entry = dead_letters.get(id).await.unwrap();
+        assert_eq!(entry.attempts, 2);
+        assert_eq!(entry.error, "second");
+        assert!(!entry.requeued);
+        assert_eq!(dead_letters.depth().await, 1);
+    }
+
+    #[tokio::test]
+    async fn processed_requeued_event_clears_its_entry() {
+        let dead_letters = queue(10);
+        let event_tx = EventQueue::new(10);
+
+        let id = dead_letters
+            .push(
+                DeadLetterStage::Processing,
+                DeadLetterPayload::Event(event()),
+                "failed",
+            )
+            .await;
+        dead_letters
+            .retry(id, &EventNormalizer::new(), &event_tx)
+            .await
+            .unwrap();
+        assert_eq!(dead_letters.depth().await, 1);
+
+        let requeued = event_tx.recv().await.unwrap();
+        dead_letters.resolve(&requeued).await;
+        assert_eq!(dead_letters.depth().await, 0);
+    }
+
+    #[tokio::test]
+    async fn full_queue_evicts_oldest() {
+        let dead_letters = queue(2);
+        let mut ids = Vec::new();
+        for error in ["a", "b", "c"] {
+            ids.push(
+                dead_letters
+                    .push(
+                        DeadLetterStage::Processing,
+                        DeadLetterPayload::Event(event()),
+                        error,
+                    )
+                    .await,
+            );
+        }
+        assert!(dead_letters.get(ids[0]).await.is_none());
+        assert_eq!(dead_letters.depth().await, 2);
+    }
+}
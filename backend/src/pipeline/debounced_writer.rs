@@ -0,0 +1,186 @@
+// backend/src/pipeline/debounced_writer.rs
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, Notify, RwLock};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Answered once the writes requested before the flush are on disk
+type FlushRequest = oneshot::Sender<()>;
+
+// Changes arriving within this window are written together
+const DEBOUNCE_MS: u64 = 500;
+
+// Step 1: Rewrites a JSON file in the background once changes settle, so callers never
+// serialize or touch the disk while holding their lock. A crash loses at most the last
+// debounce window of changes; a clean shutdown calls `flush` and loses none.
+#[derive(Clone)]
+pub struct DebouncedWriter {
+    path: Option<String>,
+    dirty: Arc<Notify>,
+    pending: Arc<AtomicBool>, // Changes not written yet
+    flush_tx: mpsc::UnboundedSender<FlushRequest>,
+    flush_rx: Arc<std::sync::Mutex<Option<mpsc::UnboundedReceiver<FlushRequest>>>>, // Until `spawn`
+}
+
+// What ended a wait of the background task
+enum Wake {
+    Settled,
+    Flush(FlushRequest),
+    Closed, // Every handle is gone
+}
+
+impl Wake {
+    fn from_request(request: Option<FlushRequest>) -> Self {
+        request.map_or(Self::Closed, Self::Flush)
+    }
+}
+
+impl DebouncedWriter {
+    pub fn new(path: Option<String>) -> Self {
+        let (flush_tx, flush_rx) = mpsc::unbounded_channel();
+        Self {
+            path,
+            dirty: Arc::new(Notify::new()),
+            pending: Arc::new(AtomicBool::new(false)),
+            flush_tx,
+            flush_rx: Arc::new(std::sync::Mutex::new(Some(flush_rx))),
+        }
+    }
+
+    // Step 2: Start writing `state` after each burst of changes. The state is cloned under
+    // its read lock, then serialized and written off the async runtime.
+    pub fn spawn<T>(&self, state: Arc<RwLock<T>>, label: &'static str)
+    where
+        T: Serialize + Clone + Send + Sync + 'static,
+    {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let Some(mut flushes) = self.flush_rx.lock().unwrap().take() else {
+            return; // Already writing
+        };
+        let dirty = self.dirty.clone();
+        let pending = self.pending.clone();
+        tokio::spawn(async move {
+            loop {
+                // A flush cuts the debounce window short
+                let wake = tokio::select! {
+                    _ = dirty.notified() => tokio::select! {
+                        _ = tokio::time::sleep(std::time::Duration::from_millis(DEBOUNCE_MS)) => {
+                            Wake::Settled
+                        }
+                        request = flushes.recv() => Wake::from_request(request),
+                    },
+                    request = flushes.recv() => Wake::from_request(request),
+                };
+
+                if pending.swap(false, Ordering::AcqRel) {
+                    let snapshot = state.read().await.clone();
+                    let target = path.clone();
+                    let result =
+                        tokio::task::spawn_blocking(move || write_atomic(&target, &snapshot))
+                            .await
+                            .map_err(ThreadSafeError::from)
+                            .and_then(|result| result);
+                    if let Err(e) = result {
+                        tracing::error!("❌ Failed to persist {} to {}: {}", label, path, e);
+                    }
+                }
+
+                match wake {
+                    Wake::Settled => {}
+                    Wake::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                    Wake::Closed => return,
+                }
+            }
+        });
+    }
+
+    // Step 3: Schedule a write; changes made before the write starts are included
+    pub fn mark_dirty(&self) {
+        if self.path.is_some() {
+            self.pending.store(true, Ordering::Release);
+            self.dirty.notify_one();
+        }
+    }
+
+    // Step 4: Write pending changes now and wait until they are on disk, e.g. on shutdown.
+    // Also waits for a write already in progress.
+    pub async fn flush(&self) {
+        if self.path.is_none() || self.flush_rx.lock().unwrap().is_some() {
+            return; // Nothing is ever written
+        }
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.flush_tx.send(done_tx).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+// Write-then-rename so a crash never leaves a truncated file behind
+pub fn write_atomic<T: Serialize>(path: &str, value: &T) -> Result<(), ThreadSafeError> {
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, serde_json::to_vec(value)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("debounced_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn on_disk(path: &str) -> Vec<u32> {
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_flush_writes_the_last_change_without_waiting_for_the_debounce() {
+        let path = temp_path("flush");
+        let state = Arc::new(RwLock::new(vec![1]));
+        let writer = DebouncedWriter::new(Some(path.clone()));
+        writer.spawn(state.clone(), "test state");
+
+        writer.mark_dirty();
+        state.write().await.push(2);
+        writer.mark_dirty();
+
+        let start = std::time::Instant::now();
+        writer.flush().await;
+        assert!(start.elapsed() < std::time::Duration::from_millis(DEBOUNCE_MS));
+        assert_eq!(on_disk(&path), vec![1, 2]);
+
+        // Later changes still go through the debounce window
+        state.write().await.push(3);
+        writer.mark_dirty();
+        tokio::time::sleep(std::time::Duration::from_millis(DEBOUNCE_MS * 3)).await;
+        assert_eq!(on_disk(&path), vec![1, 2, 3]);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn a_flush_without_anything_to_write_returns() {
+        let path = temp_path("idle");
+        std::fs::remove_file(&path).ok();
+        let writer = DebouncedWriter::new(Some(path.clone()));
+
+        // Not spawned yet
+        writer.flush().await;
+        writer.spawn(Arc::new(RwLock::new(vec![1u32])), "test state");
+        writer.flush().await;
+        assert!(!std::path::Path::new(&path).exists());
+
+        DebouncedWriter::new(None).flush().await;
+    }
+}
@@ -0,0 +1,505 @@
+// backend/src/pipeline/finality.rs
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use serde_json::json;
+use solana_transaction_status::TransactionConfirmationStatus;
+use tokio::sync::{Mutex, Notify};
+
+use crate::config::PipelineConfig;
+use crate::models::event::{Commitment, FinalityStatus, PortfolioEvent};
+use crate::pipeline::mpsc_queue::{EventQueue, QueueError};
+use crate::services::metrics::MetricsService;
+use crate::services::solana_client::SolanaClient;
+use crate::ws::hub::{WsHub, WsMessage};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// getSignatureStatuses accepts at most this many signatures per request
+const STATUS_BATCH_SIZE: usize = 256;
+// Pause between attempts to queue a compensation the priority lane had no room for
+const COMPENSATION_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+// Step 1: Events delivered from a transaction that has not finalized yet
+struct PendingTransaction {
+    slot: u64,
+    events: Vec<PortfolioEvent>,
+}
+
+// Step 2: Follows events ingested below `finalized` until their transaction settles.
+// Finalized transactions are promoted; dropped ones get compensating events.
+#[derive(Clone)]
+pub struct FinalityTracker {
+    solana_client: SolanaClient,
+    ws_hub: WsHub,
+    metrics: MetricsService,
+    event_tx: EventQueue,
+    pending: Arc<Mutex<HashMap<String, PendingTransaction>>>, // Signature -> delivered events
+    poll_interval: std::time::Duration,
+    shutdown: Arc<Notify>,
+}
+
+impl FinalityTracker {
+    pub fn new(
+        config: &PipelineConfig,
+        solana_client: SolanaClient,
+        ws_hub: WsHub,
+        metrics: MetricsService,
+        event_tx: EventQueue,
+    ) -> Self {
+        Self {
+            solana_client,
+            ws_hub,
+            metrics,
+            event_tx,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            poll_interval: config.finality_poll_interval(),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    // Step 3: Remember an event if it came from a transaction that may still be rolled back
+    pub async fn track(&self, event: &PortfolioEvent) {
+        let Some(source) = event.source() else {
+            return;
+        };
+        let Some(signature) = &source.signature else {
+            return;
+        };
+        if source.commitment == Commitment::Finalized {
+            return;
+        }
+
+        self.pending
+            .lock()
+            .await
+            .entry(signature.clone())
+            .or_insert_with(|| PendingTransaction {
+                slot: source.slot,
+                events: Vec::new(),
+            })
+            .events
+            .push(event.clone());
+    }
+
+    pub async fn pending_count(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    // Step 4: Poll signature statuses until `stop` is called
+    pub async fn start(&self) {
+        tracing::info!(
+            "🔗 Starting finality tracker (every {:?})",
+            self.poll_interval
+        );
+        let mut interval = tokio::time::interval(self.poll_interval);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.reconcile().await {
+                        tracing::warn!("⚠️ Finality reconciliation failed: {}", e);
+                    }
+                }
+                _ = self.shutdown.notified() => {
+                    tracing::info!("🛑 Finality tracker stopped");
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    // Step 5: Settle every pending transaction the cluster has made a final decision on
+    async fn reconcile(&self) -> Result<(), ThreadSafeError> {
+        let signatures: Vec<String> = self.pending.lock().await.keys().cloned().collect();
+        if signatures.is_empty() {
+            return Ok(());
+        }
+
+        // Read the finalized slot first so a transaction landing in between is not misjudged
+        let finalized_slot = self.solana_client.get_finalized_slot().await?;
+        let mut settled = Vec::new();
+        for chunk in signatures.chunks(STATUS_BATCH_SIZE) {
+            let statuses = self.solana_client.get_signature_statuses(chunk).await?;
+            settled.extend(chunk.iter().zip(statuses));
+        }
+
+        for (signature, status) in settled {
+            let slot = self.pending.lock().await.get(signature).map(|tx| tx.slot);
+            let Some(outcome) = settlement(status, slot, finalized_slot) else {
+                continue;
+            };
+
+            let Some(tx) = self.pending.lock().await.remove(signature) else {
+                continue;
+            };
+            self.settle(signature, tx, outcome).await;
+        }
+
+        self.metrics
+            .set_finality_pending(self.pending_count().await)
+            .await;
+        Ok(())
+    }
+
+    // Step 6: Emit compensating events for reverted transactions, then announce the outcome
+    // to the pipeline (once per wallet) and to WebSocket clients
+    async fn settle(&self, signature: &str, tx: PendingTransaction, status: FinalityStatus) {
+        let (status_label, log_line) = match status {
+            FinalityStatus::Finalized => ("finalized", "✅ Transaction finalized"),
+            FinalityStatus::Reverted => ("reverted", "↩️ Transaction reverted"),
+        };
+        tracing::info!("{}: {} (slot {})", log_line, signature, tx.slot);
+
+        if status == FinalityStatus::Reverted {
+            for event in &tx.events {
+                if let Some(compensation) = event.compensating_event() {
+                    self.send_compensation(signature, compensation).await;
+                }
+            }
+        }
+
+        let wallets: BTreeSet<String> = tx
+            .events
+            .iter()
+            .map(|event| event.wallet().to_string())
+            .collect();
+        for wallet in &wallets {
+            let event = PortfolioEvent::TransactionStatusChanged {
+                wallet: wallet.clone(),
+                signature: signature.to_string(),
+                slot: tx.slot,
+                status,
+                timestamp: chrono::Utc::now(),
+            };
+            if let Err(e) = self.event_tx.send(event).await {
+                tracing::warn!(
+                    "⚠️ Status of {} for {} not queued: {}",
+                    signature,
+                    wallet,
+                    e
+                );
+            }
+        }
+
+        // Nobody listening is not an error
+        let _ = self.ws_hub.broadcast(WsMessage {
+            message_type: "transaction_status".to_string(),
+            payload: json!({
+                "signature": signature,
+                "slot": tx.slot,
+                "status": status,
+                "wallets": wallets,
+                "events": tx.events.len(),
+            }),
+            timestamp: chrono::Utc::now(),
+        });
+        self.metrics.record_finality(status_label).await;
+    }
+
+    // A lost compensation leaves rule state wrong for good, so a full priority lane is retried
+    // until the queue closes
+    async fn send_compensation(&self, signature: &str, compensation: PortfolioEvent) {
+        loop {
+            match self.event_tx.send(compensation.clone()).await {
+                Ok(()) => {
+                    self.metrics.record_compensation("sent").await;
+                    return;
+                }
+                Err(QueueError::Closed) => {
+                    tracing::error!("❌ Pipeline closed, compensation for {} lost", signature);
+                    self.metrics.record_compensation("lost").await;
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "⚠️ Compensation for {} not queued, retrying: {}",
+                        signature,
+                        e
+                    );
+                    self.metrics.record_compensation("retried").await;
+                    tokio::time::sleep(COMPENSATION_RETRY_DELAY).await;
+                }
+            }
+        }
+    }
+}
+
+// Final outcome of a pending transaction, or `None` while the cluster may still change its mind
+fn settlement(
+    status: Option<TransactionConfirmationStatus>,
+    slot: Option<u64>,
+    finalized_slot: u64,
+) -> Option<FinalityStatus> {
+    match status {
+        Some(TransactionConfirmationStatus::Finalized) => Some(FinalityStatus::Finalized),
+        Some(_) => None,
+        // Unknown to the ledger even though its slot is already rooted: it was on an
+        // abandoned fork
+        None => match slot {
+            Some(slot) if slot <= finalized_slot => Some(FinalityStatus::Reverted),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+    use crate::config::{OverflowPolicy, SolanaConfig};
+    use crate::models::event::ChainSource;
+    use crate::services::metrics::MetricValue;
+
+    const SIGNATURE: &str = "sig";
+
+    fn tracker(event_tx: EventQueue) -> FinalityTracker {
+        // Nothing here reaches the RPC node, so the address only has to parse
+        let config = SolanaConfig {
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            ws_url: "ws://127.0.0.1:1".to_string(),
+            program_id: "11111111111111111111111111111111".to_string(),
+            amm_pool_program_id: "11111111111111111111111111111111".to_string(),
+            portfolio_program_id: "11111111111111111111111111111111".to_string(),
+            extra_program_ids: Vec::new(),
+            idl_dir: None,
+            commitment: "confirmed".to_string(),
+            logs_ws_enabled: false,
+            accounts_ws_enabled: false,
+        };
+        FinalityTracker {
+            solana_client: SolanaClient::new(&config),
+            ws_hub: WsHub::new(),
+            metrics: MetricsService::new(),
+            event_tx,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            poll_interval: std::time::Duration::from_secs(1),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    fn confirmed(mut event: PortfolioEvent, commitment: Commitment) -> PortfolioEvent {
+        event.set_source(ChainSource {
+            slot: 7,
+            signature: Some(SIGNATURE.to_string()),
+            commitment,
+        });
+        event
+    }
+
+    fn stake_change(wallet: &str, delta_lamports: i64) -> PortfolioEvent {
+        confirmed(
+            PortfolioEvent::StakeChanged {
+                wallet: wallet.to_string(),
+                stake_account: "stake".to_string(),
+                validator: None,
+                delta_lamports,
+                timestamp: chrono::Utc::now(),
+                source: None,
+                reversal: false,
+            },
+            Commitment::Confirmed,
+        )
+    }
+
+    async fn drain(queue: &EventQueue) -> Vec<PortfolioEvent> {
+        queue.close();
+        let mut events = Vec::new();
+        while let Some(event) = queue.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    async fn counter(metrics: &MetricsService, name: &str) -> u64 {
+        match metrics.get_metrics().await.get(name) {
+            Some(MetricValue::Counter(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn settlement_waits_for_a_final_answer() {
+        use TransactionConfirmationStatus::{Confirmed, Finalized};
+
+        assert_eq!(
+            settlement(Some(Finalized), Some(7), 5),
+            Some(FinalityStatus::Finalized)
+        );
+        assert_eq!(settlement(Some(Confirmed), Some(3), 5), None);
+        // Missing from the ledger only counts once its slot is rooted
+        assert_eq!(settlement(None, Some(5), 5), Some(FinalityStatus::Reverted));
+        assert_eq!(settlement(None, Some(6), 5), None);
+        assert_eq!(settlement(None, None, 5), None);
+    }
+
+    #[test]
+    fn compensating_a_stake_change_saturates() {
+        let Some(PortfolioEvent::StakeChanged {
+            delta_lamports,
+            reversal,
+            ..
+        }) = stake_change("a", i64::MIN).compensating_event()
+        else {
+            panic!("stake changes are compensated");
+        };
+        assert_eq!(delta_lamports, i64::MAX);
+        assert!(reversal);
+    }
+
+    #[tokio::test]
+    async fn only_unfinalized_chain_events_are_tracked() {
+        let tracker = tracker(EventQueue::new(8));
+        let off_chain = create_position_update_event("a".to_string(), "mint".to_string(), 1.0);
+        let finalized = confirmed(off_chain.clone(), Commitment::Finalized);
+        let pending = confirmed(off_chain.clone(), Commitment::Confirmed);
+
+        for event in [&off_chain, &finalized, &pending, &pending] {
+            tracker.track(event).await;
+        }
+
+        assert_eq!(tracker.pending_count().await, 1);
+        assert_eq!(tracker.pending.lock().await[SIGNATURE].events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reconcile_with_nothing_pending_skips_the_rpc_node() {
+        let tracker = tracker(EventQueue::new(8));
+        assert!(tracker.reconcile().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_reverted_transaction_is_compensated_then_announced_per_wallet() {
+        let queue = EventQueue::new(8);
+        let tracker = tracker(queue.clone());
+        let mut updates = tracker.ws_hub.subscribe();
+        let update = confirmed(
+            create_position_update_event("a".to_string(), "mint".to_string(), 2.5),
+            Commitment::Confirmed,
+        );
+        for event in [update, stake_change("a", 40), stake_change("b", -10)] {
+            tracker.track(&event).await;
+        }
+
+        let tx = tracker.pending.lock().await.remove(SIGNATURE).unwrap();
+        tracker
+            .settle(SIGNATURE, tx, FinalityStatus::Reverted)
+            .await;
+
+        let events = drain(&queue).await;
+        assert_eq!(events.len(), 5);
+        assert!(events[..3].iter().all(PortfolioEvent::is_reversal));
+        assert!(matches!(
+            &events[0],
+            PortfolioEvent::PositionUpdate { wallet, pnl_delta, .. } if wallet == "a" && *pnl_delta == -2.5
+        ));
+        assert!(matches!(
+            &events[1],
+            PortfolioEvent::StakeChanged { wallet, delta_lamports: -40, .. } if wallet == "a"
+        ));
+        assert!(matches!(
+            &events[2],
+            PortfolioEvent::StakeChanged { wallet, delta_lamports: 10, .. } if wallet == "b"
+        ));
+        for (event, expected) in events[3..].iter().zip(["a", "b"]) {
+            assert!(matches!(
+                event,
+                PortfolioEvent::TransactionStatusChanged {
+                    wallet,
+                    signature,
+                    slot: 7,
+                    status: FinalityStatus::Reverted,
+                    ..
+                } if wallet == expected && signature == SIGNATURE
+            ));
+        }
+
+        let message = updates.try_recv().unwrap();
+        assert_eq!(message.message_type, "transaction_status");
+        assert_eq!(message.payload["status"], "reverted");
+        assert_eq!(message.payload["events"], 3);
+        assert_eq!(
+            counter(
+                &tracker.metrics,
+                "finality_compensations_total,outcome=sent"
+            )
+            .await,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn a_finalized_transaction_is_only_announced() {
+        let queue = EventQueue::new(8);
+        let tracker = tracker(queue.clone());
+        tracker.track(&stake_change("a", 40)).await;
+
+        let tx = tracker.pending.lock().await.remove(SIGNATURE).unwrap();
+        tracker
+            .settle(SIGNATURE, tx, FinalityStatus::Finalized)
+            .await;
+
+        let events = drain(&queue).await;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            PortfolioEvent::TransactionStatusChanged {
+                status: FinalityStatus::Finalized,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_full_priority_lane_delays_a_compensation_without_losing_it() {
+        let queue = EventQueue::with_policy(
+            1,
+            OverflowPolicy::Block,
+            std::time::Duration::from_millis(10),
+            None,
+        );
+        let tracker = tracker(queue.clone());
+        let blocker = stake_change("blocker", 1).compensating_event().unwrap();
+        queue.send(blocker).await.unwrap();
+
+        let compensation = stake_change("a", 40).compensating_event().unwrap();
+        let sender = {
+            let tracker = tracker.clone();
+            tokio::spawn(async move { tracker.send_compensation(SIGNATURE, compensation).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(queue.recv().await.unwrap().wallet(), "blocker");
+        sender.await.unwrap();
+
+        assert_eq!(queue.recv().await.unwrap().wallet(), "a");
+        let metrics = &tracker.metrics;
+        assert!(counter(metrics, "finality_compensations_total,outcome=retried").await >= 1);
+        assert_eq!(
+            counter(metrics, "finality_compensations_total,outcome=sent").await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn a_closed_pipeline_loses_the_compensation() {
+        let queue = EventQueue::new(8);
+        queue.close();
+        let tracker = tracker(queue);
+
+        let compensation = stake_change("a", 40).compensating_event().unwrap();
+        tracker.send_compensation(SIGNATURE, compensation).await;
+
+        assert_eq!(
+            counter(
+                &tracker.metrics,
+                "finality_compensations_total,outcome=lost"
+            )
+            .await,
+            1
+        );
+    }
+}
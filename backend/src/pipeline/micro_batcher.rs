@@ -1,37 +1,106 @@
 // backend/src/pipeline/micro_batcher.rs
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+
+use futures_util::FutureExt;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
 
 use crate::models::event::PortfolioEvent;
+use crate::pipeline::dead_letter::{DeadLetterPayload, DeadLetterQueue, DeadLetterStage};
+use crate::pipeline::rules_engine::RulesEngine;
+use crate::services::metrics::MetricsService;
+
+// Wallet groups each shard may have queued before the batcher waits
+const SHARD_QUEUE_CAPACITY: usize = 256;
+
+// Shard workers of the running batcher, handed to its successor after a crash
+pub type ShardWorkers = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+// One wallet's slice of a batch, in arrival order
+struct WalletEvents {
+    wallet: String,
+    events: Vec<PortfolioEvent>,
+}
 
 // Step 1: Micro-batching processor for efficient event handling
 pub struct MicroBatcher {
-    receiver: mpsc::Receiver<PortfolioEvent>,
+    receiver: Arc<Mutex<mpsc::Receiver<PortfolioEvent>>>, // Shared with the batcher's successor
     batch_size: usize,
     batch_timeout: Duration,
+    shard_count: usize,
+    workers: ShardWorkers,
+    dead_letters: Option<DeadLetterQueue>,
+    rules: Option<Arc<RulesEngine>>,
+    metrics: Option<MetricsService>,
 }
 
 impl MicroBatcher {
+    // The receiver is shared so that a batcher restarted after a crash picks up the events
+    // its predecessor left in the channel
     pub fn new(
-        receiver: mpsc::Receiver<PortfolioEvent>,
+        receiver: Arc<Mutex<mpsc::Receiver<PortfolioEvent>>>,
         batch_size: usize,
         batch_timeout: Duration,
+        shard_count: usize,
     ) -> Self {
         Self {
             receiver,
             batch_size,
             batch_timeout,
+            shard_count: shard_count.max(1),
+            workers: ShardWorkers::default(),
+            dead_letters: None,
+            rules: None,
+            metrics: None,
         }
     }
 
+    // Share shard workers with the batcher this one replaces, so it can wait for them
+    pub fn with_shard_workers(mut self, workers: ShardWorkers) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    // Park events that fail processing instead of dropping them
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    // Run every processed event through the risk rules
+    pub fn with_rules_engine(mut self, rules: Arc<RulesEngine>) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    // Report per-shard queue depth
+    pub fn with_metrics(mut self, metrics: MetricsService) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     // Step 2: Start processing events - FIXED VERSION
-    pub async fn run(mut self) {
+    pub async fn run(self) {
         tracing::info!(
-            "🔄 Starting micro-batcher (batch_size: {}, timeout: {:?})",
+            "🔄 Starting micro-batcher (batch_size: {}, timeout: {:?}, shards: {})",
             self.batch_size,
-            self.batch_timeout
+            self.batch_timeout,
+            self.shard_count
         );
+        // A crashed predecessor's shards still hold queued groups. Let them finish first, or a
+        // wallet's older events could be processed after the newer ones handed out below.
+        let predecessors = std::mem::take(&mut *self.workers.lock().await);
+        Self::join_shards(predecessors).await;
+
+        let shards = self.spawn_shards().await;
+        // Held for the batcher's lifetime; released when it exits or crashes
+        let receiver = self.receiver.clone();
+        let mut receiver = receiver.lock().await;
 
         loop {
             let mut batch = Vec::with_capacity(self.batch_size);
@@ -42,7 +111,7 @@ impl MicroBatcher {
             // Step 3: Collect events until batch size, timeout, or channel closed
             while batch.len() < self.batch_size && !channel_closed {
                 tokio::select! {
-                    maybe = self.receiver.recv() => {
+                    maybe = receiver.recv() => {
                         match maybe {
                             Some(event) => batch.push(event),
                             None => {
@@ -63,6 +132,7 @@ impl MicroBatcher {
             if batch.is_empty() {
                 if channel_closed {
                     tracing::info!("📭 Event channel closed, shutting down batcher");
+                    self.stop_shards(shards).await;
                     return;
                 } else {
                     continue; // No events received before timeout, continue waiting
@@ -70,102 +140,429 @@ impl MicroBatcher {
             }
 
             // Step 5: Process batch
-            self.process_batch(batch).await;
+            self.process_batch(batch, &shards).await;
+        }
+    }
+
+    // Step 6: One long-lived worker per shard. A wallet always hashes to the same shard and
+    // each shard handles its queue in order, so a wallet's events never race each other.
+    async fn spawn_shards(&self) -> Vec<mpsc::Sender<WalletEvents>> {
+        let (shards, workers): (Vec<_>, Vec<_>) = (0..self.shard_count)
+            .map(|shard| {
+                let (tx, mut rx) = mpsc::channel::<WalletEvents>(SHARD_QUEUE_CAPACITY);
+                let dead_letters = self.dead_letters.clone();
+                let rules = self.rules.clone();
+
+                let worker = tokio::spawn(async move {
+                    while let Some(group) = rx.recv().await {
+                        Self::process_wallet_events(
+                            &group.wallet,
+                            group.events,
+                            dead_letters.as_ref(),
+                            rules.as_deref(),
+                        )
+                        .await;
+                    }
+                    tracing::debug!("📭 Shard {} stopped", shard);
+                });
+                (tx, worker)
+            })
+            .unzip();
+        *self.workers.lock().await = workers;
+        shards
+    }
+
+    // Close every shard queue and wait for the queued work to finish
+    async fn stop_shards(&self, shards: Vec<mpsc::Sender<WalletEvents>>) {
+        drop(shards);
+        let workers = std::mem::take(&mut *self.workers.lock().await);
+        Self::join_shards(workers).await;
+    }
+
+    async fn join_shards(workers: Vec<JoinHandle<()>>) {
+        for (shard, worker) in workers.into_iter().enumerate() {
+            if let Err(e) = worker.await {
+                tracing::error!("❌ Shard {} crashed: {}", shard, e);
+            }
         }
     }
 
-    // Step 6: Process a batch of events
-    async fn process_batch(&self, batch: Vec<PortfolioEvent>) {
+    // Step 7: Route a batch to the shards
+    async fn process_batch(
+        &self,
+        batch: Vec<PortfolioEvent>,
+        shards: &[mpsc::Sender<WalletEvents>],
+    ) {
         tracing::debug!("🔄 Processing batch of {} events", batch.len());
 
-        // Step 7: Group events by wallet for efficient processing
-        let mut events_by_wallet = HashMap::new();
+        // Group events by wallet, keeping each wallet's arrival order
+        let mut events_by_wallet: HashMap<String, Vec<PortfolioEvent>> = HashMap::new();
         for event in batch {
             let wallet = event.wallet().to_string();
-            events_by_wallet
-                .entry(wallet)
-                .or_insert_with(Vec::new)
-                .push(event);
+            events_by_wallet.entry(wallet).or_default().push(event);
         }
 
-        // Step 8: Process each wallet's events in parallel
-        let mut tasks = Vec::new();
-
+        // Step 8: Hand each wallet's events to its shard; a full shard queue applies backpressure
         for (wallet, events) in events_by_wallet {
-            let wallet_clone = wallet.clone();
-            let events_clone = events.clone();
-
-            let task = tokio::spawn(async move {
-                if let Err(e) = Self::process_wallet_events(&wallet_clone, events_clone).await {
-                    tracing::error!(
-                        "❌ Error processing events for wallet {}: {}",
-                        wallet_clone,
-                        e
-                    );
+            let shard = shard_for(&wallet, shards.len());
+            if let Err(e) = shards[shard].send(WalletEvents { wallet, events }).await {
+                // Workers never exit while their sender is alive, but do not lose the events
+                tracing::error!("❌ Shard {} is gone, dead-lettering its events", shard);
+                if let Some(dead_letters) = &self.dead_letters {
+                    for event in e.0.events {
+                        dead_letters
+                            .push(
+                                DeadLetterStage::Processing,
+                                DeadLetterPayload::Event(event),
+                                format!("shard {} unavailable", shard),
+                            )
+                            .await;
+                    }
                 }
-            });
-
-            tasks.push(task);
+            }
         }
 
-        // Wait for all wallet processing to complete
-        for task in tasks {
-            let _ = task.await;
+        if let Some(metrics) = &self.metrics {
+            for (shard, tx) in shards.iter().enumerate() {
+                metrics
+                    .set_shard_queue_depth(shard, tx.max_capacity() - tx.capacity())
+                    .await;
+            }
         }
-
-        tracing::debug!("✅ Batch processing completed");
+        tracing::debug!("✅ Batch dispatched");
     }
 
-    // Step 9: Process events for a specific wallet
+    // Step 9: Process events for a specific wallet; failures (panics included) go to the
+    // dead-letter queue and successes on to the rules engine. Nothing here may panic out,
+    // or the shard's queued groups would be lost with its worker.
     async fn process_wallet_events(
         wallet: &str,
         events: Vec<PortfolioEvent>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        dead_letters: Option<&DeadLetterQueue>,
+        rules: Option<&RulesEngine>,
+    ) {
         for event in events {
-            match event {
-                PortfolioEvent::PositionUpdate {
+            let result = AssertUnwindSafe(Self::process_event(&event))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err("panicked while processing event".into()));
+            let Err(e) = result else {
+                if let Some(dead_letters) = dead_letters {
+                    dead_letters.resolve(&event).await;
+                }
+                if let Some(rules) = rules {
+                    match AssertUnwindSafe(rules.process_event(&event))
+                        .catch_unwind()
+                        .await
+                    {
+                        Ok(alerts) => {
+                            for alert in alerts {
+                                tracing::warn!(
+                                    "🚨 Risk alert for {}: {}",
+                                    alert.wallet,
+                                    alert.message
+                                );
+                            }
+                        }
+                        Err(_) => tracing::error!(
+                            "💥 Rules engine panicked on an event for wallet {}",
+                            wallet
+                        ),
+                    }
+                }
+                continue;
+            };
+            tracing::error!("❌ Error processing event for wallet {}: {}", wallet, e);
+            if let Some(dead_letters) = dead_letters {
+                dead_letters
+                    .push(
+                        DeadLetterStage::Processing,
+                        DeadLetterPayload::Event(event),
+                        e.to_string(),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    // Step 10: Process a single event
+    pub async fn process_event(
+        event: &PortfolioEvent,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match event {
+            PortfolioEvent::PositionUpdate {
+                wallet,
+                mint,
+                pnl_delta,
+                ..
+            } => {
+                tracing::info!(
+                    "📈 Updating position for {}: {} PnL delta: {}",
                     wallet,
                     mint,
-                    pnl_delta,
-                    timestamp,
-                } => {
-                    tracing::info!(
-                        "📈 Updating position for {}: {} PnL delta: {}",
-                        wallet,
-                        mint,
-                        pnl_delta
-                    );
-                    // In production: Update database, trigger risk recalculation
-                }
-                PortfolioEvent::SwapExecuted {
+                    pnl_delta
+                );
+                // In production: Update database, trigger risk recalculation
+            }
+            PortfolioEvent::SwapExecuted {
+                wallet,
+                input_mint,
+                output_mint,
+                amount,
+                ..
+            } => {
+                tracing::info!(
+                    "💱 Swap executed for {}: {} {} -> {}",
                     wallet,
+                    amount,
                     input_mint,
-                    output_mint,
+                    output_mint
+                );
+                // In production: Update portfolio, trigger liquidity analysis
+            }
+            PortfolioEvent::TradeRecorded {
+                wallet,
+                mint,
+                amount,
+                notional,
+                ..
+            } => {
+                tracing::info!(
+                    "🧾 Trade recorded for {}: {} {} (notional {})",
+                    wallet,
                     amount,
-                    timestamp,
-                } => {
-                    tracing::info!(
-                        "💱 Swap executed for {}: {} {} -> {}",
-                        wallet,
-                        amount,
-                        input_mint,
-                        output_mint
-                    );
-                    // In production: Update portfolio, trigger liquidity analysis
-                }
-                PortfolioEvent::RiskAlertTriggered {
+                    mint,
+                    notional
+                );
+                // In production: Update trade history, trigger exposure checks
+            }
+            PortfolioEvent::LiquidityAdded {
+                wallet,
+                pool,
+                lp_tokens,
+                ..
+            } => {
+                tracing::info!(
+                    "💧 {} added liquidity to {}: {} LP",
                     wallet,
-                    alert_type,
-                    severity,
-                    message,
-                    timestamp,
-                } => {
-                    tracing::info!("🚨 Risk alert for {}: {} - {}", wallet, severity, message);
-                    // In production: Store alert, send notifications
-                }
+                    pool,
+                    lp_tokens
+                );
+                // In production: Update LP positions, trigger impermanent-loss tracking
+            }
+            PortfolioEvent::LiquidityRemoved {
+                wallet,
+                pool,
+                lp_tokens,
+                ..
+            } => {
+                tracing::info!(
+                    "💧 {} removed liquidity from {}: {} LP",
+                    wallet,
+                    pool,
+                    lp_tokens
+                );
+                // In production: Update LP positions, realize impermanent loss
+            }
+            PortfolioEvent::Transfer {
+                wallet,
+                mint,
+                amount,
+                direction,
+                ..
+            } => {
+                tracing::info!(
+                    "📦 Transfer {:?} for {}: {} {}",
+                    direction,
+                    wallet,
+                    amount,
+                    mint
+                );
+                // In production: Update balances
+            }
+            PortfolioEvent::StakeChanged {
+                wallet,
+                stake_account,
+                delta_lamports,
+                ..
+            } => {
+                tracing::info!(
+                    "🥩 Stake change for {} on {}: {} lamports",
+                    wallet,
+                    stake_account,
+                    delta_lamports
+                );
+                // In production: Update staked balance
+            }
+            PortfolioEvent::FeesCollected {
+                wallet,
+                pool,
+                amount,
+                mint,
+                ..
+            } => {
+                tracing::info!(
+                    "💰 {} collected {} {} in fees from {}",
+                    wallet,
+                    amount,
+                    mint,
+                    pool
+                );
+                // In production: Update realized yield
+            }
+            PortfolioEvent::PoolReservesChanged {
+                pool,
+                reserve_a,
+                reserve_b,
+                ..
+            } => {
+                tracing::debug!("🏊 Pool {} reserves: {} / {}", pool, reserve_a, reserve_b);
+                // In production: Refresh pool prices, trigger LP health checks
+            }
+            PortfolioEvent::PriceUpdated {
+                mint, price_usd, ..
+            } => {
+                tracing::debug!("💲 Price of {}: ${}", mint, price_usd);
+                // Market rules are evaluated by the rules engine
+            }
+            PortfolioEvent::PositionSnapshot {
+                wallet,
+                position,
+                pnl,
+                ..
+            } => {
+                tracing::debug!("📸 Position {} of {}: PnL {}", position, wallet, pnl);
+                // In production: Reconcile stored position with on-chain state
+            }
+            PortfolioEvent::TransactionStatusChanged {
+                wallet,
+                signature,
+                status,
+                ..
+            } => {
+                tracing::info!("🔗 Transaction {} for {}: {:?}", signature, wallet, status);
+                // In production: Mark stored events final, or roll back reverted ones
+            }
+            PortfolioEvent::RiskAlertTriggered {
+                wallet,
+                severity,
+                message,
+                ..
+            } => {
+                tracing::info!("🚨 Risk alert for {}: {} - {}", wallet, severity, message);
+                // In production: Store alert, send notifications
             }
         }
 
         Ok(())
     }
 }
+
+// Stable within a process, which is all per-wallet ordering needs
+fn shard_for(wallet: &str, shard_count: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    wallet.hash(&mut hasher);
+    (hasher.finish() % shard_count as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    fn batcher(shard_count: usize) -> MicroBatcher {
+        let (_, rx) = mpsc::channel(1);
+        MicroBatcher::new(
+            Arc::new(Mutex::new(rx)),
+            16,
+            Duration::from_millis(10),
+            shard_count,
+        )
+    }
+
+    // The PnL delta doubles as the event's position in the stream
+    fn event(wallet: &str, seq: u32) -> PortfolioEvent {
+        create_position_update_event(wallet.to_string(), "mint".to_string(), f64::from(seq))
+    }
+
+    fn seq(event: &PortfolioEvent) -> u32 {
+        match event {
+            PortfolioEvent::PositionUpdate { pnl_delta, .. } => *pnl_delta as u32,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn a_wallet_always_maps_to_the_same_shard() {
+        let wallets: Vec<String> = (0..64).map(|i| format!("wallet-{}", i)).collect();
+        for shard_count in [1, 3, 8] {
+            let first: Vec<usize> = wallets.iter().map(|w| shard_for(w, shard_count)).collect();
+            let again: Vec<usize> = wallets.iter().map(|w| shard_for(w, shard_count)).collect();
+            assert_eq!(first, again);
+            assert!(first.iter().all(|&shard| shard < shard_count));
+        }
+
+        // Wallets are spread out rather than piled on one shard
+        let used: std::collections::HashSet<usize> =
+            wallets.iter().map(|w| shard_for(w, 8)).collect();
+        assert!(used.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn each_wallet_keeps_its_order_on_its_own_shard() {
+        const SHARDS: usize = 4;
+        let batcher = batcher(SHARDS);
+        let (senders, mut receivers): (Vec<_>, Vec<_>) = (0..SHARDS)
+            .map(|_| mpsc::channel::<WalletEvents>(SHARD_QUEUE_CAPACITY))
+            .unzip();
+
+        let wallets = ["a", "b", "c", "d", "e", "f"];
+        let mut next = 0;
+        for _ in 0..3 {
+            let mut batch = Vec::new();
+            for wallet in wallets.iter().chain(wallets.iter().rev()) {
+                batch.push(event(wallet, next));
+                next += 1;
+            }
+            batcher.process_batch(batch, &senders).await;
+        }
+        drop(senders);
+
+        let mut seen: HashMap<String, (usize, Vec<u32>)> = HashMap::new();
+        for (shard, rx) in receivers.iter_mut().enumerate() {
+            while let Some(group) = rx.recv().await {
+                let entry = seen.entry(group.wallet).or_insert((shard, Vec::new()));
+                assert_eq!(entry.0, shard, "a wallet was split across shards");
+                entry.1.extend(group.events.iter().map(seq));
+            }
+        }
+
+        assert_eq!(seen.len(), wallets.len());
+        for (wallet, (shard, seqs)) in seen {
+            assert_eq!(shard, shard_for(&wallet, SHARDS));
+            assert_eq!(seqs.len(), 6);
+            assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]), "{}", wallet);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_restarted_batcher_waits_for_its_predecessors_shards() {
+        let workers = ShardWorkers::default();
+        let finished = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let predecessor = {
+            let finished = finished.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                finished.store(true, std::sync::atomic::Ordering::SeqCst);
+            })
+        };
+        workers.lock().await.push(predecessor);
+
+        // A closed channel makes the batcher stop right after starting its shards
+        let batcher = batcher(2).with_shard_workers(workers.clone());
+        batcher.run().await;
+
+        assert!(finished.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(workers.lock().await.is_empty());
+    }
+}
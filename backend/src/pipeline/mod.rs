@@ -1,4 +1,13 @@
 // backend/src/pipeline/mod.rs
+pub mod alert_dedup;
+pub mod alert_store;
+pub mod backtest;
+pub mod dead_letter;
+pub mod debounced_writer;
+pub mod finality;
 pub mod micro_batcher;
 pub mod mpsc_queue;
+pub mod rule_set;
+pub mod rule_state;
 pub mod rules_engine;
+pub mod runtime;
@@ -1,85 +1,577 @@
 // backend/src/pipeline/mpsc_queue.rs
-use std::time::Duration;
-use tokio::sync::mpsc;
+use std::collections::VecDeque;
+use std::io::{BufRead, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::config::{OverflowPolicy, PipelineConfig};
 use crate::models::event::PortfolioEvent;
+use crate::services::metrics::MetricsService;
 
-// Step 1: Multi-producer single-consumer queue with backpressure
-pub struct EventQueue {
-    sender: mpsc::Sender<PortfolioEvent>,
-    receiver: mpsc::Receiver<PortfolioEvent>,
-    capacity: usize,
-    metrics: QueueMetrics,
+// Send timeout for queues built without a config
+const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How often counters are exported to `MetricsService`
+const METRICS_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+// Step 1: Lanes. Priority is always drained first, so risk alerts never wait behind bulk
+// position updates. Reversals ride it too: they repair rule state and must not be shed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueLane {
+    Priority,
+    Bulk,
+}
+
+impl QueueLane {
+    pub fn for_event(event: &PortfolioEvent) -> Self {
+        match event {
+            PortfolioEvent::RiskAlertTriggered { .. } => Self::Priority,
+            event if event.is_reversal() => Self::Priority,
+            _ => Self::Bulk,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Priority => "priority",
+            Self::Bulk => "bulk",
+        }
+    }
+}
+
+struct Queued {
+    event: PortfolioEvent,
+    enqueued_at: Instant,
+}
+
+#[derive(Default)]
+struct Lanes {
+    priority: VecDeque<Queued>,
+    bulk: VecDeque<Queued>,
+}
+
+impl Lanes {
+    fn lane(&mut self, lane: QueueLane) -> &mut VecDeque<Queued> {
+        match lane {
+            QueueLane::Priority => &mut self.priority,
+            QueueLane::Bulk => &mut self.bulk,
+        }
+    }
+
+    fn pop(&mut self) -> Option<Queued> {
+        self.priority.pop_front().or_else(|| self.bulk.pop_front())
+    }
+}
+
+// Step 2: Lock-free counters, readable from any clone
+#[derive(Default)]
+struct QueueCounters {
+    received: AtomicU64,
+    processed: AtomicU64,
+    dropped: AtomicU64,
+    spilled: AtomicU64,
+    timeouts: AtomicU64,
+    latency_us: AtomicU64, // Summed over processed events
+}
+
+// Point-in-time view of the queue
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct QueueStats {
+    pub received: u64,
+    pub processed: u64,
+    pub dropped: u64,
+    pub spilled: u64,
+    pub timeouts: u64,
+    pub latency_us: u64,
+    pub priority_depth: usize,
+    pub bulk_depth: usize,
+    pub spill_depth: usize,
 }
 
-// Step 2: Queue performance metrics
-#[derive(Debug, Clone)]
-struct QueueMetrics {
-    pub total_received: u64,
-    pub total_processed: u64,
-    pub current_size: usize,
-    pub max_capacity: usize,
+// Unread part of the spill file. Events are read from `offset` onwards instead of rewriting
+// the file; the offset is saved next to it so a restart resumes where the last run stopped.
+#[derive(Debug, Default)]
+struct SpillCursor {
+    offset: u64,    // Bytes already moved back into the bulk lane
+    pending: usize, // Events after `offset`
+}
+
+impl SpillCursor {
+    // Pick up events spilled before a restart
+    fn recover(path: &str) -> std::io::Result<Self> {
+        let offset = match std::fs::read_to_string(offset_path(path)) {
+            Ok(contents) => contents.trim().parse().unwrap_or(0),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        let mut file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        std::io::Seek::seek(&mut file, SeekFrom::Start(offset))?;
+        let mut pending = 0;
+        for line in std::io::BufReader::new(file).lines() {
+            if !line?.trim().is_empty() {
+                pending += 1;
+            }
+        }
+        Ok(Self { offset, pending })
+    }
+}
+
+struct QueueInner {
+    lanes: Mutex<Lanes>, // Never held across an await
+    capacity: usize,     // Per lane
+    policy: OverflowPolicy,
+    send_timeout: Duration,
+    spill_path: Option<String>,
+    spill: tokio::sync::Mutex<SpillCursor>,
+    spill_depth: AtomicUsize, // Mirrors `SpillCursor::pending` for lock-free reads
+    closed: AtomicBool,
+    not_empty: Notify,
+    not_full: Notify,
+    counters: QueueCounters,
+}
+
+// Step 3: Bounded multi-producer queue with an overflow policy. Clones share the same lanes,
+// so it can sit in app state and be handed to every producer.
+#[derive(Clone)]
+pub struct EventQueue {
+    inner: Arc<QueueInner>,
 }
 
 impl EventQueue {
-    // Step 3: Create new queue with specified capacity
+    // Blocking queue with the default send timeout
     pub fn new(capacity: usize) -> Self {
-        let (sender, receiver) = mpsc::channel(capacity);
+        Self::with_policy(capacity, OverflowPolicy::Block, DEFAULT_SEND_TIMEOUT, None)
+    }
+
+    pub fn from_config(config: &PipelineConfig) -> Self {
+        Self::with_policy(
+            config.max_queue_size,
+            config.overflow_policy().unwrap_or(OverflowPolicy::Block),
+            config.queue_send_timeout(),
+            config.queue_spill_path.clone(),
+        )
+    }
+
+    pub fn with_policy(
+        capacity: usize,
+        policy: OverflowPolicy,
+        send_timeout: Duration,
+        spill_path: Option<String>,
+    ) -> Self {
+        let spill = match spill_path.as_deref().map(SpillCursor::recover) {
+            Some(Ok(cursor)) => cursor,
+            Some(Err(e)) => {
+                tracing::error!("❌ Failed to recover spilled events: {}", e);
+                SpillCursor::default()
+            }
+            None => SpillCursor::default(),
+        };
+        if spill.pending > 0 {
+            tracing::warn!("📂 Recovered {} spilled event(s)", spill.pending);
+        }
 
         Self {
-            sender,
-            receiver,
-            capacity,
-            metrics: QueueMetrics {
-                total_received: 0,
-                total_processed: 0,
-                current_size: 0,
-                max_capacity: capacity,
-            },
+            inner: Arc::new(QueueInner {
+                lanes: Mutex::new(Lanes::default()),
+                capacity: capacity.max(1),
+                policy,
+                send_timeout,
+                spill_path,
+                spill_depth: AtomicUsize::new(spill.pending),
+                spill: tokio::sync::Mutex::new(spill),
+                closed: AtomicBool::new(false),
+                not_empty: Notify::new(),
+                not_full: Notify::new(),
+                counters: QueueCounters::default(),
+            }),
         }
     }
 
-    // Step 4: Send event to queue with backpressure
-    pub async fn send(&mut self, event: PortfolioEvent) -> Result<(), QueueError> {
-        if self.metrics.current_size >= self.capacity {
-            return Err(QueueError::Full);
+    // Step 4: Enqueue according to the overflow policy. Priority events are never shed: a
+    // full priority lane always blocks, up to the send timeout.
+    pub async fn send(&self, event: PortfolioEvent) -> Result<(), QueueError> {
+        if self.is_closed() {
+            return Err(QueueError::Closed);
         }
 
-        match tokio::time::timeout(Duration::from_secs(5), self.sender.send(event)).await {
-            Ok(Ok(_)) => {
-                self.metrics.total_received += 1;
-                self.metrics.current_size += 1;
+        let lane = QueueLane::for_event(&event);
+        let policy = match lane {
+            QueueLane::Priority => OverflowPolicy::Block,
+            QueueLane::Bulk => self.inner.policy,
+        };
+
+        match policy {
+            OverflowPolicy::Block => self.send_blocking(event, lane).await,
+            OverflowPolicy::DropNewest => match self.try_push(event, lane) {
+                None => Ok(()),
+                Some(_) => {
+                    self.inner.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    Err(QueueError::Full)
+                }
+            },
+            OverflowPolicy::DropOldest => {
+                self.push_evicting(event, lane);
                 Ok(())
             }
-            Ok(Err(_)) => Err(QueueError::Closed),
-            Err(_) => Err(QueueError::Timeout),
+            OverflowPolicy::SpillToDisk => self.send_spilling(event).await,
+        }
+    }
+
+    // Returns the event back when its lane is full
+    fn try_push(&self, event: PortfolioEvent, lane: QueueLane) -> Option<PortfolioEvent> {
+        {
+            let mut lanes = self.lock_lanes();
+            let queue = lanes.lane(lane);
+            if queue.len() >= self.inner.capacity {
+                return Some(event);
+            }
+            queue.push_back(Queued {
+                event,
+                enqueued_at: Instant::now(),
+            });
+        }
+        self.inner.counters.received.fetch_add(1, Ordering::Relaxed);
+        self.inner.not_empty.notify_one();
+        None
+    }
+
+    async fn send_blocking(
+        &self,
+        mut event: PortfolioEvent,
+        lane: QueueLane,
+    ) -> Result<(), QueueError> {
+        let deadline = tokio::time::Instant::now() + self.inner.send_timeout;
+        loop {
+            // Register before checking so a slot freed in between is not missed
+            let notified = self.inner.not_full.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.try_push(event, lane) {
+                None => return Ok(()),
+                Some(rejected) => event = rejected,
+            }
+            if self.is_closed() {
+                return Err(QueueError::Closed);
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                self.inner.counters.timeouts.fetch_add(1, Ordering::Relaxed);
+                return Err(QueueError::Timeout);
+            }
+        }
+    }
+
+    fn push_evicting(&self, event: PortfolioEvent, lane: QueueLane) {
+        let evicted = {
+            let mut lanes = self.lock_lanes();
+            let queue = lanes.lane(lane);
+            let evicted = if queue.len() >= self.inner.capacity {
+                queue.pop_front()
+            } else {
+                None
+            };
+            queue.push_back(Queued {
+                event,
+                enqueued_at: Instant::now(),
+            });
+            evicted
+        };
+
+        self.inner.counters.received.fetch_add(1, Ordering::Relaxed);
+        if let Some(evicted) = evicted {
+            self.inner.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(
+                "🗑️ Queue full, dropped oldest event for {}",
+                evicted.event.wallet()
+            );
+        }
+        self.inner.not_empty.notify_one();
+    }
+
+    // Step 5: Overflow goes to a JSONL file and is read back once the bulk lane has room.
+    // While anything is on disk new events queue behind it, so order is kept.
+    async fn send_spilling(&self, event: PortfolioEvent) -> Result<(), QueueError> {
+        let Some(path) = &self.inner.spill_path else {
+            return self.send_blocking(event, QueueLane::Bulk).await;
+        };
+
+        let mut cursor = self.inner.spill.lock().await;
+        let event = if cursor.pending == 0 {
+            match self.try_push(event, QueueLane::Bulk) {
+                None => return Ok(()),
+                Some(rejected) => rejected,
+            }
+        } else {
+            event
+        };
+
+        let mut line =
+            serde_json::to_string(&event).map_err(|e| QueueError::Spill(e.to_string()))?;
+        line.push('\n');
+        let result = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            file.write_all(line.as_bytes()).await
+        }
+        .await;
+        result.map_err(|e| QueueError::Spill(e.to_string()))?;
+
+        cursor.pending += 1;
+        self.inner
+            .spill_depth
+            .store(cursor.pending, Ordering::Release);
+        self.inner.counters.spilled.fetch_add(1, Ordering::Relaxed);
+        self.inner.counters.received.fetch_add(1, Ordering::Relaxed);
+        self.inner.not_empty.notify_one();
+        Ok(())
+    }
+
+    // Move as many spilled events as fit back into the bulk lane, reading on from the saved
+    // offset. The new offset is saved before anything is queued, so an interrupted refill
+    // never duplicates events; once everything is read the file is emptied.
+    async fn refill_from_spill(&self) -> usize {
+        let Some(path) = &self.inner.spill_path else {
+            return 0;
+        };
+        let mut cursor = self.inner.spill.lock().await;
+        if cursor.pending == 0 {
+            return 0;
+        }
+
+        let room = self
+            .inner
+            .capacity
+            .saturating_sub(self.lock_lanes().bulk.len());
+        let result = async {
+            let mut file = tokio::fs::File::open(path).await?;
+            file.seek(SeekFrom::Start(cursor.offset)).await?;
+            let mut reader = tokio::io::BufReader::new(file);
+
+            let mut taken = Vec::new();
+            let mut consumed = 0;
+            let mut at_end = false;
+            let mut line = String::new();
+            while taken.len() < room {
+                line.clear();
+                let read = reader.read_line(&mut line).await?;
+                if read == 0 {
+                    at_end = true;
+                    break;
+                }
+                consumed += read as u64;
+                if !line.trim().is_empty() {
+                    taken.push(line.trim_end().to_string());
+                }
+            }
+
+            let pending = if at_end {
+                0
+            } else {
+                cursor.pending.saturating_sub(taken.len())
+            };
+            if pending == 0 {
+                tokio::fs::File::create(path).await?;
+                remove_if_exists(&offset_path(path)).await?;
+                *cursor = SpillCursor::default();
+            } else {
+                let offset = cursor.offset + consumed;
+                let tmp_path = format!("{}.tmp", offset_path(path));
+                tokio::fs::write(&tmp_path, offset.to_string()).await?;
+                tokio::fs::rename(&tmp_path, offset_path(path)).await?;
+                *cursor = SpillCursor { offset, pending };
+            }
+            Ok::<_, std::io::Error>(taken)
         }
+        .await;
+
+        let lines = match result {
+            Ok(lines) => lines,
+            Err(e) => {
+                tracing::error!("❌ Failed to read spilled events from {}: {}", path, e);
+                return 0;
+            }
+        };
+        let taken = lines.len();
+        let events: Vec<PortfolioEvent> = lines
+            .iter()
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    tracing::warn!("⚠️ Discarding unreadable spilled event: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        let now = Instant::now();
+        self.lock_lanes()
+            .bulk
+            .extend(events.into_iter().map(|event| Queued {
+                event,
+                enqueued_at: now,
+            }));
+        self.inner
+            .spill_depth
+            .store(cursor.pending, Ordering::Release);
+        taken
     }
 
-    // Step 5: Receive next event from queue
-    pub async fn recv(&mut self) -> Option<PortfolioEvent> {
-        match self.receiver.recv().await {
-            Some(event) => {
-                self.metrics.total_processed += 1;
-                self.metrics.current_size -= 1;
-                Some(event)
+    // Step 6: Next event, priority lane first. Returns `None` once closed and empty.
+    pub async fn recv(&self) -> Option<PortfolioEvent> {
+        loop {
+            let notified = self.inner.not_empty.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let popped = self.lock_lanes().pop();
+            if let Some(queued) = popped {
+                let counters = &self.inner.counters;
+                counters.processed.fetch_add(1, Ordering::Relaxed);
+                counters.latency_us.fetch_add(
+                    queued.enqueued_at.elapsed().as_micros() as u64,
+                    Ordering::Relaxed,
+                );
+                self.inner.not_full.notify_waiters();
+                return Some(queued.event);
             }
-            None => None,
+
+            if self.inner.spill_depth.load(Ordering::Acquire) > 0
+                && self.refill_from_spill().await > 0
+            {
+                continue;
+            }
+            if self.is_closed() {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    // Step 7: Reject new events; buffered (and spilled) ones can still be received
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+        self.inner.not_empty.notify_waiters();
+        self.inner.not_full.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::Acquire)
+    }
+
+    pub fn depth(&self, lane: QueueLane) -> usize {
+        self.lock_lanes().lane(lane).len()
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        let counters = &self.inner.counters;
+        let (priority_depth, bulk_depth) = {
+            let lanes = self.lock_lanes();
+            (lanes.priority.len(), lanes.bulk.len())
+        };
+
+        QueueStats {
+            received: counters.received.load(Ordering::Relaxed),
+            processed: counters.processed.load(Ordering::Relaxed),
+            dropped: counters.dropped.load(Ordering::Relaxed),
+            spilled: counters.spilled.load(Ordering::Relaxed),
+            timeouts: counters.timeouts.load(Ordering::Relaxed),
+            latency_us: counters.latency_us.load(Ordering::Relaxed),
+            priority_depth,
+            bulk_depth,
+            spill_depth: self.inner.spill_depth.load(Ordering::Acquire),
         }
     }
 
-    // Step 6: Get queue metrics
-    pub fn metrics(&self) -> &QueueMetrics {
-        &self.metrics
+    // Step 8: Periodically export depth, drops and latency. Counters are reported as deltas
+    // since the previous export.
+    pub fn spawn_metrics_reporter(&self, metrics: MetricsService) -> JoinHandle<()> {
+        let queue = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(METRICS_REPORT_INTERVAL);
+            let mut previous = QueueStats::default();
+            loop {
+                interval.tick().await;
+                let stats = queue.stats();
+
+                metrics
+                    .set_queue_depth(QueueLane::Priority.as_str(), stats.priority_depth)
+                    .await;
+                metrics
+                    .set_queue_depth(QueueLane::Bulk.as_str(), stats.bulk_depth)
+                    .await;
+                metrics.set_queue_depth("spill", stats.spill_depth).await;
+                metrics
+                    .record_queue_throughput(
+                        stats.received - previous.received,
+                        stats.processed - previous.processed,
+                    )
+                    .await;
+                metrics
+                    .record_queue_shed("dropped", stats.dropped - previous.dropped)
+                    .await;
+                metrics
+                    .record_queue_shed("spilled", stats.spilled - previous.spilled)
+                    .await;
+                metrics
+                    .record_queue_shed("timeout", stats.timeouts - previous.timeouts)
+                    .await;
+                let processed = stats.processed - previous.processed;
+                if processed > 0 {
+                    let latency_us = stats.latency_us - previous.latency_us;
+                    metrics
+                        .set_queue_latency(latency_us as f64 / processed as f64 / 1000.0)
+                        .await;
+                }
+
+                previous = stats;
+                if queue.is_closed() && stats.priority_depth + stats.bulk_depth == 0 {
+                    return;
+                }
+            }
+        })
+    }
+
+    // A panic while holding the lock cannot leave the lanes half-updated, so keep going
+    fn lock_lanes(&self) -> std::sync::MutexGuard<'_, Lanes> {
+        self.inner
+            .lanes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Step 9: Queue error types
+// The saved read offset lives next to the spill file
+fn offset_path(spill_path: &str) -> String {
+    format!("{}.offset", spill_path)
+}
+
+async fn remove_if_exists(path: &str) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
     }
 }
 
-// Step 7: Queue error types
 #[derive(Debug)]
 pub enum QueueError {
     Full,
     Closed,
     Timeout,
+    Spill(String),
 }
 
 impl std::fmt::Display for QueueError {
@@ -88,8 +580,127 @@ impl std::fmt::Display for QueueError {
             Self::Full => write!(f, "Queue is full"),
             Self::Closed => write!(f, "Queue is closed"),
             Self::Timeout => write!(f, "Send operation timed out"),
+            Self::Spill(e) => write!(f, "Failed to spill event to disk: {}", e),
         }
     }
 }
 
 impl std::error::Error for QueueError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn event(wallet: &str) -> PortfolioEvent {
+        create_position_update_event(wallet.to_string(), "mint".to_string(), 1.0)
+    }
+
+    fn reversal(wallet: &str) -> PortfolioEvent {
+        let mut event = event(wallet);
+        if let PortfolioEvent::PositionUpdate { reversal, .. } = &mut event {
+            *reversal = true;
+        }
+        event
+    }
+
+    async fn drain(queue: &EventQueue) -> Vec<String> {
+        queue.close();
+        let mut wallets = Vec::new();
+        while let Some(event) = queue.recv().await {
+            wallets.push(event.wallet().to_string());
+        }
+        wallets
+    }
+
+    // Fresh spill file under the temp dir, with any leftovers from an earlier run removed
+    fn spill_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mpsc_queue_{}_{}.jsonl", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(offset_path(&path));
+        path
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_evicts_the_head_of_the_lane() {
+        let queue = EventQueue::with_policy(2, OverflowPolicy::DropOldest, TIMEOUT, None);
+        for wallet in ["a", "b", "c"] {
+            queue.send(event(wallet)).await.unwrap();
+        }
+
+        assert_eq!(queue.stats().dropped, 1);
+        assert_eq!(drain(&queue).await, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn drop_newest_rejects_the_incoming_event() {
+        let queue = EventQueue::with_policy(2, OverflowPolicy::DropNewest, TIMEOUT, None);
+        queue.send(event("a")).await.unwrap();
+        queue.send(event("b")).await.unwrap();
+        assert!(matches!(
+            queue.send(event("c")).await,
+            Err(QueueError::Full)
+        ));
+
+        assert_eq!(queue.stats().dropped, 1);
+        assert_eq!(drain(&queue).await, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn priority_events_are_never_shed() {
+        let queue = EventQueue::with_policy(1, OverflowPolicy::DropOldest, TIMEOUT, None);
+        queue.send(event("bulk")).await.unwrap();
+        queue.send(reversal("first")).await.unwrap();
+        assert!(matches!(
+            queue.send(reversal("second")).await,
+            Err(QueueError::Timeout)
+        ));
+
+        let stats = queue.stats();
+        assert_eq!((stats.dropped, stats.timeouts), (0, 1));
+        // The priority lane is drained first
+        assert_eq!(drain(&queue).await, vec!["first", "bulk"]);
+    }
+
+    #[tokio::test]
+    async fn spilled_events_come_back_in_order() {
+        let path = spill_path("order");
+        let queue =
+            EventQueue::with_policy(2, OverflowPolicy::SpillToDisk, TIMEOUT, Some(path.clone()));
+        for wallet in ["a", "b", "c", "d", "e"] {
+            queue.send(event(wallet)).await.unwrap();
+        }
+
+        let stats = queue.stats();
+        assert_eq!((stats.bulk_depth, stats.spill_depth), (2, 3));
+        assert_eq!(stats.spilled, 3);
+        assert_eq!(drain(&queue).await, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(queue.stats().spill_depth, 0);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn spilled_events_survive_a_restart() {
+        let path = spill_path("restart");
+        let queue =
+            EventQueue::with_policy(1, OverflowPolicy::SpillToDisk, TIMEOUT, Some(path.clone()));
+        for wallet in ["a", "b", "c", "d"] {
+            queue.send(event(wallet)).await.unwrap();
+        }
+        // Receiving "b" reads it back from disk and saves the offset past it
+        assert_eq!(queue.recv().await.unwrap().wallet(), "a");
+        assert_eq!(queue.recv().await.unwrap().wallet(), "b");
+        drop(queue);
+
+        let restarted =
+            EventQueue::with_policy(1, OverflowPolicy::SpillToDisk, TIMEOUT, Some(path.clone()));
+        assert_eq!(restarted.stats().spill_depth, 2);
+        assert_eq!(drain(&restarted).await, vec!["c", "d"]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
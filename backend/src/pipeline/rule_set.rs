@@ -0,0 +1,962 @@
+// backend/src/pipeline/rule_set.rs
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::event::{PortfolioEvent, TransferDirection};
+use crate::models::risk_alert::AlertSeverity;
+use crate::pipeline::rule_state::{
+    LpPosition, PoolHistory, PriceHistory, PriceHistoryStore, WalletState, MAX_SAMPLES,
+    MAX_WINDOW_SECS,
+};
+
+// Placeholders every message template may use, whatever the condition
+const RULE_PLACEHOLDERS: &[&str] = &["rule_id", "rule_name"];
+// USDC and USDT, watched by depeg rules that list no mints
+const DEFAULT_STABLECOINS: &[&str] = &[
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+];
+
+fn default_peg() -> f64 {
+    1.0
+}
+
+// Step 1: Condition types a rule can be built from. Thresholds are in the event's own units;
+// the windowed conditions read the wallet's accumulated state instead of a single event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    // |pnl_delta| of a position update
+    PnlDelta {
+        min_abs: f64,
+    },
+    // Swap input amount, optionally for one input mint
+    SwapAmount {
+        min_amount: u64,
+        #[serde(default)]
+        mint: Option<String>,
+    },
+    // Notional of a recorded trade
+    TradeNotional {
+        min_notional: u64,
+    },
+    // Token transfer amount, optionally for one mint and direction
+    TransferAmount {
+        min_amount: u64,
+        #[serde(default)]
+        mint: Option<String>,
+        #[serde(default)]
+        direction: Option<TransferDirection>,
+    },
+    // |delta_lamports| of a stake change
+    StakeChange {
+        min_abs_lamports: u64,
+    },
+    // Realized PnL fell this far below its peak, all-time or within the window
+    Drawdown {
+        min_drawdown: f64,
+        #[serde(default)]
+        window_secs: Option<u64>,
+    },
+    // Largest USD-priced holding exceeds this share (0..1] of the wallet's value
+    Concentration {
+        max_share: f64,
+        #[serde(default)]
+        min_portfolio_value: f64,
+    },
+    // Trades (and swaps) within the window exceed a count or notional
+    TradeVelocity {
+        window_secs: u64,
+        #[serde(default)]
+        max_trades: Option<u32>,
+        #[serde(default)]
+        max_notional: Option<u64>,
+    },
+    // This many PnL updates in a row were losses
+    ConsecutiveLosses {
+        min_count: u32,
+    },
+    // Market-wide: a stablecoin trades more than this many bps away from its peg
+    StablecoinDepeg {
+        max_deviation_bps: u32,
+        #[serde(default)]
+        mints: Vec<String>, // Defaults to USDC and USDT
+        #[serde(default = "default_peg")]
+        peg_usd: f64,
+    },
+    // Market-wide: a mint's price moves more than this percentage within the window
+    PriceShock {
+        max_move_pct: f64,
+        window_secs: u64,
+        #[serde(default)]
+        mints: Vec<String>, // Any priced mint when empty
+    },
+    // LP health: a liquidity position is worth this many percent less than holding its deposits
+    ImpermanentLoss {
+        max_loss_pct: f64,
+        #[serde(default)]
+        pools: Vec<String>, // Any amm-pool pool when empty
+    },
+    // LP health: either vault reserve of a pool drops this many percent within the window
+    PoolReserveDrop {
+        max_drop_pct: f64,
+        window_secs: u64,
+        #[serde(default)]
+        pools: Vec<String>, // Any amm-pool pool when empty
+    },
+}
+
+// What a matched condition measured: `magnitude` grows as the condition worsens, `values` are
+// extra message placeholders taken from the wallet state
+#[derive(Debug, Clone, Default)]
+pub struct RuleMatch {
+    pub magnitude: f64,
+    pub values: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RuleMatch {
+    fn new(magnitude: f64) -> Self {
+        Self {
+            magnitude,
+            values: serde_json::Map::new(),
+        }
+    }
+
+    fn with(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.values.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+impl RuleCondition {
+    // Step 2: Evaluate against an event and its wallet's state, already updated with the event,
+    // valuing holdings at the latest feed prices
+    pub fn evaluate(
+        &self,
+        event: &PortfolioEvent,
+        state: &WalletState,
+        prices: &PriceHistoryStore,
+    ) -> Option<RuleMatch> {
+        // A reversal only takes back state; it is not new activity to alert on
+        if event.is_reversal() {
+            return None;
+        }
+        let now = *event.timestamp();
+        match (self, event) {
+            (Self::PnlDelta { min_abs }, PortfolioEvent::PositionUpdate { pnl_delta, .. }) => {
+                (pnl_delta.abs() >= *min_abs).then(|| RuleMatch::new(pnl_delta.abs()))
+            }
+            (
+                Self::SwapAmount { min_amount, mint },
+                PortfolioEvent::SwapExecuted {
+                    amount, input_mint, ..
+                },
+            ) => (amount >= min_amount && mint.as_ref().is_none_or(|mint| mint == input_mint))
+                .then(|| RuleMatch::new(*amount as f64)),
+            (
+                Self::TradeNotional { min_notional },
+                PortfolioEvent::TradeRecorded { notional, .. },
+            ) => (notional >= min_notional).then(|| RuleMatch::new(*notional as f64)),
+            (
+                Self::TransferAmount {
+                    min_amount,
+                    mint,
+                    direction,
+                },
+                PortfolioEvent::Transfer {
+                    amount,
+                    mint: event_mint,
+                    direction: event_direction,
+                    ..
+                },
+            ) => (amount >= min_amount
+                && mint.as_ref().is_none_or(|mint| mint == event_mint)
+                && direction.is_none_or(|direction| direction == *event_direction))
+            .then(|| RuleMatch::new(*amount as f64)),
+            (
+                Self::StakeChange { min_abs_lamports },
+                PortfolioEvent::StakeChanged { delta_lamports, .. },
+            ) => (delta_lamports.unsigned_abs() >= *min_abs_lamports)
+                .then(|| RuleMatch::new(delta_lamports.unsigned_abs() as f64)),
+            (
+                Self::Drawdown {
+                    min_drawdown,
+                    window_secs,
+                },
+                PortfolioEvent::PositionUpdate { .. },
+            ) => {
+                let (peak, drawdown) = state.drawdown(*window_secs, now);
+                (drawdown >= *min_drawdown).then(|| {
+                    RuleMatch::new(drawdown)
+                        .with("drawdown", drawdown)
+                        .with("peak_pnl", peak)
+                        .with("realized_pnl", state.realized_pnl)
+                })
+            }
+            (
+                Self::Concentration {
+                    max_share,
+                    min_portfolio_value,
+                },
+                PortfolioEvent::TradeRecorded { .. }
+                | PortfolioEvent::SwapExecuted { .. }
+                | PortfolioEvent::Transfer { .. },
+            ) => {
+                let concentration = state.concentration(prices)?;
+                (concentration.share > *max_share
+                    && concentration.portfolio_value >= *min_portfolio_value)
+                    .then(|| {
+                        RuleMatch::new(concentration.share)
+                            .with("top_mint", concentration.mint)
+                            .with("share_pct", concentration.share * 100.0)
+                            .with("position_value", concentration.position_value)
+                            .with("portfolio_value", concentration.portfolio_value)
+                    })
+            }
+            (
+                Self::TradeVelocity {
+                    window_secs,
+                    max_trades,
+                    max_notional,
+                },
+                PortfolioEvent::TradeRecorded { .. } | PortfolioEvent::SwapExecuted { .. },
+            ) => {
+                let (trades, notional) = state.trade_activity(*window_secs, now);
+                // How far past its limit the worse of the two measures is
+                let ratio = f64::max(
+                    max_trades.map_or(0.0, |max| trades as f64 / max as f64),
+                    max_notional.map_or(0.0, |max| notional as f64 / max as f64),
+                );
+                (ratio > 1.0).then(|| {
+                    RuleMatch::new(ratio)
+                        .with("trade_count", trades)
+                        .with("window_notional", notional)
+                })
+            }
+            (Self::ConsecutiveLosses { min_count }, PortfolioEvent::PositionUpdate { .. }) => {
+                (state.losing_streak >= *min_count).then(|| {
+                    RuleMatch::new(state.losing_streak as f64)
+                        .with("losing_streak", state.losing_streak)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // Market-wide conditions are judged on price events and fanned out to every holder
+    pub fn is_market(&self) -> bool {
+        matches!(self, Self::StablecoinDepeg { .. } | Self::PriceShock { .. })
+    }
+
+    // Step 3: Evaluate a market-wide condition against a price event and the mint's history,
+    // already updated with the event
+    pub fn evaluate_market(
+        &self,
+        event: &PortfolioEvent,
+        history: &PriceHistory,
+    ) -> Option<RuleMatch> {
+        let PortfolioEvent::PriceUpdated {
+            mint,
+            price_usd,
+            timestamp,
+        } = event
+        else {
+            return None;
+        };
+
+        match self {
+            Self::StablecoinDepeg {
+                max_deviation_bps,
+                mints,
+                peg_usd,
+            } => {
+                let watched = if mints.is_empty() {
+                    DEFAULT_STABLECOINS.contains(&mint.as_str())
+                } else {
+                    mints.contains(mint)
+                };
+                let deviation_bps = (price_usd - peg_usd).abs() / peg_usd * 10_000.0;
+                (watched && deviation_bps > *max_deviation_bps as f64)
+                    .then(|| RuleMatch::new(deviation_bps).with("deviation_bps", deviation_bps))
+            }
+            Self::PriceShock {
+                max_move_pct,
+                window_secs,
+                mints,
+            } => {
+                if !mints.is_empty() && !mints.contains(mint) {
+                    return None;
+                }
+                let (reference, move_pct) = history.price_move(*window_secs, *timestamp)?;
+                (move_pct.abs() > *max_move_pct).then(|| {
+                    RuleMatch::new(move_pct.abs())
+                        .with("move_pct", move_pct)
+                        .with("reference_price", reference)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // LP health conditions are judged on reserve changes, once per liquidity provider
+    pub fn is_pool(&self) -> bool {
+        matches!(
+            self,
+            Self::ImpermanentLoss { .. } | Self::PoolReserveDrop { .. }
+        )
+    }
+
+    // Step 4: Evaluate an LP health condition for one provider's position, against the pool's
+    // history already updated with the reserve change
+    pub fn evaluate_pool(
+        &self,
+        event: &PortfolioEvent,
+        history: &PoolHistory,
+        position: &LpPosition,
+    ) -> Option<RuleMatch> {
+        let PortfolioEvent::PoolReservesChanged {
+            pool, timestamp, ..
+        } = event
+        else {
+            return None;
+        };
+
+        let mut matched = match self {
+            Self::ImpermanentLoss {
+                max_loss_pct,
+                pools,
+            } => {
+                if !pools.is_empty() && !pools.contains(pool) {
+                    return None;
+                }
+                let loss = position.impermanent_loss(&history.latest)?;
+                (-loss.il_pct > *max_loss_pct).then(|| {
+                    RuleMatch::new(-loss.il_pct)
+                        .with("il_pct", loss.il_pct)
+                        .with("lp_value", loss.lp_value)
+                        .with("hold_value", loss.hold_value)
+                })?
+            }
+            Self::PoolReserveDrop {
+                max_drop_pct,
+                window_secs,
+                pools,
+            } => {
+                if !pools.is_empty() && !pools.contains(pool) {
+                    return None;
+                }
+                let (drained_mint, drop_pct) = history.reserve_drop(*window_secs, *timestamp)?;
+                (drop_pct > *max_drop_pct).then(|| {
+                    RuleMatch::new(drop_pct)
+                        .with("drop_pct", drop_pct)
+                        .with("drained_mint", drained_mint)
+                })?
+            }
+            _ => return None,
+        };
+
+        // A fully drained pool has no LP supply left to decompose
+        if let Some((underlying_a, underlying_b)) = position.underlying(&history.latest) {
+            matched = matched
+                .with("underlying_a", underlying_a)
+                .with("underlying_b", underlying_b);
+        }
+        Some(matched.with("lp_tokens", position.lp_tokens))
+    }
+
+    // Fields of the matched event plus the condition's own parameters and measured values
+    fn placeholders(&self) -> &'static [&'static str] {
+        match self {
+            Self::PnlDelta { .. } => &["wallet", "mint", "pnl_delta", "timestamp", "min_abs"],
+            Self::SwapAmount { .. } => &[
+                "wallet",
+                "input_mint",
+                "output_mint",
+                "amount",
+                "timestamp",
+                "min_amount",
+                "mint",
+            ],
+            Self::TradeNotional { .. } => &[
+                "wallet",
+                "mint",
+                "amount",
+                "notional",
+                "timestamp",
+                "min_notional",
+            ],
+            Self::TransferAmount { .. } => &[
+                "wallet",
+                "mint",
+                "amount",
+                "direction",
+                "counterparty",
+                "timestamp",
+                "min_amount",
+            ],
+            Self::StakeChange { .. } => &[
+                "wallet",
+                "stake_account",
+                "validator",
+                "delta_lamports",
+                "timestamp",
+                "min_abs_lamports",
+            ],
+            Self::Drawdown { .. } => &[
+                "wallet",
+                "mint",
+                "pnl_delta",
+                "timestamp",
+                "min_drawdown",
+                "window_secs",
+                "drawdown",
+                "peak_pnl",
+                "realized_pnl",
+            ],
+            Self::Concentration { .. } => &[
+                "wallet",
+                "timestamp",
+                "max_share",
+                "min_portfolio_value",
+                "top_mint",
+                "share_pct",
+                "position_value",
+                "portfolio_value",
+            ],
+            Self::TradeVelocity { .. } => &[
+                "wallet",
+                "timestamp",
+                "window_secs",
+                "max_trades",
+                "max_notional",
+                "trade_count",
+                "window_notional",
+            ],
+            Self::ConsecutiveLosses { .. } => &[
+                "wallet",
+                "mint",
+                "pnl_delta",
+                "timestamp",
+                "min_count",
+                "losing_streak",
+            ],
+            Self::StablecoinDepeg { .. } => &[
+                "wallet",
+                "mint",
+                "price_usd",
+                "timestamp",
+                "max_deviation_bps",
+                "peg_usd",
+                "deviation_bps",
+            ],
+            Self::PriceShock { .. } => &[
+                "wallet",
+                "mint",
+                "price_usd",
+                "timestamp",
+                "max_move_pct",
+                "window_secs",
+                "move_pct",
+                "reference_price",
+            ],
+            Self::ImpermanentLoss { .. } => &[
+                "wallet",
+                "pool",
+                "token_a",
+                "token_b",
+                "reserve_a",
+                "reserve_b",
+                "total_liquidity",
+                "timestamp",
+                "max_loss_pct",
+                "il_pct",
+                "lp_value",
+                "hold_value",
+                "lp_tokens",
+                "underlying_a",
+                "underlying_b",
+            ],
+            Self::PoolReserveDrop { .. } => &[
+                "wallet",
+                "pool",
+                "token_a",
+                "token_b",
+                "reserve_a",
+                "reserve_b",
+                "total_liquidity",
+                "timestamp",
+                "max_drop_pct",
+                "window_secs",
+                "drop_pct",
+                "drained_mint",
+                "lp_tokens",
+                "underlying_a",
+                "underlying_b",
+            ],
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let valid_window = |secs: u64| (1..=MAX_WINDOW_SECS).contains(&secs);
+        let valid = match self {
+            Self::PnlDelta { min_abs } => min_abs.is_finite() && *min_abs > 0.0,
+            Self::SwapAmount { min_amount, .. } | Self::TransferAmount { min_amount, .. } => {
+                *min_amount > 0
+            }
+            Self::TradeNotional { min_notional } => *min_notional > 0,
+            Self::StakeChange { min_abs_lamports } => *min_abs_lamports > 0,
+            Self::Drawdown {
+                min_drawdown,
+                window_secs,
+            } => {
+                if window_secs.is_some_and(|secs| !valid_window(secs)) {
+                    return Err(format!(
+                        "window_secs must be between 1 and {}",
+                        MAX_WINDOW_SECS
+                    ));
+                }
+                min_drawdown.is_finite() && *min_drawdown > 0.0
+            }
+            Self::Concentration {
+                max_share,
+                min_portfolio_value,
+            } => {
+                if !(*max_share > 0.0 && *max_share < 1.0) {
+                    return Err("max_share must be between 0 and 1 (exclusive)".to_string());
+                }
+                min_portfolio_value.is_finite() && *min_portfolio_value >= 0.0
+            }
+            Self::TradeVelocity {
+                window_secs,
+                max_trades,
+                max_notional,
+            } => {
+                if !valid_window(*window_secs) {
+                    return Err(format!(
+                        "window_secs must be between 1 and {}",
+                        MAX_WINDOW_SECS
+                    ));
+                }
+                if max_trades.is_none() && max_notional.is_none() {
+                    return Err("max_trades or max_notional is required".to_string());
+                }
+                // Older trades are dropped past this many, so a higher count could never trip
+                if max_trades.is_some_and(|max| max as usize > MAX_SAMPLES) {
+                    return Err(format!("max_trades must be at most {}", MAX_SAMPLES));
+                }
+                max_trades.is_none_or(|max| max > 0) && max_notional.is_none_or(|max| max > 0)
+            }
+            Self::ConsecutiveLosses { min_count } => *min_count > 0,
+            Self::StablecoinDepeg {
+                max_deviation_bps,
+                peg_usd,
+                ..
+            } => {
+                if !(peg_usd.is_finite() && *peg_usd > 0.0) {
+                    return Err("peg_usd must be positive".to_string());
+                }
+                *max_deviation_bps > 0
+            }
+            Self::PriceShock {
+                max_move_pct,
+                window_secs,
+                ..
+            } => {
+                if !valid_window(*window_secs) {
+                    return Err(format!(
+                        "window_secs must be between 1 and {}",
+                        MAX_WINDOW_SECS
+                    ));
+                }
+                max_move_pct.is_finite() && *max_move_pct > 0.0
+            }
+            Self::ImpermanentLoss { max_loss_pct, .. } => {
+                if !(*max_loss_pct > 0.0 && *max_loss_pct < 100.0) {
+                    return Err("max_loss_pct must be between 0 and 100 (exclusive)".to_string());
+                }
+                true
+            }
+            Self::PoolReserveDrop {
+                max_drop_pct,
+                window_secs,
+                ..
+            } => {
+                if !valid_window(*window_secs) {
+                    return Err(format!(
+                        "window_secs must be between 1 and {}",
+                        MAX_WINDOW_SECS
+                    ));
+                }
+                if !(*max_drop_pct > 0.0 && *max_drop_pct < 100.0) {
+                    return Err("max_drop_pct must be between 0 and 100 (exclusive)".to_string());
+                }
+                true
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err("threshold must be positive".to_string())
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// Step 5: One rule as written in the rules file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub severity: AlertSeverity,
+    pub message: String, // Template with named placeholders, e.g. `{wallet}` or `{pnl_delta:.2}`
+    pub condition: RuleCondition,
+}
+
+impl RuleDefinition {
+    // Step 6: Fill the message template from the event, the condition parameters and the
+    // values the condition measured
+    pub fn render_message(&self, event: &PortfolioEvent, matched: &RuleMatch) -> String {
+        let mut values = event_fields(event);
+        values.extend(matched.values.clone());
+        if let Ok(serde_json::Value::Object(params)) = serde_json::to_value(&self.condition) {
+            for (key, value) in params {
+                values.entry(key).or_insert(value);
+            }
+        }
+        values.insert("rule_id".to_string(), serde_json::json!(self.id));
+        values.insert("rule_name".to_string(), serde_json::json!(self.name));
+
+        let mut message = String::with_capacity(self.message.len());
+        let mut rest = self.message.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            message.push_str(&rest[..start]);
+            let placeholder = &rest[start + 1..start + end];
+            let (name, precision) = parse_placeholder(placeholder);
+            message.push_str(&format_value(values.get(name), precision));
+            rest = &rest[start + end + 1..];
+        }
+        message.push_str(rest);
+        message
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let label = if self.id.is_empty() {
+            "<unnamed>"
+        } else {
+            &self.id
+        };
+
+        if self.id.trim().is_empty() {
+            errors.push("rule id cannot be empty".to_string());
+        }
+        if self.name.trim().is_empty() {
+            errors.push(format!("rule '{}': name cannot be empty", label));
+        }
+        if let Err(e) = self.condition.validate() {
+            errors.push(format!("rule '{}': {}", label, e));
+        }
+
+        match template_placeholders(&self.message) {
+            Ok(placeholders) => {
+                let allowed = self.condition.placeholders();
+                for placeholder in placeholders {
+                    let (name, _) = parse_placeholder(&placeholder);
+                    if !allowed.contains(&name) && !RULE_PLACEHOLDERS.contains(&name) {
+                        errors.push(format!(
+                            "rule '{}': unknown placeholder '{{{}}}' (available: {})",
+                            label,
+                            name,
+                            allowed
+                                .iter()
+                                .chain(RULE_PLACEHOLDERS)
+                                .copied()
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("rule '{}': {}", label, e)),
+        }
+
+        errors
+    }
+}
+
+// Step 7: A validated set of rules and the file it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleSet {
+    pub source: Option<String>, // `None` for the built-in defaults
+    pub rules: Vec<RuleDefinition>,
+}
+
+#[derive(Deserialize)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+}
+
+impl Default for RuleSet {
+    // Used when no rules file exists
+    fn default() -> Self {
+        Self {
+            source: None,
+            rules: vec![
+                RuleDefinition {
+                    id: "high_pnl_swing".to_string(),
+                    name: "High PnL Swing".to_string(),
+                    enabled: true,
+                    severity: AlertSeverity::High,
+                    message: "Position {mint} moved {pnl_delta:.2} in a single update".to_string(),
+                    condition: RuleCondition::PnlDelta { min_abs: 300.0 },
+                },
+                RuleDefinition {
+                    id: "large_swap".to_string(),
+                    name: "Large Swap Detected".to_string(),
+                    enabled: true,
+                    severity: AlertSeverity::Medium,
+                    message: "Large swap of {amount} {input_mint} detected".to_string(),
+                    condition: RuleCondition::SwapAmount {
+                        min_amount: 1_000_000,
+                        mint: None,
+                    },
+                },
+            ],
+        }
+    }
+}
+
+impl RuleSet {
+    // Step 8: Load and validate a TOML or YAML rules file (format chosen by extension)
+    pub fn load(path: &str) -> Result<Self, RuleError> {
+        if !Path::new(path).is_file() {
+            return Err(RuleError::Io(format!("{} does not exist", path)));
+        }
+        let file: RuleFile = ::config::Config::builder()
+            .add_source(::config::File::from(Path::new(path)))
+            .build()
+            .and_then(|settings| settings.try_deserialize())
+            .map_err(|e| RuleError::Parse(e.to_string()))?;
+
+        let rule_set = Self {
+            source: Some(path.to_string()),
+            rules: file.rules,
+        };
+        rule_set.validate()?;
+        Ok(rule_set)
+    }
+
+    pub fn validate(&self) -> Result<(), RuleError> {
+        let mut errors: Vec<String> = self.rules.iter().flat_map(|rule| rule.validate()).collect();
+
+        let mut seen = HashSet::new();
+        for rule in &self.rules {
+            if !seen.insert(rule.id.as_str()) {
+                errors.push(format!("duplicate rule id '{}'", rule.id));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(RuleError::Invalid(errors))
+        }
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &RuleDefinition> {
+        self.rules.iter().filter(|rule| rule.enabled)
+    }
+}
+
+// Step 9: Rule loading errors
+#[derive(Debug)]
+pub enum RuleError {
+    Io(String),
+    Parse(String),
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read rules: {}", e),
+            Self::Parse(e) => write!(f, "Failed to parse rules: {}", e),
+            Self::Invalid(errors) => write!(f, "Invalid rules: {}", errors.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// The variant's fields of an externally tagged event, e.g. `wallet` and `pnl_delta`
+pub fn event_fields(event: &PortfolioEvent) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::to_value(event) {
+        Ok(serde_json::Value::Object(variant)) => variant
+            .into_iter()
+            .next()
+            .and_then(|(_, fields)| match fields {
+                serde_json::Value::Object(fields) => Some(fields),
+                _ => None,
+            })
+            .unwrap_or_default(),
+        _ => serde_json::Map::new(),
+    }
+}
+
+// `name` or `name:.N`
+fn parse_placeholder(placeholder: &str) -> (&str, Option<usize>) {
+    match placeholder.split_once(":.") {
+        Some((name, precision)) => (name.trim(), precision.trim().parse().ok()),
+        None => (placeholder.trim(), None),
+    }
+}
+
+fn template_placeholders(template: &str) -> Result<Vec<String>, String> {
+    let mut placeholders = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err("unmatched '}' in message".to_string());
+        }
+        let Some(end) = rest[start + 1..].find(['{', '}']) else {
+            return Err("unclosed '{' in message".to_string());
+        };
+        let end = start + 1 + end;
+        if rest[end..].starts_with('{') {
+            return Err("nested '{' in message".to_string());
+        }
+        let placeholder = &rest[start + 1..end];
+        if let Some((_, precision)) = placeholder.split_once(":.") {
+            if precision.trim().parse::<usize>().is_err() {
+                return Err(format!("invalid precision in '{{{}}}'", placeholder));
+            }
+        }
+        placeholders.push(placeholder.to_string());
+        rest = &rest[end + 1..];
+    }
+    Ok(placeholders)
+}
+
+fn format_value(value: Option<&serde_json::Value>, precision: Option<usize>) -> String {
+    match (value, precision) {
+        (None | Some(serde_json::Value::Null), _) => "-".to_string(),
+        (Some(serde_json::Value::String(s)), _) => s.clone(),
+        (Some(serde_json::Value::Number(n)), Some(precision)) => match n.as_f64() {
+            Some(n) => format!("{:.*}", precision, n),
+            None => n.to_string(),
+        },
+        (Some(value), _) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    fn rule(message: &str, condition: RuleCondition) -> RuleDefinition {
+        RuleDefinition {
+            id: "swing".to_string(),
+            name: "PnL Swing".to_string(),
+            enabled: true,
+            severity: AlertSeverity::High,
+            message: message.to_string(),
+            condition,
+        }
+    }
+
+    fn pnl_rule(message: &str) -> RuleDefinition {
+        rule(message, RuleCondition::PnlDelta { min_abs: 300.0 })
+    }
+
+    fn event() -> PortfolioEvent {
+        create_position_update_event("wallet-1".to_string(), "mint-1".to_string(), -412.3456)
+    }
+
+    #[test]
+    fn placeholders_come_from_the_event_condition_and_rule() {
+        let rule =
+            pnl_rule("[{rule_name}/{rule_id}] {wallet} {mint} moved {pnl_delta:.2} (>= {min_abs})");
+        assert_eq!(
+            rule.render_message(&event(), &RuleMatch::new(412.0)),
+            "[PnL Swing/swing] wallet-1 mint-1 moved -412.35 (>= 300.0)"
+        );
+    }
+
+    #[test]
+    fn measured_values_override_condition_parameters() {
+        let rule = rule(
+            "down {drawdown:.1} of {min_drawdown}",
+            RuleCondition::Drawdown {
+                min_drawdown: 100.0,
+                window_secs: None,
+            },
+        );
+        let matched = RuleMatch::new(150.0)
+            .with("drawdown", 150.04)
+            .with("min_drawdown", 1.0);
+        assert_eq!(rule.render_message(&event(), &matched), "down 150.0 of 1.0");
+    }
+
+    #[test]
+    fn missing_values_render_as_a_dash() {
+        let rule = pnl_rule("{wallet} at {price:.2}, {unclosed");
+        assert_eq!(
+            rule.render_message(&event(), &RuleMatch::new(1.0)),
+            "wallet-1 at -, {unclosed"
+        );
+    }
+
+    #[test]
+    fn templates_are_checked_against_the_condition() {
+        assert!(pnl_rule("{wallet} moved {pnl_delta:.2} per {rule_name}")
+            .validate()
+            .is_empty());
+
+        for (template, error) in [
+            ("{price}", "unknown placeholder '{price}'"),
+            ("{wallet", "unclosed '{'"),
+            ("wallet}", "unmatched '}'"),
+            ("{wal{let}}", "nested '{'"),
+            ("{pnl_delta:.x}", "invalid precision in '{pnl_delta:.x}'"),
+        ] {
+            let errors = pnl_rule(template).validate();
+            assert_eq!(errors.len(), 1, "{}: {:?}", template, errors);
+            assert!(errors[0].contains(error), "{}: {}", template, errors[0]);
+        }
+    }
+
+    #[test]
+    fn trade_limits_must_fit_the_kept_samples() {
+        let velocity = |max_trades| {
+            rule(
+                "{trade_count} trades",
+                RuleCondition::TradeVelocity {
+                    window_secs: 60,
+                    max_trades: Some(max_trades),
+                    max_notional: None,
+                },
+            )
+        };
+
+        assert!(velocity(MAX_SAMPLES as u32).validate().is_empty());
+        let errors = velocity(MAX_SAMPLES as u32 + 1).validate();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(
+            errors[0].contains("max_trades must be at most"),
+            "{}",
+            errors[0]
+        );
+    }
+}
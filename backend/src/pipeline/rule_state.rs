@@ -0,0 +1,981 @@
+// backend/src/pipeline/rule_state.rs
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::event::{PortfolioEvent, TransferDirection};
+use crate::pipeline::debounced_writer::write_atomic;
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Longest sliding window a rule may use; older samples are dropped
+pub const MAX_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+// Samples kept per wallet and series, whatever the window length
+pub const MAX_SAMPLES: usize = 1024;
+// Share of the tracked wallets evicted at once when the store is full
+const EVICT_FRACTION: usize = 100;
+// Mints with a price history; updates for further mints are not tracked
+const MAX_PRICED_MINTS: usize = 10_000;
+// Pools with a reserve history; updates for further pools are not tracked
+const MAX_TRACKED_POOLS: usize = 10_000;
+
+// Step 1: Per-wallet state the windowed rules read from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletState {
+    pub realized_pnl: f64, // Sum of PnL deltas seen since tracking started
+    pub peak_pnl: f64,
+    pub losing_streak: u32, // Consecutive PnL updates below zero
+    pnl_history: VecDeque<(DateTime<Utc>, f64)>, // Realized PnL after each update
+    trades: VecDeque<(DateTime<Utc>, u64)>, // Notional per trade; 0 for swaps, which carry none
+    holdings: HashMap<String, u64>, // Base units: on-chain balances plus transfers and swaps since
+    #[serde(default)]
+    positions: HashSet<String>, // Mints with a portfolio-program position
+    #[serde(default)]
+    decimals: HashMap<String, u8>, // Per mint, from the seeded token accounts
+    #[serde(default)]
+    lp_positions: HashMap<String, LpPosition>, // Keyed by amm-pool pool address
+    last_seen: DateTime<Utc>,
+}
+
+// LP tokens a wallet holds in one pool and the amounts they stood for when acquired
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LpPosition {
+    pub lp_tokens: u64,
+    pub deposited_a: u64,
+    pub deposited_b: u64,
+    #[serde(default)]
+    pub lp_mint: Option<String>, // Set once the position follows a token balance
+}
+
+// LP position valued against simply holding its deposits, both in token B at the pool price
+#[derive(Debug, Clone)]
+pub struct ImpermanentLoss {
+    pub underlying_a: f64,
+    pub underlying_b: f64,
+    pub lp_value: f64,
+    pub hold_value: f64,
+    pub il_pct: f64, // Negative when the position is worth less than holding
+}
+
+// Largest priced holding and the wallet value it was measured against
+#[derive(Debug, Clone)]
+pub struct Concentration {
+    pub mint: String,
+    pub position_value: f64,
+    pub portfolio_value: f64,
+    pub share: f64,
+}
+
+impl WalletState {
+    // Step 2: Fold an event into the state; returns false for events the rules do not track
+    fn apply(&mut self, event: &PortfolioEvent) -> bool {
+        let now = *event.timestamp();
+        match event {
+            // Reversals keep the timestamp of the event they undo
+            PortfolioEvent::PositionUpdate {
+                pnl_delta,
+                reversal: true,
+                ..
+            } => self.undo_pnl(now, *pnl_delta),
+            PortfolioEvent::PositionUpdate { pnl_delta, .. } => {
+                self.realized_pnl += pnl_delta;
+                self.peak_pnl = self.peak_pnl.max(self.realized_pnl);
+                if *pnl_delta < 0.0 {
+                    self.losing_streak += 1;
+                } else if *pnl_delta > 0.0 {
+                    self.losing_streak = 0;
+                }
+                push_sample(&mut self.pnl_history, (now, self.realized_pnl));
+            }
+            PortfolioEvent::TradeRecorded {
+                notional,
+                reversal: true,
+                ..
+            } => self.remove_trade(now, *notional),
+            PortfolioEvent::TradeRecorded { notional, .. } => {
+                push_sample(&mut self.trades, (now, *notional));
+            }
+            PortfolioEvent::SwapExecuted {
+                input_mint,
+                output_mint,
+                amount,
+                amount_out,
+                reversal,
+                ..
+            } => {
+                // A reversal is already inverted, so only its trade sample needs undoing
+                self.debit(input_mint, *amount);
+                self.credit(output_mint, *amount_out);
+                if *reversal {
+                    self.remove_trade(now, 0);
+                } else {
+                    push_sample(&mut self.trades, (now, 0));
+                }
+            }
+            PortfolioEvent::LiquidityAdded {
+                pool,
+                amount_a,
+                amount_b,
+                lp_tokens,
+                ..
+            } => {
+                // The minted tokens also move the balance a followed position is checked against
+                let position = self.lp_positions.entry(pool.clone()).or_default();
+                position.add(*lp_tokens, *amount_a, *amount_b);
+                if let Some(lp_mint) = position.lp_mint.clone() {
+                    self.credit(&lp_mint, *lp_tokens);
+                }
+            }
+            PortfolioEvent::LiquidityRemoved {
+                pool, lp_tokens, ..
+            } => {
+                if let Some(position) = self.lp_positions.get_mut(pool) {
+                    position.reduce_to(position.lp_tokens.saturating_sub(*lp_tokens));
+                    let lp_mint = position.lp_mint.clone();
+                    if position.lp_tokens == 0 {
+                        self.lp_positions.remove(pool);
+                    }
+                    if let Some(lp_mint) = lp_mint {
+                        self.debit(&lp_mint, *lp_tokens);
+                    }
+                }
+            }
+            PortfolioEvent::Transfer {
+                mint,
+                amount,
+                direction,
+                ..
+            } => match direction {
+                TransferDirection::Incoming => self.credit(mint, *amount),
+                TransferDirection::Outgoing => self.debit(mint, *amount),
+            },
+            PortfolioEvent::PositionSnapshot {
+                mint: Some(mint), ..
+            } => {
+                self.positions.insert(mint.clone());
+            }
+            _ => return false,
+        }
+
+        self.last_seen = self.last_seen.max(now);
+        self.prune(self.last_seen);
+        true
+    }
+
+    // Take back a reverted PnL update recorded at `at`; `delta` is already negated. Later
+    // samples shift by it, and the peak is recomputed if the reverted update reached it.
+    fn undo_pnl(&mut self, at: DateTime<Utc>, delta: f64) {
+        self.realized_pnl += delta;
+        if delta > 0.0 {
+            self.losing_streak = self.losing_streak.saturating_sub(1);
+        }
+
+        let reached_peak = self
+            .pnl_history
+            .iter()
+            .any(|(sampled, pnl)| *sampled >= at && *pnl >= self.peak_pnl);
+        if let Some(index) = self
+            .pnl_history
+            .iter()
+            .position(|(sampled, _)| *sampled == at)
+        {
+            self.pnl_history.remove(index);
+        }
+        for (_, pnl) in self
+            .pnl_history
+            .iter_mut()
+            .filter(|(sampled, _)| *sampled >= at)
+        {
+            *pnl += delta;
+        }
+        if reached_peak {
+            self.peak_pnl = self
+                .pnl_history
+                .iter()
+                .map(|(_, pnl)| *pnl)
+                .fold(self.realized_pnl, f64::max);
+        }
+    }
+
+    // Drop a reverted trade from the activity window
+    fn remove_trade(&mut self, at: DateTime<Utc>, notional: u64) {
+        if let Some(index) = self
+            .trades
+            .iter()
+            .rposition(|sample| *sample == (at, notional))
+        {
+            self.trades.remove(index);
+        }
+    }
+
+    fn credit(&mut self, mint: &str, amount: u64) {
+        if amount > 0 {
+            let balance = self.holdings.entry(mint.to_string()).or_default();
+            *balance = balance.saturating_add(amount);
+        }
+    }
+
+    fn debit(&mut self, mint: &str, amount: u64) {
+        if let Some(balance) = self.holdings.get_mut(mint) {
+            *balance = balance.saturating_sub(amount);
+            if *balance == 0 {
+                self.holdings.remove(mint);
+            }
+        }
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let horizon = now - chrono::Duration::seconds(MAX_WINDOW_SECS as i64);
+        while self
+            .pnl_history
+            .front()
+            .is_some_and(|(at, _)| *at < horizon)
+        {
+            self.pnl_history.pop_front();
+        }
+        while self.trades.front().is_some_and(|(at, _)| *at < horizon) {
+            self.trades.pop_front();
+        }
+    }
+
+    // Step 3: Drop from the highest realized PnL, all-time or within `window_secs` of `now`
+    pub fn drawdown(&self, window_secs: Option<u64>, now: DateTime<Utc>) -> (f64, f64) {
+        let peak = match window_secs {
+            Some(secs) => {
+                let since = now - chrono::Duration::seconds(secs as i64);
+                self.pnl_history
+                    .iter()
+                    .filter(|(at, _)| *at >= since)
+                    .map(|(_, pnl)| *pnl)
+                    .fold(self.realized_pnl, f64::max)
+            }
+            None => self.peak_pnl,
+        };
+        (peak, peak - self.realized_pnl)
+    }
+
+    // Step 4: Trade count and notional within `window_secs` of `now`
+    pub fn trade_activity(&self, window_secs: u64, now: DateTime<Utc>) -> (u32, u64) {
+        let since = now - chrono::Duration::seconds(window_secs as i64);
+        self.trades
+            .iter()
+            .filter(|(at, _)| *at >= since)
+            .fold((0, 0), |(count, notional), (_, trade)| {
+                (count + 1, notional.saturating_add(*trade))
+            })
+    }
+
+    // Step 5: Share of the wallet's USD value held in its largest position. Only holdings with
+    // a feed price and known decimals count towards the value.
+    pub fn concentration(&self, prices: &PriceHistoryStore) -> Option<Concentration> {
+        let values: Vec<(&String, f64)> = self
+            .holdings
+            .iter()
+            .filter_map(|(mint, amount)| {
+                let decimals = *self.decimals.get(mint)?;
+                let price_usd = prices.latest(mint)?;
+                let tokens = *amount as f64 / 10f64.powi(decimals as i32);
+                Some((mint, tokens * price_usd))
+            })
+            .collect();
+        let portfolio_value: f64 = values.iter().map(|(_, value)| value).sum();
+        if portfolio_value <= 0.0 {
+            return None;
+        }
+
+        let (mint, position_value) = values.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        Some(Concentration {
+            mint: mint.clone(),
+            position_value,
+            portfolio_value,
+            share: position_value / portfolio_value,
+        })
+    }
+
+    // Step 6: The wallet's LP position in `pool`, brought in line with its balance of the
+    // pool's LP mint. LP tokens gained since the last valuation are booked at their current
+    // share of the reserves, which becomes their hold basis; tokens given up shrink it.
+    fn lp_position(&mut self, pool: &str, reserves: &PoolReserves) -> Option<LpPosition> {
+        if reserves.lp_mint.is_empty() {
+            return self.lp_positions.get(pool).cloned();
+        }
+        let balance = self.holdings.get(&reserves.lp_mint).copied();
+        let Some(position) = self.lp_positions.get_mut(pool) else {
+            let balance = balance?;
+            let (amount_a, amount_b) = pool_share(balance, reserves)?;
+            let mut position = LpPosition {
+                lp_mint: Some(reserves.lp_mint.clone()),
+                ..LpPosition::default()
+            };
+            position.add(balance, amount_a as u64, amount_b as u64);
+            self.lp_positions.insert(pool.to_string(), position.clone());
+            return Some(position);
+        };
+
+        // A position only known from liquidity events keeps its count until a balance shows up
+        let balance = match balance {
+            Some(balance) => balance,
+            None if position.lp_mint.is_none() => return Some(position.clone()),
+            None => 0,
+        };
+        position.lp_mint = Some(reserves.lp_mint.clone());
+        if balance > position.lp_tokens {
+            let gained = balance - position.lp_tokens;
+            let (amount_a, amount_b) = pool_share(gained, reserves)?;
+            position.add(gained, amount_a as u64, amount_b as u64);
+        } else {
+            position.reduce_to(balance);
+        }
+
+        if position.lp_tokens == 0 {
+            self.lp_positions.remove(pool);
+            return None;
+        }
+        Some(position.clone())
+    }
+}
+
+impl LpPosition {
+    fn add(&mut self, lp_tokens: u64, amount_a: u64, amount_b: u64) {
+        self.lp_tokens = self.lp_tokens.saturating_add(lp_tokens);
+        self.deposited_a = self.deposited_a.saturating_add(amount_a);
+        self.deposited_b = self.deposited_b.saturating_add(amount_b);
+    }
+
+    // Deposits shrink with the share of LP tokens given up
+    fn reduce_to(&mut self, remaining: u64) {
+        if remaining >= self.lp_tokens {
+            return;
+        }
+        let kept = remaining as f64 / self.lp_tokens as f64;
+        self.deposited_a = (self.deposited_a as f64 * kept) as u64;
+        self.deposited_b = (self.deposited_b as f64 * kept) as u64;
+        self.lp_tokens = remaining;
+    }
+
+    // Step 7: Decompose the LP tokens into their share of the pool's vault reserves
+    pub fn underlying(&self, reserves: &PoolReserves) -> Option<(f64, f64)> {
+        pool_share(self.lp_tokens, reserves)
+    }
+
+    // Step 8: Impermanent loss at the pool's current price (token B per token A). Collected
+    // fees stay in the reserves, so they offset the loss.
+    pub fn impermanent_loss(&self, reserves: &PoolReserves) -> Option<ImpermanentLoss> {
+        if reserves.reserve_a == 0 {
+            return None;
+        }
+        let price = reserves.reserve_b as f64 / reserves.reserve_a as f64;
+        let (underlying_a, underlying_b) = self.underlying(reserves)?;
+        let lp_value = underlying_a * price + underlying_b;
+        let hold_value = self.deposited_a as f64 * price + self.deposited_b as f64;
+        if hold_value <= 0.0 {
+            return None;
+        }
+
+        Some(ImpermanentLoss {
+            underlying_a,
+            underlying_b,
+            lp_value,
+            hold_value,
+            il_pct: (lp_value / hold_value - 1.0) * 100.0,
+        })
+    }
+}
+
+// Amounts of each reserve that `lp_tokens` of the LP supply stand for
+fn pool_share(lp_tokens: u64, reserves: &PoolReserves) -> Option<(f64, f64)> {
+    if reserves.total_liquidity == 0 {
+        return None;
+    }
+    let share = lp_tokens as f64 / reserves.total_liquidity as f64;
+    Some((
+        reserves.reserve_a as f64 * share,
+        reserves.reserve_b as f64 * share,
+    ))
+}
+
+fn push_sample<T>(samples: &mut VecDeque<T>, sample: T) {
+    if samples.len() >= MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+// Step 9: Bounded map of wallet states; the least recently active wallets are evicted first
+pub struct WalletStateStore {
+    wallets: Mutex<HashMap<String, WalletState>>,
+    max_wallets: usize,
+}
+
+impl WalletStateStore {
+    pub fn new(max_wallets: usize) -> Self {
+        Self {
+            wallets: Mutex::new(HashMap::new()),
+            max_wallets: max_wallets.max(1),
+        }
+    }
+
+    // Step 10: Apply `event` to its wallet's state and read the result under the same lock, so
+    // rules see the state exactly as of this event
+    pub fn apply<R>(&self, event: &PortfolioEvent, read: impl FnOnce(&WalletState) -> R) -> R {
+        let mut wallets = self.wallets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !wallets.contains_key(event.wallet()) {
+            let mut state = WalletState::default();
+            if !state.apply(event) {
+                return read(&state); // Nothing to track for this event
+            }
+            if wallets.len() >= self.max_wallets {
+                evict_idle(&mut wallets, self.max_wallets);
+            }
+            let state = wallets.entry(event.wallet().to_string()).or_insert(state);
+            return read(state);
+        }
+
+        let state = wallets
+            .get_mut(event.wallet())
+            .expect("wallet state checked above");
+        state.apply(event);
+        read(state)
+    }
+
+    // Wallets with LP tokens in `pool`, valued against its latest reserves, for fanning out
+    // pool alerts
+    pub fn liquidity_providers(
+        &self,
+        pool: &str,
+        reserves: &PoolReserves,
+    ) -> Vec<(String, LpPosition)> {
+        let mut wallets = self.wallets.lock().unwrap_or_else(|e| e.into_inner());
+        wallets
+            .iter_mut()
+            .filter_map(|(wallet, state)| {
+                let position = state.lp_position(pool, reserves)?;
+                Some((wallet.clone(), position))
+            })
+            .collect()
+    }
+
+    // Wallets currently holding `mint` or a position in it, for fanning out market-wide alerts.
+    // Only wallets this store knows about are found: those that emitted an event or were seeded
+    // by GET /api/portfolio/:wallet. Other holders, and wallets evicted past `max_wallets`, get
+    // no market alerts until they show up again.
+    pub fn holders(&self, mint: &str) -> Vec<String> {
+        let wallets = self.wallets.lock().unwrap_or_else(|e| e.into_inner());
+        wallets
+            .iter()
+            .filter(|(_, state)| {
+                state.holdings.contains_key(mint) || state.positions.contains(mint)
+            })
+            .map(|(wallet, _)| wallet.clone())
+            .collect()
+    }
+
+    // Replace a wallet's holdings with its on-chain token balances (mint, base units,
+    // decimals), so tokens it held before tracking started count too
+    pub fn seed_balances(
+        &self,
+        wallet: &str,
+        balances: impl IntoIterator<Item = (String, u64, u8)>,
+    ) {
+        let mut wallets = self.wallets.lock().unwrap_or_else(|e| e.into_inner());
+        if !wallets.contains_key(wallet) && wallets.len() >= self.max_wallets {
+            evict_idle(&mut wallets, self.max_wallets);
+        }
+        let state = wallets
+            .entry(wallet.to_string())
+            .or_insert_with(|| WalletState {
+                last_seen: Utc::now(),
+                ..WalletState::default()
+            });
+        state.holdings.clear();
+        for (mint, amount, decimals) in balances {
+            state.decimals.insert(mint.clone(), decimals);
+            if amount > 0 {
+                state.holdings.insert(mint, amount);
+            }
+        }
+    }
+
+    // Step 11: Copy the states under the lock, then serialize and write them off the async
+    // runtime; write-then-rename so a crash never leaves a truncated snapshot behind
+    pub async fn snapshot(&self, path: &str) -> Result<usize, ThreadSafeError> {
+        let wallets = self
+            .wallets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let count = wallets.len();
+        let path = path.to_string();
+        tokio::task::spawn_blocking(move || write_atomic(&path, &wallets)).await??;
+        Ok(count)
+    }
+
+    // Step 12: Load a snapshot written by `snapshot`; a missing file leaves the store empty
+    pub fn restore(&self, path: &str) -> std::io::Result<usize> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut restored: HashMap<String, WalletState> = serde_json::from_str(&contents)?;
+        if restored.len() > self.max_wallets {
+            evict_idle(&mut restored, self.max_wallets + 1);
+        }
+
+        let count = restored.len();
+        *self.wallets.lock().unwrap_or_else(|e| e.into_inner()) = restored;
+        Ok(count)
+    }
+}
+
+// Drop the least recently active wallets until there is room for one more
+fn evict_idle(wallets: &mut HashMap<String, WalletState>, max_wallets: usize) {
+    let batch = (max_wallets / EVICT_FRACTION).max(1);
+    let excess = (wallets.len() + 1).saturating_sub(max_wallets);
+    let mut by_activity: Vec<(DateTime<Utc>, String)> = wallets
+        .iter()
+        .map(|(wallet, state)| (state.last_seen, wallet.clone()))
+        .collect();
+    by_activity.sort_unstable();
+
+    for (_, wallet) in by_activity.into_iter().take(excess.max(batch)) {
+        wallets.remove(&wallet);
+    }
+}
+
+// Step 13: Recent USD prices of one mint, newest last
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory {
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+}
+
+impl PriceHistory {
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().map(|(_, price)| *price)
+    }
+
+    // Largest move of the latest price against any price within `window_secs` of `now`, as
+    // (reference price, signed percentage)
+    pub fn price_move(&self, window_secs: u64, now: DateTime<Utc>) -> Option<(f64, f64)> {
+        let latest = self.latest()?;
+        let since = now - chrono::Duration::seconds(window_secs as i64);
+        self.samples
+            .iter()
+            .filter(|(at, price)| *at >= since && *price > 0.0)
+            .map(|(_, reference)| (*reference, (latest / reference - 1.0) * 100.0))
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+    }
+}
+
+// Step 14: Price histories read by the market-wide rules
+pub struct PriceHistoryStore {
+    mints: Mutex<HashMap<String, PriceHistory>>,
+}
+
+impl PriceHistoryStore {
+    pub fn new() -> Self {
+        Self {
+            mints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Record a price and read the mint's history under the same lock
+    pub fn record<R>(
+        &self,
+        mint: &str,
+        price_usd: f64,
+        at: DateTime<Utc>,
+        read: impl FnOnce(&PriceHistory) -> R,
+    ) -> R {
+        let mut mints = self.mints.lock().unwrap_or_else(|e| e.into_inner());
+        if !mints.contains_key(mint) && mints.len() >= MAX_PRICED_MINTS {
+            let mut history = PriceHistory::default();
+            history.samples.push_back((at, price_usd));
+            return read(&history); // Judged on this price alone
+        }
+
+        let history = mints.entry(mint.to_string()).or_default();
+        push_sample(&mut history.samples, (at, price_usd));
+        let horizon = at - chrono::Duration::seconds(MAX_WINDOW_SECS as i64);
+        while history.samples.front().is_some_and(|(at, _)| *at < horizon) {
+            history.samples.pop_front();
+        }
+        read(history)
+    }
+
+    // Latest USD price of `mint`, if the feed has reported one
+    pub fn latest(&self, mint: &str) -> Option<f64> {
+        let mints = self.mints.lock().unwrap_or_else(|e| e.into_inner());
+        mints.get(mint)?.latest()
+    }
+}
+
+impl Default for PriceHistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Vault balances and LP supply of an amm-pool pool, as carried by `PoolReservesChanged`
+#[derive(Debug, Clone, Default)]
+pub struct PoolReserves {
+    pub token_a: String,
+    pub token_b: String,
+    pub lp_mint: String,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub total_liquidity: u64,
+}
+
+// Step 15: Latest reserves of one pool and their recent history, newest last
+#[derive(Debug, Clone, Default)]
+pub struct PoolHistory {
+    pub latest: PoolReserves,
+    samples: VecDeque<(DateTime<Utc>, u64, u64)>,
+}
+
+impl PoolHistory {
+    // Largest drop of either reserve from its peak within `window_secs` of `now`, as
+    // (mint that drained, percentage)
+    pub fn reserve_drop(&self, window_secs: u64, now: DateTime<Utc>) -> Option<(String, f64)> {
+        let since = now - chrono::Duration::seconds(window_secs as i64);
+        let (peak_a, peak_b) = self
+            .samples
+            .iter()
+            .filter(|(at, _, _)| *at >= since)
+            .fold((0, 0), |(a, b), (_, reserve_a, reserve_b)| {
+                (a.max(*reserve_a), b.max(*reserve_b))
+            });
+
+        let drop = |peak: u64, current: u64| {
+            (peak > 0).then(|| (1.0 - current as f64 / peak as f64) * 100.0)
+        };
+        let drop_a = drop(peak_a, self.latest.reserve_a)?;
+        let drop_b = drop(peak_b, self.latest.reserve_b)?;
+        Some(if drop_a >= drop_b {
+            (self.latest.token_a.clone(), drop_a)
+        } else {
+            (self.latest.token_b.clone(), drop_b)
+        })
+    }
+}
+
+// Step 16: Reserve histories read by the LP health rules
+pub struct PoolHistoryStore {
+    pools: Mutex<HashMap<String, PoolHistory>>,
+}
+
+impl PoolHistoryStore {
+    pub fn new() -> Self {
+        Self {
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Record new reserves and read the pool's history under the same lock
+    pub fn record<R>(
+        &self,
+        pool: &str,
+        reserves: PoolReserves,
+        at: DateTime<Utc>,
+        read: impl FnOnce(&PoolHistory) -> R,
+    ) -> R {
+        let mut pools = self.pools.lock().unwrap_or_else(|e| e.into_inner());
+        let sample = (at, reserves.reserve_a, reserves.reserve_b);
+        if !pools.contains_key(pool) && pools.len() >= MAX_TRACKED_POOLS {
+            let mut history = PoolHistory {
+                latest: reserves,
+                samples: VecDeque::new(),
+            };
+            history.samples.push_back(sample);
+            return read(&history); // Judged on these reserves alone
+        }
+
+        let history = pools.entry(pool.to_string()).or_default();
+        history.latest = reserves;
+        push_sample(&mut history.samples, sample);
+        let horizon = at - chrono::Duration::seconds(MAX_WINDOW_SECS as i64);
+        while history
+            .samples
+            .front()
+            .is_some_and(|(at, _, _)| *at < horizon)
+        {
+            history.samples.pop_front();
+        }
+        read(history)
+    }
+}
+
+impl Default for PoolHistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_utils::create_position_update_event;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn pnl(wallet: &str, delta: f64, secs: i64) -> PortfolioEvent {
+        let mut event = create_position_update_event(wallet.to_string(), "mint".to_string(), delta);
+        event.set_timestamp(at(secs));
+        event
+    }
+
+    fn trade(notional: u64, secs: i64) -> PortfolioEvent {
+        PortfolioEvent::TradeRecorded {
+            wallet: "wallet".to_string(),
+            mint: "mint".to_string(),
+            amount: 1,
+            notional,
+            timestamp: at(secs),
+            source: None,
+            reversal: false,
+        }
+    }
+
+    // Apply `events` in order and read the wallet's state after the last one
+    fn state_after(store: &WalletStateStore, events: &[PortfolioEvent]) -> WalletState {
+        let (last, rest) = events.split_last().expect("at least one event");
+        for event in rest {
+            store.apply(event, |_| ());
+        }
+        store.apply(last, WalletState::clone)
+    }
+
+    // Fresh snapshot file under the temp dir
+    fn snapshot_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("rule_state_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn reserves(reserve_a: u64, reserve_b: u64, total_liquidity: u64) -> PoolReserves {
+        PoolReserves {
+            token_a: "mint_a".to_string(),
+            token_b: "mint_b".to_string(),
+            lp_mint: "lp_mint".to_string(),
+            reserve_a,
+            reserve_b,
+            total_liquidity,
+        }
+    }
+
+    fn transfer(mint: &str, amount: u64, direction: TransferDirection) -> PortfolioEvent {
+        PortfolioEvent::Transfer {
+            wallet: "wallet".to_string(),
+            mint: mint.to_string(),
+            amount,
+            direction,
+            counterparty: None,
+            timestamp: Utc::now(),
+            source: None,
+            reversal: false,
+        }
+    }
+
+    #[test]
+    fn drawdown_is_measured_from_the_all_time_or_windowed_peak() {
+        let store = WalletStateStore::new(10);
+        let state = state_after(
+            &store,
+            &[
+                pnl("wallet", 10.0, 0),
+                pnl("wallet", 5.0, 60),
+                pnl("wallet", -12.0, 3_600),
+            ],
+        );
+
+        assert_eq!(state.drawdown(None, at(3_600)), (15.0, 12.0));
+        // Within the last 10 minutes the latest update is its own peak
+        assert_eq!(state.drawdown(Some(600), at(3_600)), (3.0, 0.0));
+        assert_eq!(state.losing_streak, 1);
+    }
+
+    #[test]
+    fn trade_activity_counts_only_the_window() {
+        let store = WalletStateStore::new(10);
+        let state = state_after(&store, &[trade(10, 0), trade(20, 100), trade(30, 200)]);
+
+        assert_eq!(state.trade_activity(150, at(200)), (2, 50));
+        assert_eq!(state.trade_activity(MAX_WINDOW_SECS, at(200)), (3, 60));
+    }
+
+    #[test]
+    fn reversals_take_back_pnl_and_trades() {
+        let store = WalletStateStore::new(10);
+        let peak = pnl("wallet", 5.0, 60);
+        let reverted = trade(20, 120);
+        state_after(
+            &store,
+            &[pnl("wallet", 10.0, 0), peak.clone(), reverted.clone()],
+        );
+
+        let state = state_after(
+            &store,
+            &[
+                peak.compensating_event().unwrap(),
+                reverted.compensating_event().unwrap(),
+            ],
+        );
+        assert_eq!(state.realized_pnl, 10.0);
+        // The reverted update set the peak, so it is recomputed without it
+        assert_eq!(state.drawdown(None, at(120)), (10.0, 0.0));
+        assert_eq!(state.trade_activity(MAX_WINDOW_SECS, at(120)), (0, 0));
+    }
+
+    #[test]
+    fn full_store_evicts_the_least_recently_active_wallet() {
+        let store = WalletStateStore::new(2);
+        for (wallet, secs) in [("old", 0), ("recent", 60), ("new", 120)] {
+            store.apply(&pnl(wallet, 1.0, secs), |_| ());
+            store.seed_balances(wallet, [("mint".to_string(), 1, 0)]);
+        }
+
+        let mut holders = store.holders("mint");
+        holders.sort();
+        assert_eq!(holders, vec!["new", "recent"]);
+    }
+
+    #[tokio::test]
+    async fn snapshots_restore_wallet_state() {
+        let path = snapshot_path("restore");
+        let store = WalletStateStore::new(10);
+        state_after(
+            &store,
+            &[
+                pnl("wallet", 10.0, 0),
+                pnl("wallet", -4.0, 60),
+                trade(25, 60),
+            ],
+        );
+        assert_eq!(store.snapshot(&path).await.unwrap(), 1);
+
+        let restored = WalletStateStore::new(10);
+        assert_eq!(restored.restore(&path).unwrap(), 1);
+        let state = state_after(&restored, &[trade(5, 120)]);
+        assert_eq!(state.drawdown(None, at(120)), (10.0, 4.0));
+        assert_eq!(state.trade_activity(MAX_WINDOW_SECS, at(120)), (2, 30));
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(WalletStateStore::new(10).restore(&path).unwrap(), 0);
+    }
+
+    #[test]
+    fn concentration_values_holdings_at_feed_prices() {
+        let store = WalletStateStore::new(10);
+        store.seed_balances(
+            "wallet",
+            [
+                ("sol".to_string(), 2_000_000_000, 9),
+                ("usdc".to_string(), 100_000_000, 6),
+                ("unpriced".to_string(), 1_000_000, 0),
+            ],
+        );
+        let prices = PriceHistoryStore::new();
+        prices.record("sol", 150.0, at(0), |_| ());
+        prices.record("usdc", 1.0, at(0), |_| ());
+
+        let concentration = store
+            .apply(&pnl("wallet", 1.0, 0), |state| state.concentration(&prices))
+            .unwrap();
+        assert_eq!(concentration.mint, "sol");
+        assert_eq!(concentration.position_value, 300.0);
+        assert_eq!(concentration.portfolio_value, 400.0);
+        assert_eq!(concentration.share, 0.75);
+    }
+
+    #[test]
+    fn underlying_is_the_share_of_each_reserve() {
+        let position = LpPosition {
+            lp_tokens: 250,
+            ..LpPosition::default()
+        };
+        let (a, b) = position.underlying(&reserves(4_000, 8_000, 1_000)).unwrap();
+        assert_eq!((a, b), (1_000.0, 2_000.0));
+        assert!(position.underlying(&reserves(4_000, 8_000, 0)).is_none());
+    }
+
+    #[test]
+    fn impermanent_loss_matches_constant_product_after_price_move() {
+        // Deposited at 1 B per A; the price quadruples along x * y = k
+        let position = LpPosition {
+            lp_tokens: 100,
+            deposited_a: 1_000,
+            deposited_b: 1_000,
+            lp_mint: None,
+        };
+        let il = position
+            .impermanent_loss(&reserves(500, 2_000, 100))
+            .unwrap();
+        assert_eq!(il.lp_value, 4_000.0);
+        assert_eq!(il.hold_value, 5_000.0);
+        assert!((il.il_pct + 20.0).abs() < 1e-9);
+
+        let unchanged = position
+            .impermanent_loss(&reserves(1_000, 1_000, 100))
+            .unwrap();
+        assert!(unchanged.il_pct.abs() < 1e-9);
+    }
+
+    #[test]
+    fn reserve_drop_reports_the_reserve_that_drained_most() {
+        let history = PoolHistoryStore::new();
+        let start = Utc::now();
+        history.record("pool", reserves(1_000, 1_000, 100), start, |_| ());
+        let later = start + chrono::Duration::seconds(60);
+        let (mint, drop) = history.record("pool", reserves(900, 400, 100), later, |history| {
+            history.reserve_drop(300, later).unwrap()
+        });
+        assert_eq!(mint, "mint_b");
+        assert!((drop - 60.0).abs() < 1e-9);
+
+        // Peaks outside the window no longer count
+        let much_later = later + chrono::Duration::seconds(600);
+        let drop = history.record("pool", reserves(900, 400, 100), much_later, |history| {
+            history.reserve_drop(300, much_later).unwrap().1
+        });
+        assert_eq!(drop, 0.0);
+    }
+
+    #[test]
+    fn lp_positions_follow_lp_mint_balances() {
+        let store = WalletStateStore::new(10);
+        store.seed_balances("wallet", [("lp_mint".to_string(), 100, 6)]);
+
+        // Seeded tokens are booked at their current share of the reserves
+        let pool = reserves(1_000, 2_000, 1_000);
+        let providers = store.liquidity_providers("pool", &pool);
+        assert_eq!(providers.len(), 1);
+        let position = &providers[0].1;
+        assert_eq!(position.lp_tokens, 100);
+        assert_eq!((position.deposited_a, position.deposited_b), (100, 200));
+
+        // Sending half the tokens away halves the basis
+        store.apply(
+            &transfer("lp_mint", 50, TransferDirection::Outgoing),
+            |_| (),
+        );
+        let position = store.liquidity_providers("pool", &pool).remove(0).1;
+        assert_eq!(position.lp_tokens, 50);
+        assert_eq!((position.deposited_a, position.deposited_b), (50, 100));
+
+        // Sending the rest closes the position
+        store.apply(
+            &transfer("lp_mint", 50, TransferDirection::Outgoing),
+            |_| (),
+        );
+        assert!(store.liquidity_providers("pool", &pool).is_empty());
+    }
+}
@@ -1,141 +1,475 @@
 // backend/src/pipeline/rules_engine.rs
-use std::collections::HashMap;
+use std::sync::Arc;
 
+use tokio::sync::RwLock;
+
+use crate::config::RiskConfig;
+use crate::pipeline::alert_dedup::{AlertDecision, AlertDeduplicator};
+use crate::pipeline::alert_store::AlertStore;
+use crate::pipeline::rule_set::{RuleDefinition, RuleError, RuleMatch, RuleSet};
+use crate::pipeline::rule_state::{
+    PoolHistoryStore, PoolReserves, PriceHistoryStore, WalletStateStore,
+};
+use crate::services::metrics::MetricsService;
 use crate::{models::event::PortfolioEvent, models::risk_alert::RiskAlert, ws::hub::WsHub};
 
-// Step 1: Rules engine for real-time risk detection
+// Step 1: Rules engine for real-time risk detection; alerts go out on the app's hub
 pub struct RulesEngine {
-    rules: HashMap<String, RiskRule>,
+    rules: RwLock<Arc<RuleSet>>, // Swapped as a whole on reload
+    deduplicator: AlertDeduplicator,
+    alerts: Option<AlertStore>, // Where raised alerts are kept for the lifecycle API
+    state: WalletStateStore,    // Per-wallet windows read by the stateful conditions
+    prices: PriceHistoryStore,  // Per-mint prices read by the market-wide conditions
+    pools: PoolHistoryStore,    // Per-pool reserves read by the LP health conditions
+    state_path: Option<String>,
+    snapshot_interval: std::time::Duration,
+    event_time: bool, // Stamp alerts with their event's time rather than the wall clock
     ws_hub: WsHub,
-}
-
-// Step 2: Individual risk rule definition
-#[derive(Clone)]
-struct RiskRule {
-    pub name: String,
-    pub condition: RuleCondition,
-    pub severity: crate::models::risk_alert::AlertSeverity,
-    pub message_template: String,
-}
-
-// Step 3: Rule condition types
-#[derive(Clone)]
-enum RuleCondition {
-    PositionConcentration { threshold: f64 },
-    LargeTrade { min_amount_usd: f64 },
+    metrics: MetricsService,
 }
 
 impl RulesEngine {
-    pub fn new() -> Self {
-        let mut rules = HashMap::new();
-
-        // Step 4: Define risk rules
-        rules.insert(
-            "high_concentration".to_string(),
-            RiskRule {
-                name: "High Position Concentration".to_string(),
-                condition: RuleCondition::PositionConcentration { threshold: 0.3 },
-                severity: crate::models::risk_alert::AlertSeverity::High,
-                message_template: "Position {} represents {:.1}% of portfolio".to_string(),
-            },
-        );
+    // Step 2: Start with the built-in rules until a rules file is loaded
+    pub fn new(config: &RiskConfig, ws_hub: WsHub, metrics: MetricsService) -> Self {
+        Self {
+            rules: RwLock::new(Arc::new(RuleSet::default())),
+            deduplicator: AlertDeduplicator::new(config),
+            alerts: None,
+            state: WalletStateStore::new(config.state_max_wallets),
+            prices: PriceHistoryStore::new(),
+            pools: PoolHistoryStore::new(),
+            state_path: config.state_path.clone(),
+            snapshot_interval: config.state_snapshot_interval(),
+            event_time: false,
+            ws_hub,
+            metrics,
+        }
+    }
 
-        rules.insert(
-            "large_trade".to_string(),
-            RiskRule {
-                name: "Large Trade Detected".to_string(),
-                condition: RuleCondition::LargeTrade {
-                    min_amount_usd: 10000.0,
-                },
-                severity: crate::models::risk_alert::AlertSeverity::Medium,
-                message_template: "Large trade of ${:.0} detected".to_string(),
-            },
-        );
+    pub fn with_rule_set(self, rule_set: RuleSet) -> Self {
+        Self {
+            rules: RwLock::new(Arc::new(rule_set)),
+            ..self
+        }
+    }
+
+    pub fn with_alert_store(self, alerts: AlertStore) -> Self {
+        Self {
+            alerts: Some(alerts),
+            ..self
+        }
+    }
 
+    // For recorded events: cooldowns then follow the recording instead of the replay speed
+    pub fn with_event_time(self) -> Self {
         Self {
-            rules,
-            ws_hub: WsHub::new(),
+            event_time: true,
+            ..self
         }
     }
 
-    // Step 5: Process events through rules engine
+    // Step 3: Currently active rules
+    pub async fn rule_set(&self) -> Arc<RuleSet> {
+        self.rules.read().await.clone()
+    }
+
+    // Step 4: Load `path` and swap it in; the active rules are kept if the file is invalid
+    pub async fn reload(&self, path: &str) -> Result<Arc<RuleSet>, RuleError> {
+        let rule_set = Arc::new(RuleSet::load(path)?);
+        *self.rules.write().await = rule_set.clone();
+        tracing::info!(
+            "📏 Loaded {} risk rule(s) from {}",
+            rule_set.rules.len(),
+            path
+        );
+        Ok(rule_set)
+    }
+
+    // Seed a wallet's holdings from its on-chain token balances (mint, base units, decimals)
+    pub fn seed_balances(
+        &self,
+        wallet: &str,
+        balances: impl IntoIterator<Item = (String, u64, u8)>,
+    ) {
+        self.state.seed_balances(wallet, balances);
+    }
+
+    // Cooldown and confidence filter applied to every alert
+    pub fn deduplicator(&self) -> &AlertDeduplicator {
+        &self.deduplicator
+    }
+
+    // Step 5: Process events through rules engine; repeats within the cooldown are dropped
     pub async fn process_event(&self, event: &PortfolioEvent) -> Vec<RiskAlert> {
-        let mut alerts = Vec::new();
+        let rule_set = self.rule_set().await;
+        match event {
+            PortfolioEvent::PriceUpdated { .. } => {
+                return self.process_price(event, &rule_set).await
+            }
+            PortfolioEvent::PoolReservesChanged { .. } => {
+                return self.process_pool(event, &rule_set).await
+            }
+            _ => {}
+        }
+
+        // Update the wallet's windows and evaluate every rule against the same state
+        let matches: Vec<(&RuleDefinition, RuleMatch)> = self.state.apply(event, |state| {
+            rule_set
+                .enabled()
+                .filter_map(|rule| {
+                    Some((rule, rule.condition.evaluate(event, state, &self.prices)?))
+                })
+                .collect()
+        });
 
-        for (rule_id, rule) in &self.rules {
-            if self.evaluate_condition(rule, event).await {
-                let alert = self.create_alert(rule, event).await;
-                alerts.push(alert.clone());
-
-                // Step 6: Send real-time alert via WebSocket
-                let ws_message = crate::ws::hub::WsMessage {
-                    message_type: "risk_alert".to_string(),
-                    payload: serde_json::json!({
-                        "wallet": alert.wallet,
-                        "severity": format!("{:?}", alert.severity),
-                        "message": alert.message,
-                    }),
-                    timestamp: chrono::Utc::now(),
-                };
-
-                let _ = self.ws_hub.broadcast(ws_message);
+        let mut alerts = Vec::new();
+        for (rule, matched) in matches {
+            let alert = self.create_alert(rule, event, event.wallet(), &matched);
+            if let Some(alert) = self.raise(rule, alert, None, matched.magnitude).await {
+                alerts.push(alert);
             }
         }
 
         alerts
     }
 
-    // Step 7: Evaluate individual rule condition
-    async fn evaluate_condition(&self, rule: &RiskRule, event: &PortfolioEvent) -> bool {
-        match &rule.condition {
-            RuleCondition::PositionConcentration { threshold } => {
-                self.check_position_concentration(event, *threshold).await
-            }
-            RuleCondition::LargeTrade { min_amount_usd } => {
-                self.check_large_trade(event, *min_amount_usd).await
+    // Step 6: Market-wide rules judge the mint's price history and alert every wallet holding it
+    async fn process_price(&self, event: &PortfolioEvent, rule_set: &RuleSet) -> Vec<RiskAlert> {
+        let PortfolioEvent::PriceUpdated {
+            mint,
+            price_usd,
+            timestamp,
+        } = event
+        else {
+            return Vec::new();
+        };
+
+        let matches: Vec<(&RuleDefinition, RuleMatch)> =
+            self.prices.record(mint, *price_usd, *timestamp, |history| {
+                rule_set
+                    .enabled()
+                    .filter(|rule| rule.condition.is_market())
+                    .filter_map(|rule| {
+                        Some((rule, rule.condition.evaluate_market(event, history)?))
+                    })
+                    .collect()
+            });
+        if matches.is_empty() {
+            return Vec::new();
+        }
+
+        let holders = self.state.holders(mint);
+        tracing::info!(
+            "📉 {} market rule(s) matched {} at ${}, fanning out to {} holder(s)",
+            matches.len(),
+            mint,
+            price_usd,
+            holders.len()
+        );
+
+        let mut alerts = Vec::new();
+        for (rule, matched) in &matches {
+            for holder in &holders {
+                let alert = self.create_alert(rule, event, holder, matched);
+                if let Some(alert) = self.raise(rule, alert, Some(mint), matched.magnitude).await {
+                    alerts.push(alert);
+                }
             }
         }
+
+        alerts
     }
 
-    // Step 8: Check position concentration rule
-    async fn check_position_concentration(&self, event: &PortfolioEvent, threshold: f64) -> bool {
-        if let PortfolioEvent::PositionUpdate { pnl_delta, .. } = event {
-            // Simplified check - in production, would check actual portfolio concentration
-            pnl_delta.abs() > threshold * 1000.0 // Mock logic
-        } else {
-            false
+    // Step 7: LP health rules judge the pool's reserves for every liquidity provider in it
+    async fn process_pool(&self, event: &PortfolioEvent, rule_set: &RuleSet) -> Vec<RiskAlert> {
+        let PortfolioEvent::PoolReservesChanged {
+            pool,
+            token_a,
+            token_b,
+            lp_mint,
+            reserve_a,
+            reserve_b,
+            total_liquidity,
+            timestamp,
+            ..
+        } = event
+        else {
+            return Vec::new();
+        };
+
+        let reserves = PoolReserves {
+            token_a: token_a.clone(),
+            token_b: token_b.clone(),
+            lp_mint: lp_mint.clone(),
+            reserve_a: *reserve_a,
+            reserve_b: *reserve_b,
+            total_liquidity: *total_liquidity,
+        };
+        let providers = self.state.liquidity_providers(pool, &reserves);
+        let matches: Vec<(&RuleDefinition, &str, RuleMatch)> =
+            self.pools.record(pool, reserves, *timestamp, |history| {
+                rule_set
+                    .enabled()
+                    .filter(|rule| rule.condition.is_pool())
+                    .flat_map(|rule| {
+                        providers.iter().filter_map(move |(wallet, position)| {
+                            let matched = rule.condition.evaluate_pool(event, history, position)?;
+                            Some((rule, wallet.as_str(), matched))
+                        })
+                    })
+                    .collect()
+            });
+
+        let mut alerts = Vec::new();
+        for (rule, wallet, matched) in matches {
+            let alert = self.create_alert(rule, event, wallet, &matched);
+            if let Some(alert) = self.raise(rule, alert, Some(pool), matched.magnitude).await {
+                alerts.push(alert);
+            }
         }
+
+        alerts
     }
 
-    // Step 9: Check large trade rule
-    async fn check_large_trade(&self, event: &PortfolioEvent, min_amount: f64) -> bool {
-        if let PortfolioEvent::SwapExecuted { amount, .. } = event {
-            let amount_usd = *amount as f64 * 0.01; // Mock conversion
-            amount_usd >= min_amount
-        } else {
-            false
+    // Step 8: Drop alerts muted by a snooze or the cooldown; broadcast and store the rest.
+    // `subject` is the mint or pool a market or LP rule judged.
+    async fn raise(
+        &self,
+        rule: &RuleDefinition,
+        alert: RiskAlert,
+        subject: Option<&str>,
+        magnitude: f64,
+    ) -> Option<RiskAlert> {
+        if let Some(alerts) = &self.alerts {
+            if alerts.is_snoozed(&alert.wallet, &rule.id).await {
+                self.metrics.record_alert_suppressed("snoozed", 1).await;
+                return None;
+            }
         }
+        let alert = match self.deduplicator.admit(alert, &rule.id, subject, magnitude) {
+            AlertDecision::Emit(alert) => alert,
+            AlertDecision::Escalate(alert) => {
+                self.metrics.record_alert_escalated(&rule.id).await;
+                alert
+            }
+            AlertDecision::Suppress => {
+                self.metrics.record_alert_suppressed("cooldown", 1).await;
+                return None;
+            }
+        };
+        self.metrics
+            .record_risk_alert(&format!("{:?}", alert.severity).to_lowercase())
+            .await;
+
+        // Send real-time alert via WebSocket
+        let ws_message = crate::ws::hub::WsMessage {
+            message_type: "risk_alert".to_string(),
+            payload: serde_json::json!({
+                "id": alert.id,
+                "wallet": alert.wallet,
+                "severity": format!("{:?}", alert.severity),
+                "message": alert.message,
+                "rule_id": rule.id,
+            }),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let _ = self.ws_hub.broadcast(ws_message);
+        if let Some(alerts) = &self.alerts {
+            alerts.record(alert.clone()).await;
+        }
+        Some(alert)
     }
 
-    // Step 10: Create risk alert from triggered rule
-    async fn create_alert(&self, rule: &RiskRule, event: &PortfolioEvent) -> RiskAlert {
-        let wallet = event.wallet().to_string();
-        let message = rule.message_template.clone();
+    // Step 9: Create risk alert for `wallet` from triggered rule
+    fn create_alert(
+        &self,
+        rule: &RuleDefinition,
+        event: &PortfolioEvent,
+        wallet: &str,
+        matched: &RuleMatch,
+    ) -> RiskAlert {
+        let mut values = matched.clone();
+        values
+            .values
+            .insert("wallet".to_string(), serde_json::json!(wallet));
 
-        RiskAlert::new(
-            wallet,
+        let mut alert = RiskAlert::new(
+            wallet.to_string(),
             rule.severity.clone(),
-            message,
+            rule.render_message(event, &values),
             Some(serde_json::json!({
-                "rule_id": rule.name,
+                "rule_id": rule.id,
+                "rule_name": rule.name,
                 "triggered_at": chrono::Utc::now().to_rfc3339(),
             })),
-        )
+        );
+        if self.event_time {
+            alert.timestamp = *event.timestamp();
+        }
+        alert
     }
 
-    // Step 11: Process alerts in background
+    // Step 10: Restore wallet windows saved by a previous run
+    pub fn restore_state(&self) -> std::io::Result<usize> {
+        match &self.state_path {
+            Some(path) => self.state.restore(path),
+            None => Ok(0),
+        }
+    }
+
+    // Step 11: Save wallet windows so they survive a restart
+    pub async fn snapshot_state(&self) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+        match self.state.snapshot(path).await {
+            Ok(count) => tracing::debug!("💾 Saved rule state for {} wallet(s)", count),
+            Err(e) => tracing::error!("❌ Failed to save rule state to {}: {}", path, e),
+        }
+    }
+
+    // Step 12: Snapshot periodically in the background
+    pub fn spawn_state_snapshots(self: &Arc<Self>) {
+        if self.state_path.is_none() {
+            return;
+        }
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(engine.snapshot_interval);
+            interval.tick().await; // Nothing to save yet
+            loop {
+                interval.tick().await;
+                engine.snapshot_state().await;
+            }
+        });
+    }
+
+    // Step 13: Process alerts in background
     pub async fn process_alerts(self) {
         tracing::info!("🚨 Starting rules engine alert processing");
         // Background processing logic
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::risk_alert::AlertSeverity;
+    use crate::pipeline::rule_set::RuleCondition;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const USDT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn market_rule(id: &str, condition: RuleCondition) -> RuleDefinition {
+        RuleDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            enabled: true,
+            severity: AlertSeverity::High,
+            message: "{mint} moved".to_string(),
+            condition,
+        }
+    }
+
+    fn engine() -> RulesEngine {
+        let config = RiskConfig {
+            cooldown_minutes: 60,
+            alert_retention_days: 30,
+            alerts_path: None,
+            alert_max_entries: 100,
+            min_confidence: 0.0,
+            rules_path: None,
+            state_path: None,
+            state_snapshot_secs: 60,
+            state_max_wallets: 100,
+        };
+        let rules = RuleSet {
+            source: None,
+            rules: vec![
+                market_rule(
+                    "depeg",
+                    RuleCondition::StablecoinDepeg {
+                        max_deviation_bps: 50,
+                        mints: Vec::new(),
+                        peg_usd: 1.0,
+                    },
+                ),
+                market_rule(
+                    "shock",
+                    RuleCondition::PriceShock {
+                        max_move_pct: 10.0,
+                        window_secs: 3_600,
+                        mints: Vec::new(),
+                    },
+                ),
+            ],
+        };
+        RulesEngine::new(&config, WsHub::new(), MetricsService::new()).with_rule_set(rules)
+    }
+
+    fn price(mint: &str, price_usd: f64, secs_ago: i64) -> PortfolioEvent {
+        PortfolioEvent::PriceUpdated {
+            mint: mint.to_string(),
+            price_usd,
+            timestamp: chrono::Utc::now() - chrono::Duration::seconds(secs_ago),
+        }
+    }
+
+    // (wallet, rule) of every alert, sorted
+    fn raised(alerts: &[RiskAlert]) -> Vec<(String, String)> {
+        let mut raised: Vec<(String, String)> = alerts
+            .iter()
+            .map(|alert| {
+                let rule = alert.metadata["rule_id"].as_str().unwrap_or_default();
+                (alert.wallet.clone(), rule.to_string())
+            })
+            .collect();
+        raised.sort();
+        raised
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(wallet, rule)| (wallet.to_string(), rule.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_price_update_alerts_every_holder_once_per_mint() {
+        let engine = engine();
+        engine.seed_balances("alice", [(USDC.to_string(), 5_000_000, 6)]);
+        engine.seed_balances(
+            "bob",
+            [
+                (USDC.to_string(), 1_000_000, 6),
+                (USDT.to_string(), 1_000_000, 6),
+            ],
+        );
+        engine.seed_balances("carol", [(SOL.to_string(), 1_000_000_000, 9)]);
+
+        // On peg: nothing to report, but the shock rule now has a reference price
+        assert!(engine.process_event(&price(USDC, 1.0, 60)).await.is_empty());
+
+        // Off peg by 15%: both rules fire for each USDC holder, and only for them
+        let alerts = engine.process_event(&price(USDC, 0.85, 0)).await;
+        assert_eq!(
+            raised(&alerts),
+            pairs(&[
+                ("alice", "depeg"),
+                ("alice", "shock"),
+                ("bob", "depeg"),
+                ("bob", "shock"),
+            ])
+        );
+
+        // A slightly worse print inside the cooldown does not repeat them
+        assert!(engine.process_event(&price(USDC, 0.84, 0)).await.is_empty());
+
+        // Another mint is judged on its own, even for a wallet already alerted about USDC
+        let alerts = engine.process_event(&price(USDT, 0.9, 0)).await;
+        assert_eq!(raised(&alerts), pairs(&[("bob", "depeg")]));
+    }
+}
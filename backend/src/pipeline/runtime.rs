@@ -0,0 +1,185 @@
+// backend/src/pipeline/runtime.rs
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::task::JoinHandle;
+
+use crate::config::PipelineConfig;
+use crate::models::event::PortfolioEvent;
+use crate::pipeline::dead_letter::DeadLetterQueue;
+use crate::pipeline::micro_batcher::{MicroBatcher, ShardWorkers};
+use crate::pipeline::mpsc_queue::EventQueue;
+use crate::pipeline::rules_engine::RulesEngine;
+use crate::services::metrics::MetricsService;
+
+// Restart backoff bounds for a crashed stage
+const RESTART_BASE_DELAY_MS: u64 = 100;
+const RESTART_MAX_DELAY_MS: u64 = 10_000;
+
+// Step 1: Owns the event receiver and keeps the processing stages running
+pub struct PipelineRuntime {
+    config: PipelineConfig,
+    metrics: MetricsService,
+    rules: Arc<RulesEngine>,
+    dead_letters: DeadLetterQueue,
+}
+
+// Step 2: Cloneable handle used to stop the runtime
+#[derive(Clone)]
+pub struct PipelineHandle {
+    shutdown: Arc<Notify>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    drain_timeout: std::time::Duration,
+}
+
+impl PipelineRuntime {
+    pub fn new(
+        config: &PipelineConfig,
+        metrics: MetricsService,
+        rules: Arc<RulesEngine>,
+        dead_letters: DeadLetterQueue,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            metrics,
+            rules,
+            dead_letters,
+        }
+    }
+
+    // Step 3: Start consuming `queue` in the background
+    pub fn start(self, queue: EventQueue) -> PipelineHandle {
+        let shutdown = Arc::new(Notify::new());
+        let drain_timeout = self.config.drain_timeout();
+        let task = tokio::spawn(self.supervise(queue, shutdown.clone()));
+
+        PipelineHandle {
+            shutdown,
+            task: Arc::new(Mutex::new(Some(task))),
+            drain_timeout,
+        }
+    }
+
+    // Step 4: Forward events to the batcher stage, restarting it whenever it crashes. The
+    // stage channel outlives each batcher, so a crash never loses the receiver the app sends
+    // into nor the events already forwarded to the stage.
+    async fn supervise(self, queue: EventQueue, shutdown: Arc<Notify>) {
+        tracing::info!("🏭 Starting pipeline runtime");
+        let (stage_tx, stage_rx) = mpsc::channel(self.config.max_queue_size);
+        let stage_rx = Arc::new(Mutex::new(stage_rx));
+        // A restarted batcher joins its predecessor's shards before spawning its own
+        let shard_workers = ShardWorkers::default();
+        let mut stage = self.spawn_batcher(stage_rx.clone(), shard_workers.clone());
+        let mut restarts: u32 = 0;
+        let mut pending: Option<PortfolioEvent> = None; // Received, waiting for stage capacity
+        let mut draining = false;
+
+        loop {
+            tokio::select! {
+                biased;
+                // The stage only exits on its own by crashing: we still hold its sender
+                result = &mut stage => {
+                    restarts += 1;
+                    match result {
+                        Err(e) if e.is_panic() => {
+                            tracing::error!(
+                                "💥 Batcher stage panicked (restart {}): {}",
+                                restarts,
+                                e
+                            );
+                        }
+                        _ => {
+                            tracing::error!("💥 Batcher stage exited (restart {})", restarts);
+                        }
+                    }
+                    self.metrics.record_pipeline_restart("batcher").await;
+                    tokio::time::sleep(restart_delay(restarts)).await;
+
+                    // Whatever the crashed batcher left in the channel goes to its successor
+                    let carried_over = stage_tx.max_capacity() - stage_tx.capacity();
+                    if carried_over > 0 {
+                        tracing::warn!(
+                            "♻️ Handing {} buffered event(s) to the restarted batcher",
+                            carried_over
+                        );
+                        self.metrics
+                            .record_pipeline_carried_over("batcher", carried_over)
+                            .await;
+                    }
+                    stage = self.spawn_batcher(stage_rx.clone(), shard_workers.clone());
+                }
+                // Step 5: Drain: stop accepting, then keep forwarding until the queue is empty
+                _ = shutdown.notified(), if !draining => {
+                    draining = true;
+                    queue.close();
+                }
+                // Waiting for capacity here keeps a dead stage from blocking its own restart
+                permit = stage_tx.reserve(), if pending.is_some() => {
+                    if let (Ok(permit), Some(event)) = (permit, pending.take()) {
+                        permit.send(event);
+                    }
+                }
+                maybe = queue.recv(), if pending.is_none() => match maybe {
+                    Some(event) => pending = Some(event),
+                    None => break, // Closed and empty
+                },
+            }
+        }
+
+        // Let the batcher finish what was forwarded
+        drop(stage_tx);
+        if let Err(e) = stage.await {
+            tracing::error!("💥 Batcher stage crashed while draining: {}", e);
+        }
+        tracing::info!("✅ Pipeline drained");
+    }
+
+    fn spawn_batcher(
+        &self,
+        stage_rx: Arc<Mutex<mpsc::Receiver<PortfolioEvent>>>,
+        shard_workers: ShardWorkers,
+    ) -> JoinHandle<()> {
+        let batcher = MicroBatcher::new(
+            stage_rx,
+            self.config.batch_size,
+            self.config.batch_timeout_duration(),
+            self.config.worker_shards,
+        )
+        .with_shard_workers(shard_workers)
+        .with_dead_letters(self.dead_letters.clone())
+        .with_rules_engine(self.rules.clone())
+        .with_metrics(self.metrics.clone());
+
+        tokio::spawn(batcher.run())
+    }
+}
+
+impl PipelineHandle {
+    // Step 6: Stop intake and wait for buffered events to be processed, up to the drain timeout
+    pub async fn shutdown(&self) {
+        let Some(task) = self.task.lock().await.take() else {
+            return; // Already stopped
+        };
+        tracing::info!("🛑 Draining pipeline");
+        self.shutdown.notify_one();
+
+        match tokio::time::timeout(self.drain_timeout, task).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("💥 Pipeline runtime crashed: {}", e),
+            Err(_) => tracing::warn!(
+                "⚠️ Pipeline did not drain within {:?}; remaining events are dropped",
+                self.drain_timeout
+            ),
+        }
+    }
+}
+
+// Step 7: Exponential restart backoff
+fn restart_delay(restarts: u32) -> std::time::Duration {
+    let exponent = restarts.saturating_sub(1).min(16);
+    std::time::Duration::from_millis(
+        RESTART_BASE_DELAY_MS
+            .saturating_mul(1 << exponent)
+            .min(RESTART_MAX_DELAY_MS),
+    )
+}
@@ -0,0 +1,295 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::integration::normalizer::EventNormalizer;
+use crate::pipeline::backtest::{Backtest, BacktestResult};
+use crate::pipeline::dead_letter::{DeadLetter, DeadLetterStage, RetryOutcome};
+use crate::pipeline::rule_set::{RuleDefinition, RuleSet};
+use crate::server_functions::portfolio::ErrorResponse;
+use crate::BackendAppState;
+
+type AdminError = (StatusCode, Json<ErrorResponse>);
+
+// Default and maximum page size for DLQ listings
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_LIST_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub stage: Option<DeadLetterStage>,
+    pub limit: Option<usize>,
+}
+
+// Candidate rules for a backtest; the events always come from the configured recording
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BacktestRequest {
+    pub rules: Vec<RuleDefinition>,
+}
+
+// Step 1: Admin routes need `Authorization: Bearer <ADMIN_TOKEN>`. Without a configured
+// token they are closed unless ADMIN_OPEN_WITHOUT_TOKEN explicitly opens them.
+pub fn require_admin(state: &BackendAppState, headers: &HeaderMap) -> Result<(), AdminError> {
+    let server = &state.config.server;
+    if server.admin_token.is_none() && server.admin_open_without_token {
+        return Ok(());
+    }
+    require_admin_token(state, headers)
+}
+
+// Routes that read server files need the token even when the opt-in is set
+pub fn require_admin_token(state: &BackendAppState, headers: &HeaderMap) -> Result<(), AdminError> {
+    let Some(token) = &state.config.server.admin_token else {
+        return Err(admin_error(
+            StatusCode::FORBIDDEN,
+            "Admin API is disabled: ADMIN_TOKEN is not set",
+        ));
+    };
+
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if provided == Some(token.as_str()) {
+        Ok(())
+    } else {
+        Err(admin_error(StatusCode::UNAUTHORIZED, "Invalid admin token"))
+    }
+}
+
+pub fn admin_error(status: StatusCode, message: impl Into<String>) -> AdminError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.into(),
+        }),
+    )
+}
+
+// Step 2: List dead-lettered events, oldest first
+pub async fn list_dead_letters(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("list_dead_letters", 200, 0.0)
+        .await;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .min(MAX_LIST_LIMIT);
+    let entries = state.dead_letters.list(query.stage, limit).await;
+
+    Ok(Json(serde_json::json!({
+        "depth": state.dead_letters.depth().await,
+        "entries": entries,
+    })))
+}
+
+// Step 3: Inspect a single entry, including its raw payload
+pub async fn get_dead_letter(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DeadLetter>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("get_dead_letter", 200, 0.0)
+        .await;
+
+    state
+        .dead_letters
+        .get(id)
+        .await
+        .map(Json)
+        .ok_or_else(|| admin_error(StatusCode::NOT_FOUND, format!("No dead letter {}", id)))
+}
+
+// Step 4: Retry an entry through the stage that failed
+pub async fn retry_dead_letter(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RetryOutcome>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("retry_dead_letter", 200, 0.0)
+        .await;
+
+    let normalizer = EventNormalizer::from_config(&state.config.solana, state.idl_registry.clone());
+    let outcome = state
+        .dead_letters
+        .retry(id, &normalizer, &state.event_tx)
+        .await
+        .ok_or_else(|| admin_error(StatusCode::NOT_FOUND, format!("No dead letter {}", id)))?;
+
+    tracing::info!(
+        "🔁 Retried dead letter {}: {}",
+        id,
+        if outcome.succeeded {
+            "succeeded"
+        } else {
+            "failed"
+        }
+    );
+    Ok(Json(outcome))
+}
+
+// Step 5: Purge a single entry
+pub async fn delete_dead_letter(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("delete_dead_letter", 200, 0.0)
+        .await;
+
+    state
+        .dead_letters
+        .remove(id)
+        .await
+        .ok_or_else(|| admin_error(StatusCode::NOT_FOUND, format!("No dead letter {}", id)))?;
+
+    Ok(Json(
+        serde_json::json!({ "status": "success", "purged": 1 }),
+    ))
+}
+
+// Step 6: Purge every entry, or only those of `?stage=`
+pub async fn purge_dead_letters(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("purge_dead_letters", 200, 0.0)
+        .await;
+
+    let purged = state.dead_letters.purge(query.stage).await;
+    tracing::warn!("🗑️ Purged {} dead letter(s)", purged);
+
+    Ok(Json(
+        serde_json::json!({ "status": "success", "purged": purged }),
+    ))
+}
+
+// Step 7: Active risk rules and the file they were loaded from
+pub async fn list_rules(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("list_rules", 200, 0.0)
+        .await;
+
+    let rule_set = state.rules_engine.rule_set().await;
+    Ok(Json(serde_json::json!({
+        "source": rule_set.source,
+        "rules": rule_set.rules,
+    })))
+}
+
+// Step 8: Re-read `RULES_PATH`; the active rules stay in place if the file is invalid
+pub async fn reload_rules(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("reload_rules", 200, 0.0)
+        .await;
+
+    let Some(path) = &state.config.risk.rules_path else {
+        return Err(admin_error(
+            StatusCode::BAD_REQUEST,
+            "RULES_PATH is not set",
+        ));
+    };
+
+    match state.rules_engine.reload(path).await {
+        Ok(rule_set) => Ok(Json(serde_json::json!({
+            "status": "success",
+            "source": rule_set.source,
+            "rules": rule_set.rules.len(),
+            "enabled": rule_set.enabled().count(),
+        }))),
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to reload rules from {}: {}", path, e);
+            Err(admin_error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+        }
+    }
+}
+
+// Step 9: Run inline candidate rules over the recording at RECORD_PATH (or REPLAY_PATH) and
+// compare them with the active rules. Nothing is broadcast or stored, and the live rule
+// windows are left untouched.
+pub async fn backtest_rules(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Json(request): Json<BacktestRequest>,
+) -> Result<Json<BacktestResult>, AdminError> {
+    require_admin_token(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("backtest_rules", 200, 0.0)
+        .await;
+
+    let candidate = RuleSet {
+        source: None,
+        rules: request.rules,
+    };
+    candidate
+        .validate()
+        .map_err(|e| admin_error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+
+    let events_path = state
+        .config
+        .replay
+        .record_path
+        .clone()
+        .or_else(|| Some(state.config.replay.path.clone()).filter(|path| !path.is_empty()))
+        .ok_or_else(|| {
+            admin_error(
+                StatusCode::BAD_REQUEST,
+                "No recording to backtest against: set RECORD_PATH",
+            )
+        })?;
+
+    // Details stay in the server log
+    let active = state.rules_engine.rule_set().await;
+    let result = Backtest::new(&state.config, state.idl_registry.clone(), &events_path)
+        .run(&candidate, &active)
+        .await
+        .map_err(|e| {
+            tracing::error!("❌ Backtest over {} failed: {}", events_path, e);
+            admin_error(StatusCode::INTERNAL_SERVER_ERROR, "Backtest failed")
+        })?;
+
+    tracing::info!(
+        "🧪 Backtested {} event(s) from {}: {} alert(s) with the candidate, {} with the active rules",
+        result.events,
+        result.events_path,
+        result.candidate.alerts,
+        result.active.alerts
+    );
+    Ok(Json(result))
+}
@@ -1,4 +1,5 @@
 // backend/src/server_functions/mod.rs
+pub mod admin;
 pub mod portfolio;
 pub mod risk;
 pub mod swap;
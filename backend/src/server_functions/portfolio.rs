@@ -54,6 +54,19 @@ pub async fn get_portfolio(
         .get_token_accounts(&wallet)
         .await
         .unwrap_or_default();
+    // Market rules alert every holder, including of tokens held before tracking started
+    if !token_accounts.is_empty() {
+        state.rules_engine.seed_balances(
+            &wallet,
+            token_accounts.iter().map(|account| {
+                (
+                    account.mint.clone(),
+                    account.amount as u64,
+                    account.decimals,
+                )
+            }),
+        );
+    }
 
     // Step 3️⃣ Build basic positions
     let positions: Vec<Position> = token_accounts
@@ -143,6 +156,8 @@ pub async fn update_position(
         mint: payload.mint.clone(),
         pnl_delta: payload.pnl_delta,
         timestamp: chrono::Utc::now(),
+        source: None,
+        reversal: false,
     };
 
     if let Err(e) = state.event_tx.send(event).await {
@@ -1,7 +1,17 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use crate::models::risk_alert::{AlertSeverity, AlertStatus, AlertTransitionError, RiskAlert};
+use crate::pipeline::alert_store::{AlertAction, AlertFilter, AlertUpdateError};
+use crate::server_functions::admin::{admin_error, require_admin};
+use crate::server_functions::portfolio::ErrorResponse;
 use crate::BackendAppState;
 
+type AlertError = (StatusCode, Json<ErrorResponse>);
+
 // Step 1: Risk analysis request
 #[derive(Debug, Deserialize)]
 pub struct RiskAnalysisRequest {
@@ -22,32 +32,49 @@ pub struct PositionForAnalysis {
     pub volatility: f64,
 }
 
-// Step 3: Get risk alerts for a wallet
+// Default and maximum page size for alert listings
+const DEFAULT_ALERT_LIMIT: usize = 50;
+const MAX_ALERT_LIMIT: usize = 500;
+
+// Filters and page of an alert listing
+#[derive(Debug, Deserialize)]
+pub struct AlertListQuery {
+    pub wallet: Option<String>,
+    pub severity: Option<AlertSeverity>,
+    pub status: Option<AlertStatus>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+// Step 3: List stored risk alerts, newest first
 pub async fn get_risk_alerts(
     State(state): State<BackendAppState>,
-) -> Json<Vec<crate::models::risk_alert::RiskAlert>> {
+    Query(query): Query<AlertListQuery>,
+) -> Json<serde_json::Value> {
     state
         .metrics
         .record_api_request("get_risk_alerts", 200, 0.0)
         .await;
 
-    // Step 4: Mock alerts - in production, query database
-    let mock_alerts = vec![
-        crate::models::risk_alert::RiskAlert::new(
-            "mock_wallet".to_string(),
-            crate::models::risk_alert::AlertSeverity::Medium,
-            "High concentration in meme coins".to_string(),
-            Some(serde_json::json!({"concentration": 0.45})),
-        ),
-        crate::models::risk_alert::RiskAlert::new(
-            "mock_wallet".to_string(),
-            crate::models::risk_alert::AlertSeverity::High,
-            "Large position size relative to portfolio".to_string(),
-            Some(serde_json::json!({"position_size_ratio": 0.32})),
-        ),
-    ];
-
-    Json(mock_alerts)
+    // Step 4: Filter, then page through the matches
+    let filter = AlertFilter {
+        wallet: query.wallet,
+        severity: query.severity,
+        status: query.status,
+    };
+    let offset = query.offset.unwrap_or(0);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ALERT_LIMIT)
+        .min(MAX_ALERT_LIMIT);
+    let (total, alerts) = state.alerts.list(&filter, offset, limit).await;
+
+    Json(serde_json::json!({
+        "alerts": alerts,
+        "total": total,
+        "offset": offset,
+        "limit": limit,
+    }))
 }
 
 // Step 5: Analyze position risk using AI
@@ -79,14 +106,31 @@ pub async fn analyze_position(
         .await;
 
     match risk_analysis {
-        Ok(analysis) => Json(serde_json::json!({
-            "status": "success",
-            "wallet": payload.wallet,
-            "risk_score": analysis.risk_score,
-            "risk_level": analysis.risk_level,
-            "alerts": analysis.alerts,
-            "recommendations": analysis.recommendations,
-        })),
+        Ok(mut analysis) => {
+            // Step 7: Drop AI alerts the model is not confident enough about
+            if let Some(alerts) = &mut analysis.alerts {
+                let before = alerts.len();
+                alerts.retain(|alert| {
+                    state
+                        .rules_engine
+                        .deduplicator()
+                        .meets_confidence(alert.confidence)
+                });
+                state
+                    .metrics
+                    .record_alert_suppressed("low_confidence", (before - alerts.len()) as u64)
+                    .await;
+            }
+
+            Json(serde_json::json!({
+                "status": "success",
+                "wallet": payload.wallet,
+                "risk_score": analysis.risk_score,
+                "risk_level": analysis.risk_level,
+                "alerts": analysis.alerts,
+                "recommendations": analysis.recommendations,
+            }))
+        }
         Err(e) => {
             tracing::error!("❌ Risk analysis error: {}", e);
             Json(serde_json::json!({
@@ -97,3 +141,148 @@ pub async fn analyze_position(
     }
 }
 
+// Body of an acknowledge request
+#[derive(Debug, Default, Deserialize)]
+pub struct AcknowledgeAlertRequest {
+    pub assignee: Option<String>,
+}
+
+// Body of a resolve request
+#[derive(Debug, Default, Deserialize)]
+pub struct ResolveAlertRequest {
+    pub assignee: Option<String>,
+    pub notes: Option<String>,
+}
+
+// Body of a snooze request
+#[derive(Debug, Deserialize)]
+pub struct SnoozeAlertRequest {
+    pub until: chrono::DateTime<chrono::Utc>,
+}
+
+// Step 8: Get a single alert
+pub async fn get_risk_alert(
+    State(state): State<BackendAppState>,
+    Path(id): Path<String>,
+) -> Result<Json<RiskAlert>, AlertError> {
+    state
+        .metrics
+        .record_api_request("get_risk_alert", 200, 0.0)
+        .await;
+
+    state
+        .alerts
+        .get(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| admin_error(StatusCode::NOT_FOUND, format!("No alert {}", id)))
+}
+
+// Step 9: Acknowledge an alert, optionally assigning it
+pub async fn acknowledge_alert(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    payload: Option<Json<AcknowledgeAlertRequest>>,
+) -> Result<Json<RiskAlert>, AlertError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("acknowledge_alert", 200, 0.0)
+        .await;
+
+    let Json(payload) = payload.unwrap_or_default();
+    update_alert(
+        &state,
+        &id,
+        AlertAction::Acknowledge {
+            assignee: payload.assignee,
+        },
+    )
+    .await
+}
+
+// Step 10: Resolve an alert with optional notes; resolved alerts are final
+pub async fn resolve_alert(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    payload: Option<Json<ResolveAlertRequest>>,
+) -> Result<Json<RiskAlert>, AlertError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("resolve_alert", 200, 0.0)
+        .await;
+
+    let Json(payload) = payload.unwrap_or_default();
+    update_alert(
+        &state,
+        &id,
+        AlertAction::Resolve {
+            notes: payload.notes,
+            assignee: payload.assignee,
+        },
+    )
+    .await
+}
+
+// Step 11: Snooze an alert until a time; repeats of its rule for the wallet stay quiet until then
+pub async fn snooze_alert(
+    State(state): State<BackendAppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SnoozeAlertRequest>,
+) -> Result<Json<RiskAlert>, AlertError> {
+    require_admin(&state, &headers)?;
+    state
+        .metrics
+        .record_api_request("snooze_alert", 200, 0.0)
+        .await;
+
+    update_alert(&state, &id, AlertAction::Snooze { until: payload.until }).await
+}
+
+async fn update_alert(
+    state: &BackendAppState,
+    id: &str,
+    action: AlertAction,
+) -> Result<Json<RiskAlert>, AlertError> {
+    match state.alerts.update(id, action).await {
+        Ok(alert) => {
+            tracing::info!("🚨 Alert {} is now {}", alert.id, alert.status.as_str());
+            Ok(Json(alert))
+        }
+        Err(e) => Err(admin_error(update_error_status(&e), e.to_string())),
+    }
+}
+
+// A resolved alert is final (409); snoozing into the past is a bad request (400)
+fn update_error_status(e: &AlertUpdateError) -> StatusCode {
+    match e {
+        AlertUpdateError::NotFound(_) => StatusCode::NOT_FOUND,
+        AlertUpdateError::Transition(AlertTransitionError::AlreadyResolved) => StatusCode::CONFLICT,
+        AlertUpdateError::Transition(AlertTransitionError::SnoozeInPast(_)) => {
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refused_transitions_map_to_client_errors() {
+        let resolved = AlertUpdateError::Transition(AlertTransitionError::AlreadyResolved);
+        assert_eq!(update_error_status(&resolved), StatusCode::CONFLICT);
+
+        let past = AlertUpdateError::Transition(AlertTransitionError::SnoozeInPast(
+            chrono::Utc::now(),
+        ));
+        assert_eq!(update_error_status(&past), StatusCode::BAD_REQUEST);
+
+        let missing = AlertUpdateError::NotFound("nope".to_string());
+        assert_eq!(update_error_status(&missing), StatusCode::NOT_FOUND);
+    }
+}
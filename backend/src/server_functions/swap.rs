@@ -101,7 +101,10 @@ pub async fn execute_swap(
                 input_mint: payload.input_mint,
                 output_mint: payload.output_mint,
                 amount: payload.amount,
+                amount_out: 0, // Not reported by the swap transaction
                 timestamp: chrono::Utc::now(),
+                source: None,
+                reversal: false,
             };
 
             let _ = state.event_tx.send(event).await;
@@ -134,5 +137,5 @@ async fn calculate_swap_output(input_mint: &str, output_mint: &str, amount: f64)
         _ => 1.0,
     };
 
-    (amount as f64 * base_rate) as u64
+    (amount * base_rate) as u64
 }
@@ -27,6 +27,8 @@ pub struct RiskAlert {
     pub message: String,
     pub metric: Option<String>,
     pub value: Option<f64>,
+    #[serde(default)]
+    pub confidence: Option<f64>, // Model confidence in [0, 1]; `None` for local fallback alerts
 }
 
 // Step 3: AI client service
@@ -84,6 +86,7 @@ impl AIClient {
                     message: "AI service disabled, using fallback risk calculation.".to_string(),
                     metric: None,
                     value: None,
+                    confidence: None,
                 }]),
                 recommendations: Some(vec!["Enable AI service for live analysis".to_string()]),
             });
@@ -100,6 +103,7 @@ impl AIClient {
                     message: "Portfolio total_value must be > 0 for AI analysis".to_string(),
                     metric: Some("total_value".to_string()),
                     value: Some(total_value),
+                    confidence: None,
                 }]),
                 recommendations: Some(vec![
                     "Fund portfolio or add positions before analysis".to_string()
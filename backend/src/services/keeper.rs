@@ -0,0 +1,193 @@
+// backend/src/services/keeper.rs
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+};
+
+use crate::config::Config;
+use crate::integration::program_accounts::{
+    anchor_discriminator, PoolStateAccount, TriggerOrderAccount,
+};
+use crate::services::metrics::MetricsService;
+use crate::services::solana_client::SolanaClient;
+use crate::{BackendError, BackendResult};
+
+type ThreadSafeError = Box<dyn std::error::Error + Send + Sync>;
+
+// Seeds mirrored from programs/portfolio-program/src/state.rs
+const TRIGGER_ESCROW_SEED: &[u8] = b"trigger_escrow";
+
+// Step 1: Keeper that executes triggered stop-loss / take-profit orders for their bounty
+pub struct TriggerKeeper {
+    solana_client: SolanaClient,
+    metrics: MetricsService,
+    keypair: Keypair,
+    amm_pool_program_id: Pubkey,
+    portfolio_program_id: Pubkey,
+    poll_interval: std::time::Duration,
+}
+
+impl TriggerKeeper {
+    pub fn new(
+        config: &Config,
+        solana_client: SolanaClient,
+        metrics: MetricsService,
+    ) -> BackendResult<Self> {
+        let keypair = read_keypair_file(&config.keeper.keypair_path).map_err(|e| {
+            BackendError::ConfigError(format!("Failed to read KEEPER_KEYPAIR_PATH: {}", e))
+        })?;
+        let amm_pool_program_id = config.solana.amm_pool_program_pubkey()?;
+        let portfolio_program_id = config.solana.portfolio_program_pubkey()?;
+
+        Ok(Self {
+            solana_client,
+            metrics,
+            keypair,
+            amm_pool_program_id,
+            portfolio_program_id,
+            poll_interval: config.keeper.poll_interval(),
+        })
+    }
+
+    // Step 2: Scan open orders on a fixed interval
+    pub async fn run(self) {
+        tracing::info!(
+            "🤖 Starting trigger-order keeper {} (interval: {:?})",
+            self.keypair.pubkey(),
+            self.poll_interval
+        );
+
+        let mut interval = tokio::time::interval(self.poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.scan_and_execute().await {
+                tracing::error!("❌ Keeper scan failed: {}", e);
+            }
+        }
+    }
+
+    // Step 3: Load every open order, check it against its pool's TWAP and execute it
+    async fn scan_and_execute(&self) -> Result<(), ThreadSafeError> {
+        let accounts = self
+            .solana_client
+            .get_program_accounts_by_discriminator(
+                &self.portfolio_program_id.to_string(),
+                anchor_discriminator("account", "TriggerOrder"),
+            )
+            .await?;
+
+        let mut orders_by_pool: HashMap<String, Vec<(String, TriggerOrderAccount)>> =
+            HashMap::new();
+        for (address, data) in accounts {
+            match TriggerOrderAccount::decode(&data) {
+                Ok(order) => orders_by_pool
+                    .entry(order.pool.clone())
+                    .or_default()
+                    .push((address, order)),
+                Err(e) => tracing::warn!("⚠️ Skipping trigger order {}: {}", address, e),
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        for (pool_address, orders) in orders_by_pool {
+            // A closed or undecodable pool must not hold up orders on the others
+            let pool = match self.load_pool(&pool_address).await {
+                Ok(pool) => pool,
+                Err(e) => {
+                    tracing::warn!("⚠️ Skipping orders on pool {}: {}", pool_address, e);
+                    continue;
+                }
+            };
+            let Some(twap) = pool.twap.and_then(|oracle| oracle.twap(now)) else {
+                tracing::debug!("⏳ No TWAP yet for pool {}", pool_address);
+                continue;
+            };
+
+            for (order_address, order) in orders {
+                if !order.kind.is_triggered(twap, order.trigger_price) {
+                    continue;
+                }
+
+                match self
+                    .execute_order(&order_address, &order, &pool_address, &pool)
+                    .await
+                {
+                    Ok(signature) => {
+                        tracing::info!(
+                            "🎯 Executed {:?} order {} at TWAP {}: {}",
+                            order.kind,
+                            order_address,
+                            twap,
+                            signature
+                        );
+                        self.metrics.record_keeper_execution(true).await;
+                    }
+                    Err(e) => {
+                        tracing::warn!("⚠️ Failed to execute order {}: {}", order_address, e);
+                        self.metrics.record_keeper_execution(false).await;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn load_pool(&self, pool_address: &str) -> Result<PoolStateAccount, ThreadSafeError> {
+        let data = self.solana_client.get_account_data(pool_address).await?;
+        Ok(PoolStateAccount::decode(&data)?)
+    }
+
+    // Step 4: Build and submit `execute_trigger_order`
+    async fn execute_order(
+        &self,
+        order_address: &str,
+        order: &TriggerOrderAccount,
+        pool_address: &str,
+        pool: &PoolStateAccount,
+    ) -> Result<String, ThreadSafeError> {
+        let order_key = Pubkey::from_str(order_address)?;
+        let pool_key = Pubkey::from_str(pool_address)?;
+        let (escrow, _) = Pubkey::find_program_address(
+            &[TRIGGER_ESCROW_SEED, order_key.as_ref()],
+            &self.portfolio_program_id,
+        );
+        let pool_authority =
+            PoolStateAccount::authority_address(&pool_key, &self.amm_pool_program_id);
+
+        // The program only accepts the vaults recorded in PoolState, which needs v3
+        let (Some(vault_a), Some(vault_b)) = (&pool.vault_a, &pool.vault_b) else {
+            return Err(format!(
+                "pool is at v{}, migrate it to record its vaults",
+                pool.version
+            )
+            .into());
+        };
+
+        let instruction = Instruction {
+            program_id: self.portfolio_program_id,
+            accounts: vec![
+                AccountMeta::new(order_key, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(pool_key, false),
+                AccountMeta::new_readonly(pool_authority, false),
+                AccountMeta::new(Pubkey::from_str(vault_a)?, false),
+                AccountMeta::new(Pubkey::from_str(vault_b)?, false),
+                AccountMeta::new(Pubkey::from_str(&order.destination)?, false),
+                AccountMeta::new(Pubkey::from_str(&order.owner)?, false),
+                AccountMeta::new(self.keypair.pubkey(), true),
+                AccountMeta::new_readonly(self.amm_pool_program_id, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: anchor_discriminator("global", "execute_trigger_order").to_vec(),
+        };
+
+        self.solana_client
+            .send_instructions(&self.keypair, &[instruction])
+            .await
+    }
+}
@@ -16,6 +16,12 @@ pub enum MetricValue {
     Gauge(f64),
 }
 
+impl Default for MetricsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MetricsService {
     pub fn new() -> Self {
         Self {
@@ -63,4 +69,131 @@ impl MetricsService {
         self.increment_counter("websocket_connections_total", 1)
             .await;
     }
+
+    // Step 8: Record trigger-order keeper executions
+    pub async fn record_keeper_execution(&self, success: bool) {
+        self.increment_counter(
+            &format!("keeper_executions_total,success={}", success),
+            1,
+        )
+        .await;
+    }
+
+    // Step 9: Record Solana WebSocket reconnects
+    pub async fn record_ws_reconnect(&self) {
+        self.increment_counter("solana_ws_reconnects_total", 1)
+            .await;
+    }
+
+    // Step 10: Record transactions replayed to close a reconnect gap
+    pub async fn record_ws_gap(&self, transactions: u64, slots: u64) {
+        self.increment_counter("solana_ws_gap_transactions_total", transactions)
+            .await;
+        self.set_gauge("solana_ws_last_gap_slots", slots as f64)
+            .await;
+    }
+
+    // Step 11: Record events dropped as already delivered
+    pub async fn record_ws_duplicates(&self, count: u64) {
+        self.increment_counter("solana_ws_duplicate_events_total", count)
+            .await;
+    }
+
+    // Step 12: Record how tracked transactions settled (finalized or reverted)
+    pub async fn record_finality(&self, status: &str) {
+        self.increment_counter(&format!("finality_transactions_total,status={}", status), 1)
+            .await;
+    }
+
+    // Compensating events for reverted transactions, by outcome (sent, retried or lost)
+    pub async fn record_compensation(&self, outcome: &str) {
+        self.increment_counter(&format!("finality_compensations_total,outcome={}", outcome), 1)
+            .await;
+    }
+
+    pub async fn set_finality_pending(&self, pending: usize) {
+        self.set_gauge("finality_pending_transactions", pending as f64)
+            .await;
+    }
+
+    // Step 13: Record events parked in the dead-letter queue
+    pub async fn record_dead_letter(&self, stage: &str, depth: usize) {
+        self.increment_counter(&format!("dead_letters_total,stage={}", stage), 1)
+            .await;
+        self.set_dead_letter_depth(depth).await;
+    }
+
+    pub async fn set_dead_letter_depth(&self, depth: usize) {
+        self.set_gauge("dead_letter_queue_depth", depth as f64)
+            .await;
+    }
+
+    // Step 14: Record pipeline stage restarts and raised risk alerts
+    pub async fn record_pipeline_restart(&self, stage: &str) {
+        self.increment_counter(&format!("pipeline_stage_restarts_total,stage={}", stage), 1)
+            .await;
+    }
+
+    // Events a crashed stage left buffered, handed to its replacement
+    pub async fn record_pipeline_carried_over(&self, stage: &str, events: usize) {
+        self.increment_counter(
+            &format!("pipeline_restart_carried_over_events_total,stage={}", stage),
+            events as u64,
+        )
+        .await;
+    }
+
+    pub async fn record_risk_alert(&self, severity: &str) {
+        self.increment_counter(&format!("risk_alerts_total,severity={}", severity), 1)
+            .await;
+    }
+
+    // Step 15: Record wallet groups waiting in a batcher shard
+    pub async fn set_shard_queue_depth(&self, shard: usize, depth: usize) {
+        self.set_gauge(
+            &format!("pipeline_shard_queue_depth,shard={}", shard),
+            depth as f64,
+        )
+        .await;
+    }
+
+    // Step 16: Record event queue depth, throughput, shed events and latency
+    pub async fn set_queue_depth(&self, lane: &str, depth: usize) {
+        self.set_gauge(&format!("event_queue_depth,lane={}", lane), depth as f64)
+            .await;
+    }
+
+    pub async fn record_queue_throughput(&self, received: u64, processed: u64) {
+        self.increment_counter("event_queue_received_total", received)
+            .await;
+        self.increment_counter("event_queue_processed_total", processed)
+            .await;
+    }
+
+    pub async fn record_queue_shed(&self, reason: &str, count: u64) {
+        if count > 0 {
+            self.increment_counter(&format!("event_queue_shed_total,reason={}", reason), count)
+                .await;
+        }
+    }
+
+    pub async fn set_queue_latency(&self, avg_ms: f64) {
+        self.set_gauge("event_queue_latency_ms", avg_ms).await;
+    }
+
+    // Step 17: Record alerts held back by the deduplicator and repeats re-sent as escalations
+    pub async fn record_alert_suppressed(&self, reason: &str, count: u64) {
+        if count > 0 {
+            self.increment_counter(
+                &format!("risk_alerts_suppressed_total,reason={}", reason),
+                count,
+            )
+            .await;
+        }
+    }
+
+    pub async fn record_alert_escalated(&self, rule_id: &str) {
+        self.increment_counter(&format!("risk_alerts_escalated_total,rule={}", rule_id), 1)
+            .await;
+    }
 }
@@ -1,4 +1,5 @@
 // backend/src/services/mod.rs
 pub mod ai_client;
+pub mod keeper;
 pub mod metrics;
 pub mod solana_client;
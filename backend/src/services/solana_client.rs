@@ -3,17 +3,25 @@ use base64::Engine as _;
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use solana_account_decoder::UiAccountEncoding;
 use solana_client::{
     nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
     rpc_request::TokenAccountsFilter,
-    rpc_response::RpcKeyedAccount,
+    rpc_response::{RpcConfirmedTransactionStatusWithSignature, RpcKeyedAccount},
 };
-use solana_program::program_pack::Pack;
 use solana_sdk::{
+    instruction::Instruction,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
+use solana_transaction_status::{
+    EncodedTransaction, TransactionConfirmationStatus, UiLoadedAddresses, UiMessage,
+    UiRawMessage, UiTransactionEncoding,
+};
 use std::str::FromStr;
 
 use crate::config::SolanaConfig;
@@ -89,6 +97,31 @@ pub struct TokenBalance {
     pub decimals: u8,
 }
 
+// Transaction history returned by getTransaction
+#[derive(Debug, Clone)]
+pub struct TransactionLogs {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+    pub instructions: Vec<TransactionInstruction>, // Top-level only
+}
+
+impl TransactionLogs {
+    // When the transaction's block was produced, if the node still knows
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.block_time?, 0)
+    }
+}
+
+// A top-level instruction with its account keys resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInstruction {
+    pub program_id: String,
+    pub accounts: Vec<String>,
+    pub data: Vec<u8>,
+}
+
 // Solana client service
 pub struct SolanaClient {
     rpc_client: RpcClient,
@@ -329,6 +362,189 @@ impl SolanaClient {
         Ok(signature.to_string())
     }
 
+    // Fetch every account of a program whose data starts with an Anchor discriminator
+    pub async fn get_program_accounts_by_discriminator(
+        &self,
+        program_id: &str,
+        discriminator: [u8; 8],
+    ) -> Result<Vec<(String, Vec<u8>)>, ThreadSafeError> {
+        let program_id = Pubkey::from_str(program_id)?;
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &discriminator,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = self
+            .rpc_client
+            .get_program_accounts_with_config(&program_id, config)
+            .await?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey.to_string(), account.data))
+            .collect())
+    }
+
+    // Current slot at the configured commitment
+    pub async fn get_slot(&self) -> Result<u64, ThreadSafeError> {
+        Ok(self.rpc_client.get_slot().await?)
+    }
+
+    // Newest rooted slot; transactions below it that are still unknown have been dropped
+    pub async fn get_finalized_slot(&self) -> Result<u64, ThreadSafeError> {
+        Ok(self
+            .rpc_client
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .await?)
+    }
+
+    // Confirmation level of each signature, searching the ledger history; `None` if unknown
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[String],
+    ) -> Result<Vec<Option<TransactionConfirmationStatus>>, ThreadSafeError> {
+        let signatures = signatures
+            .iter()
+            .map(|signature| Signature::from_str(signature))
+            .collect::<Result<Vec<_>, _>>()?;
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses_with_history(&signatures)
+            .await?
+            .value;
+
+        Ok(statuses
+            .into_iter()
+            .map(|status| status.map(|status| status.confirmation_status()))
+            .collect())
+    }
+
+    // Signatures mentioning `address`, newest first, strictly between `until` and `before`
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ThreadSafeError> {
+        let address = Pubkey::from_str(address)?;
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before: before.map(Signature::from_str).transpose()?,
+            until: until.map(Signature::from_str).transpose()?,
+            limit: Some(limit),
+            commitment: Some(self.history_commitment()),
+        };
+
+        Ok(self
+            .rpc_client
+            .get_signatures_for_address_with_config(&address, config)
+            .await?)
+    }
+
+    // Slot, block time and log messages of a landed transaction
+    pub async fn get_transaction_logs(
+        &self,
+        signature: &str,
+    ) -> Result<TransactionLogs, ThreadSafeError> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(self.history_commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self
+            .rpc_client
+            .get_transaction_with_config(&Signature::from_str(signature)?, config)
+            .await?;
+
+        let meta = transaction.transaction.meta;
+        let loaded_addresses = meta
+            .as_ref()
+            .and_then(|meta| Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone()));
+        let logs = meta
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+            .unwrap_or_default();
+        let instructions = match &transaction.transaction.transaction {
+            EncodedTransaction::Json(ui_transaction) => match &ui_transaction.message {
+                UiMessage::Raw(message) => {
+                    resolve_instructions(message, loaded_addresses.as_ref())
+                }
+                UiMessage::Parsed(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        Ok(TransactionLogs {
+            signature: signature.to_string(),
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            logs,
+            instructions,
+        })
+    }
+
+    // History endpoints do not accept `processed`
+    fn history_commitment(&self) -> CommitmentConfig {
+        let commitment = self.rpc_client.commitment();
+        if commitment == CommitmentConfig::processed() {
+            CommitmentConfig::confirmed()
+        } else {
+            commitment
+        }
+    }
+
+    // Fetch raw account data
+    pub async fn get_account_data(&self, address: &str) -> Result<Vec<u8>, ThreadSafeError> {
+        let pubkey = Pubkey::from_str(address)?;
+        Ok(self.rpc_client.get_account_data(&pubkey).await?)
+    }
+
+    // Find a token account held by `owner` for `mint`
+    pub async fn find_token_account(
+        &self,
+        owner: &str,
+        mint: &str,
+    ) -> Result<Option<String>, ThreadSafeError> {
+        let owner = Pubkey::from_str(owner)?;
+        let mint = Pubkey::from_str(mint)?;
+
+        let accounts = self
+            .rpc_client
+            .get_token_accounts_by_owner(&owner, TokenAccountsFilter::Mint(mint))
+            .await?;
+
+        Ok(accounts.into_iter().next().map(|account| account.pubkey))
+    }
+
+    // Sign and send instructions with `payer` as fee payer and only signer
+    pub async fn send_instructions(
+        &self,
+        payer: &Keypair,
+        instructions: &[Instruction],
+    ) -> Result<String, ThreadSafeError> {
+        let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &[payer],
+            recent_blockhash,
+        );
+
+        let signature = self
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .await?;
+
+        Ok(signature.to_string())
+    }
+
     // Fetch 24h volatility for a given token mint using Coingecko
     pub async fn get_token_volatility(&self, mint: &str) -> f64 {
         let (coingecko_id, default_vol) = match mint {
@@ -376,4 +592,36 @@ impl Clone for SolanaClient {
             http: HttpClient::new(),
         }
     }
-}
\ No newline at end of file
+}
+
+// Resolve account indexes against the static keys followed by the lookup-table addresses.
+// Instructions with out-of-range indexes or undecodable data are dropped.
+fn resolve_instructions(
+    message: &UiRawMessage,
+    loaded_addresses: Option<&UiLoadedAddresses>,
+) -> Vec<TransactionInstruction> {
+    let mut keys = message.account_keys.clone();
+    if let Some(loaded) = loaded_addresses {
+        keys.extend(loaded.writable.iter().cloned());
+        keys.extend(loaded.readonly.iter().cloned());
+    }
+
+    message
+        .instructions
+        .iter()
+        .filter_map(|instruction| {
+            let program_id = keys.get(instruction.program_id_index as usize)?.clone();
+            let accounts = instruction
+                .accounts
+                .iter()
+                .map(|index| keys.get(*index as usize).cloned())
+                .collect::<Option<Vec<_>>>()?;
+            let data = bs58::decode(&instruction.data).into_vec().ok()?;
+            Some(TransactionInstruction {
+                program_id,
+                accounts,
+                data,
+            })
+        })
+        .collect()
+}
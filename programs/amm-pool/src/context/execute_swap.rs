@@ -1,4 +1,5 @@
 use crate::pool_state::PoolState;
+use crate::swap_math::ErrorCode;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 
@@ -20,11 +21,11 @@ pub struct ExecuteSwap<'info> {
     pub user_destination: Account<'info, TokenAccount>,
 
     /// pool's token A vault
-    #[account(mut)]
+    #[account(mut, address = pool_state.vault_a @ ErrorCode::InvalidVault)]
     pub pool_vault_a: Account<'info, TokenAccount>,
 
     /// pool's token B vault
-    #[account(mut)]
+    #[account(mut, address = pool_state.vault_b @ ErrorCode::InvalidVault)]
     pub pool_vault_b: Account<'info, TokenAccount>,
 
     /// token program
@@ -1,5 +1,6 @@
 use crate::pool_state::PoolState;
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
@@ -19,6 +20,14 @@ pub struct InitializePool<'info> {
     /// CHECK: LP mint account
     pub lp_mint: UncheckedAccount<'info>,
 
+    /// pool's token A vault, owned by the pool authority
+    #[account(token::mint = token_a, token::authority = pool_authority)]
+    pub pool_vault_a: Account<'info, TokenAccount>,
+
+    /// pool's token B vault, owned by the pool authority
+    #[account(token::mint = token_b, token::authority = pool_authority)]
+    pub pool_vault_b: Account<'info, TokenAccount>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// CHECK: May still hold a legacy layout that `Account<PoolState>` cannot
+    /// deserialize; ownership and discriminator are checked in `PoolState::migrate`.
+    #[account(mut, owner = crate::ID)]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// Pool's token A vault, recorded from v3; mint and owner are checked in
+    /// `PoolState::migrate` once the legacy layout is read
+    pub pool_vault_a: Account<'info, TokenAccount>,
+
+    /// Pool's token B vault, recorded from v3
+    pub pool_vault_b: Account<'info, TokenAccount>,
+
+    /// Pays for the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
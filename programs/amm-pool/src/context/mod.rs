@@ -2,7 +2,9 @@
 
 pub mod initialize_pool;
 pub mod execute_swap;
+pub mod migrate_pool;
 
 // Re-export structs so you can use them easily like `Context<InitializePool>`
 pub use initialize_pool::*;
 pub use execute_swap::*;
+pub use migrate_pool::*;
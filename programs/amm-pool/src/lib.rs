@@ -4,11 +4,12 @@ pub mod context;
 pub mod fees;
 pub mod pool_state;
 pub mod swap_math;
+pub mod twap;
 
 use context::*;
 
-// Use Anchor's standard placeholder - Anchor will replace this during build
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+// Must match `amm_pool` in Anchor.toml and AMM_POOL_PROGRAM_ID in the backend
+declare_id!("2YsibxDCmwrkAVVwbdkpN64RNVhnFjvJK5HSaZkdvZta");
 
 #[program]
 pub mod amm_pool {
@@ -21,4 +22,8 @@ pub mod amm_pool {
     pub fn execute_swap(ctx: Context<ExecuteSwap>, amount_in: u64, min_out: u64) -> Result<()> {
         swap_math::execute_swap(ctx, amount_in, min_out)
     }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        pool_state::PoolState::migrate(ctx)
+    }
 }
@@ -1,31 +1,367 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::swap_math::ErrorCode;
+use crate::twap::TwapOracle;
 
 #[account]
 pub struct PoolState {
+    pub version: u8,
     pub token_a: Pubkey,
     pub token_b: Pubkey,
     pub lp_mint: Pubkey,
     pub fee_rate_bps: u64,
     pub total_liquidity: u64,
     pub authority_bump: u8, // Store the bump for the PDA authority
+    pub twap: TwapOracle,   // Added in v2
+    pub vault_a: Pubkey,    // Added in v3
+    pub vault_b: Pubkey,    // Added in v3
+    pub reserved: [u8; 64], // RESERVED; shrink when adding fields, then bump VERSION
 }
 
 impl PoolState {
-    // discriminator (8) + 4 pubkeys (32*4) + 2 u64s (8*2) + 1 u8
-    pub const LEN: usize = 8 + (32 * 4) + (8 * 2) + 1;
+    pub const VERSION: u8 = 3;
+    pub const RESERVED: usize = 64;
+
+    // discriminator (8) + version (1) + 3 pubkeys (32*3) + 2 u64s (8*2) + 1 u8 + twap
+    // + 2 vault pubkeys (32*2) + reserved
+    pub const LEN: usize =
+        8 + 1 + (32 * 3) + (8 * 2) + 1 + TwapOracle::LEN + (32 * 2) + Self::RESERVED;
 
     pub fn initialize(
         ctx: Context<crate::context::InitializePool>,
         fee_rate_bps: u64,
     ) -> Result<()> {
         let pool = &mut ctx.accounts.pool_state;
+        pool.version = Self::VERSION;
         pool.token_a = ctx.accounts.token_a.key();
         pool.token_b = ctx.accounts.token_b.key();
         pool.lp_mint = ctx.accounts.lp_mint.key();
         pool.fee_rate_bps = fee_rate_bps;
         pool.total_liquidity = 0;
         pool.authority_bump = ctx.bumps.pool_authority;
+        pool.twap = TwapOracle::default();
+        pool.vault_a = ctx.accounts.pool_vault_a.key();
+        pool.vault_b = ctx.accounts.pool_vault_b.key();
+        pool.reserved = [0; Self::RESERVED];
+
+        Ok(())
+    }
+
+    pub fn migrate(ctx: Context<crate::context::MigratePool>) -> Result<()> {
+        let info = ctx.accounts.pool_state.to_account_info();
+        let mut pool = Self::from_legacy(&info.try_borrow_data()?)?;
+
+        // Record the vaults: they must hold the pool's mints and be owned by its authority
+        let authority = Pubkey::create_program_address(
+            &[info.key.as_ref(), &[pool.authority_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| error!(ErrorCode::InvalidAccountData))?;
+        let (vault_a, vault_b) = (&ctx.accounts.pool_vault_a, &ctx.accounts.pool_vault_b);
+        require!(
+            vault_a.mint == pool.token_a && vault_a.owner == authority,
+            ErrorCode::InvalidVault
+        );
+        require!(
+            vault_b.mint == pool.token_b && vault_b.owner == authority,
+            ErrorCode::InvalidVault
+        );
+        pool.vault_a = vault_a.key();
+        pool.vault_b = vault_b.key();
+
+        // Top up rent for the larger layout, then grow the account in place
+        let required = Rent::get()?.minimum_balance(Self::LEN);
+        let shortfall = required.saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(Self::LEN)?;
+
+        let mut data = info.try_borrow_mut_data()?;
+        pool.try_serialize(&mut &mut data[..])?;
 
         Ok(())
     }
+
+    /// Read an account still in a legacy layout, discriminator included, into
+    /// the current one; the vaults are left for `migrate` to record.
+    pub fn from_legacy(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::InvalidAccountData
+        );
+
+        // The unversioned layout is identified by its length, later ones by version byte
+        let body = &mut &data[8..];
+        if data.len() == PoolStateV0::LEN {
+            return Ok(PoolStateV0::deserialize(body)
+                .map_err(|_| error!(ErrorCode::InvalidAccountData))?
+                .into());
+        }
+        match data.get(8).copied() {
+            Some(1) => Ok(PoolStateV1::deserialize(body)
+                .map_err(|_| error!(ErrorCode::InvalidAccountData))?
+                .into()),
+            Some(2) => Ok(PoolStateV2::deserialize(body)
+                .map_err(|_| error!(ErrorCode::InvalidAccountData))?
+                .into()),
+            Some(Self::VERSION) => err!(ErrorCode::AlreadyMigrated),
+            _ => err!(ErrorCode::InvalidAccountData),
+        }
+    }
+}
+
+/// Unversioned `PoolState` layout written before the version byte existed.
+/// Only read by `PoolState::migrate`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolStateV0 {
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_rate_bps: u64,
+    pub total_liquidity: u64,
+    pub authority_bump: u8,
+}
+
+impl PoolStateV0 {
+    // Allocated as discriminator (8) + 4 pubkeys (32*4) + 2 u64s (8*2) + 1 u8,
+    // one pubkey more than the struct actually holds
+    pub const LEN: usize = 8 + (32 * 4) + (8 * 2) + 1;
+}
+
+impl From<PoolStateV0> for PoolState {
+    fn from(legacy: PoolStateV0) -> Self {
+        Self {
+            version: Self::VERSION,
+            token_a: legacy.token_a,
+            token_b: legacy.token_b,
+            lp_mint: legacy.lp_mint,
+            fee_rate_bps: legacy.fee_rate_bps,
+            total_liquidity: legacy.total_liquidity,
+            authority_bump: legacy.authority_bump,
+            twap: TwapOracle::default(),
+            vault_a: Pubkey::default(), // Set by `migrate`
+            vault_b: Pubkey::default(),
+            reserved: [0; Self::RESERVED],
+        }
+    }
+}
+
+/// v1 `PoolState` layout, before the TWAP oracle was added.
+/// Only read by `PoolState::migrate`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolStateV1 {
+    pub version: u8,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_rate_bps: u64,
+    pub total_liquidity: u64,
+    pub authority_bump: u8,
+    pub reserved: [u8; 64],
+}
+
+impl From<PoolStateV1> for PoolState {
+    fn from(legacy: PoolStateV1) -> Self {
+        Self {
+            version: Self::VERSION,
+            token_a: legacy.token_a,
+            token_b: legacy.token_b,
+            lp_mint: legacy.lp_mint,
+            fee_rate_bps: legacy.fee_rate_bps,
+            total_liquidity: legacy.total_liquidity,
+            authority_bump: legacy.authority_bump,
+            twap: TwapOracle::default(),
+            vault_a: Pubkey::default(), // Set by `migrate`
+            vault_b: Pubkey::default(),
+            reserved: [0; Self::RESERVED],
+        }
+    }
+}
+
+/// v2 `PoolState` layout, before the vaults were recorded.
+/// Only read by `PoolState::migrate`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolStateV2 {
+    pub version: u8,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_rate_bps: u64,
+    pub total_liquidity: u64,
+    pub authority_bump: u8,
+    pub twap: TwapOracle,
+    pub reserved: [u8; 64],
+}
+
+impl From<PoolStateV2> for PoolState {
+    fn from(legacy: PoolStateV2) -> Self {
+        Self {
+            version: Self::VERSION,
+            token_a: legacy.token_a,
+            token_b: legacy.token_b,
+            lp_mint: legacy.lp_mint,
+            fee_rate_bps: legacy.fee_rate_bps,
+            total_liquidity: legacy.total_liquidity,
+            authority_bump: legacy.authority_bump,
+            twap: legacy.twap,
+            vault_a: Pubkey::default(), // Set by `migrate`
+            vault_b: Pubkey::default(),
+            reserved: [0; Self::RESERVED],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(body: impl AnchorSerialize, len: Option<usize>) -> Vec<u8> {
+        let mut data = PoolState::DISCRIMINATOR.to_vec();
+        body.serialize(&mut data).unwrap();
+        if let Some(len) = len {
+            data.resize(len, 0);
+        }
+        data
+    }
+
+    fn keys() -> (Pubkey, Pubkey, Pubkey) {
+        (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+    }
+
+    fn assert_carried(pool: &PoolState, keys: (Pubkey, Pubkey, Pubkey)) {
+        assert_eq!(pool.version, PoolState::VERSION);
+        assert_eq!(
+            (pool.token_a, pool.token_b, pool.lp_mint),
+            (keys.0, keys.1, keys.2)
+        );
+        assert_eq!(pool.fee_rate_bps, 30);
+        assert_eq!(pool.total_liquidity, 1_000_000);
+        assert_eq!(pool.authority_bump, 254);
+        assert_eq!(pool.vault_a, Pubkey::default());
+        assert_eq!(pool.vault_b, Pubkey::default());
+    }
+
+    #[test]
+    fn current_layout_fits_its_allocation() {
+        let pool = PoolState::from(PoolStateV1 {
+            version: 1,
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            fee_rate_bps: 30,
+            total_liquidity: 0,
+            authority_bump: 0,
+            reserved: [0; 64],
+        });
+        assert_eq!(account(pool, None).len(), PoolState::LEN);
+    }
+
+    #[test]
+    fn v0_is_recognised_by_length() {
+        let keys = keys();
+        let data = account(
+            PoolStateV0 {
+                token_a: keys.0,
+                token_b: keys.1,
+                lp_mint: keys.2,
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+            },
+            Some(PoolStateV0::LEN),
+        );
+
+        let pool = PoolState::from_legacy(&data).unwrap();
+        assert_carried(&pool, keys);
+        assert_eq!(pool.twap.last_price, 0);
+    }
+
+    #[test]
+    fn v1_is_recognised_by_version() {
+        let keys = keys();
+        let data = account(
+            PoolStateV1 {
+                version: 1,
+                token_a: keys.0,
+                token_b: keys.1,
+                lp_mint: keys.2,
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+                reserved: [0; 64],
+            },
+            None,
+        );
+
+        let pool = PoolState::from_legacy(&data).unwrap();
+        assert_carried(&pool, keys);
+        assert_eq!(pool.twap.last_price, 0);
+    }
+
+    #[test]
+    fn v2_keeps_its_oracle() {
+        let keys = keys();
+        let twap = TwapOracle {
+            last_price: 7 << 32,
+            ..TwapOracle::default()
+        };
+        let data = account(
+            PoolStateV2 {
+                version: 2,
+                token_a: keys.0,
+                token_b: keys.1,
+                lp_mint: keys.2,
+                fee_rate_bps: 30,
+                total_liquidity: 1_000_000,
+                authority_bump: 254,
+                twap,
+                reserved: [0; 64],
+            },
+            None,
+        );
+
+        let pool = PoolState::from_legacy(&data).unwrap();
+        assert_carried(&pool, keys);
+        assert_eq!(pool.twap.last_price, 7 << 32);
+    }
+
+    #[test]
+    fn current_and_unknown_layouts_are_refused() {
+        let mut data = vec![0; PoolState::LEN];
+        data[..8].copy_from_slice(PoolState::DISCRIMINATOR);
+
+        data[8] = PoolState::VERSION;
+        assert_eq!(
+            PoolState::from_legacy(&data).err(),
+            Some(ErrorCode::AlreadyMigrated.into())
+        );
+
+        data[8] = PoolState::VERSION + 1;
+        assert_eq!(
+            PoolState::from_legacy(&data).err(),
+            Some(ErrorCode::InvalidAccountData.into())
+        );
+
+        data[0] ^= 0xff;
+        data[8] = 1;
+        assert_eq!(
+            PoolState::from_legacy(&data).err(),
+            Some(ErrorCode::InvalidAccountData.into())
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use crate::fees::apply_fee;
+use crate::twap::TwapOracle;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
@@ -57,6 +58,13 @@ pub fn execute_swap(
     );
     token::transfer(cpi_ctx, amount_out_u64)?;
 
+    // 6) Feed the post-swap spot price into the TWAP oracle
+    let reserve_a = x.checked_add(amount_in as u128).ok_or(ErrorCode::MathOverflow)?;
+    if let Some(price) = TwapOracle::spot_price(reserve_a, new_y) {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.pool_state.twap.update(now, price);
+    }
+
     Ok(())
 }
 
@@ -66,4 +74,10 @@ pub enum ErrorCode {
     MathOverflow,
     #[msg("Slippage exceeded")]
     SlippageExceeded,
+    #[msg("Account data does not match any known layout")]
+    InvalidAccountData,
+    #[msg("Account is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Vault does not belong to the pool")]
+    InvalidVault,
 }
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+// Minimum age of the checkpoint a TWAP is measured from
+pub const TWAP_WINDOW_SECS: i64 = 600;
+
+// Prices are Q32.32 fixed point: token B per token A, scaled by 2^32
+pub const PRICE_SCALE_BITS: u32 = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct TwapCheckpoint {
+    pub cumulative: u128,
+    pub timestamp: i64,
+}
+
+/// Cumulative-price oracle updated on every swap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct TwapOracle {
+    pub last_price: u64,        // Spot price after the last swap
+    pub price_cumulative: u128, // Sum of last_price * seconds held
+    pub last_update_ts: i64,
+    pub checkpoints: [TwapCheckpoint; 2], // [older, newer]
+}
+
+impl TwapOracle {
+    // last_price (8) + price_cumulative (16) + last_update_ts (8) + 2 checkpoints (24*2)
+    pub const LEN: usize = 8 + 16 + 8 + (24 * 2);
+
+    /// Spot price implied by the given reserves, saturating at u64::MAX.
+    pub fn spot_price(reserve_a: u128, reserve_b: u128) -> Option<u64> {
+        if reserve_a == 0 {
+            return None;
+        }
+        let price = (reserve_b << PRICE_SCALE_BITS) / reserve_a;
+        Some(price.min(u64::MAX as u128) as u64)
+    }
+
+    /// Cumulative price extrapolated to `now` at the last observed price.
+    pub fn cumulative_at(&self, now: i64) -> u128 {
+        let elapsed = now.saturating_sub(self.last_update_ts).max(0) as u128;
+        self.price_cumulative
+            .saturating_add((self.last_price as u128).saturating_mul(elapsed))
+    }
+
+    /// Record a new spot price, rolling checkpoints once the newer one is a
+    /// full window old so the older one always spans at least a window.
+    pub fn update(&mut self, now: i64, price: u64) {
+        if self.last_update_ts == 0 {
+            let seed = TwapCheckpoint {
+                cumulative: 0,
+                timestamp: now,
+            };
+            self.checkpoints = [seed, seed];
+        } else {
+            self.price_cumulative = self.cumulative_at(now);
+        }
+        self.last_price = price;
+        self.last_update_ts = now;
+
+        if now - self.checkpoints[1].timestamp >= TWAP_WINDOW_SECS {
+            self.checkpoints[0] = self.checkpoints[1];
+            self.checkpoints[1] = TwapCheckpoint {
+                cumulative: self.price_cumulative,
+                timestamp: now,
+            };
+        }
+    }
+
+    /// Time-weighted average price since the older checkpoint, or `None` until
+    /// the oracle has observed at least one full window.
+    pub fn twap(&self, now: i64) -> Option<u64> {
+        let start = self.checkpoints[0];
+        let elapsed = now.checked_sub(start.timestamp)?;
+        if self.last_update_ts == 0 || elapsed < TWAP_WINDOW_SECS {
+            return None;
+        }
+        let delta = self.cumulative_at(now).checked_sub(start.cumulative)?;
+        Some((delta / elapsed as u128).min(u64::MAX as u128) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_000;
+
+    #[test]
+    fn spot_price_is_q32_and_needs_reserve_a() {
+        assert_eq!(TwapOracle::spot_price(0, 10), None);
+        assert_eq!(
+            TwapOracle::spot_price(1_000, 2_000),
+            Some(2 << PRICE_SCALE_BITS)
+        );
+        assert_eq!(TwapOracle::spot_price(1, u128::MAX >> 32), Some(u64::MAX));
+    }
+
+    #[test]
+    fn no_twap_before_a_full_window() {
+        let mut oracle = TwapOracle::default();
+        assert_eq!(oracle.twap(T0), None);
+
+        oracle.update(T0, 100);
+        assert_eq!(oracle.twap(T0 + TWAP_WINDOW_SECS - 1), None);
+        assert_eq!(oracle.twap(T0 + TWAP_WINDOW_SECS), Some(100));
+    }
+
+    #[test]
+    fn twap_weights_prices_by_time_held() {
+        let mut oracle = TwapOracle::default();
+        oracle.update(T0, 100);
+        oracle.update(T0 + 300, 200);
+
+        // 300s at 100, then 300s at 200
+        assert_eq!(oracle.twap(T0 + 600), Some(150));
+        // The last price keeps accruing until the next swap
+        assert_eq!(oracle.twap(T0 + 900), Some(500 / 3));
+    }
+
+    #[test]
+    fn checkpoints_roll_so_the_window_follows_recent_swaps() {
+        let mut oracle = TwapOracle::default();
+        oracle.update(T0, 100);
+        oracle.update(T0 + 300, 200);
+        oracle.update(T0 + 600, 300);
+        assert_eq!(oracle.checkpoints[0].timestamp, T0);
+        assert_eq!(oracle.checkpoints[1].timestamp, T0 + 600);
+
+        oracle.update(T0 + 1_200, 300);
+        assert_eq!(oracle.checkpoints[0].timestamp, T0 + 600);
+        // Only the last window, all at 300, is averaged
+        assert_eq!(oracle.twap(T0 + 1_200), Some(300));
+    }
+
+    #[test]
+    fn twap_is_none_for_a_time_before_the_window() {
+        let mut oracle = TwapOracle::default();
+        oracle.update(T0, 100);
+        assert_eq!(oracle.twap(T0 - 1), None);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::state::TriggerOrder;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CancelTriggerOrder<'info> {
+    #[account(mut, has_one = owner, close = owner)]
+    pub order: Account<'info, TriggerOrder>,
+
+    #[account(
+        mut,
+        seeds = [TriggerOrder::ESCROW_SEED, order.key().as_ref()],
+        bump = order.escrow_bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = order.mint, token::authority = owner)]
+    pub owner_destination: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
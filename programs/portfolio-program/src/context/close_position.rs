@@ -0,0 +1,19 @@
+use crate::state::{Portfolio, Position};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut, has_one = owner, close = owner)]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [Portfolio::SEED, owner.key().as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,46 @@
+use crate::state::TriggerOrder;
+use amm_pool::pool_state::PoolState;
+use amm_pool::program::AmmPool;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ExecuteTriggerOrder<'info> {
+    #[account(mut, has_one = owner, has_one = destination, close = owner)]
+    pub order: Account<'info, TriggerOrder>,
+
+    #[account(
+        mut,
+        seeds = [TriggerOrder::ESCROW_SEED, order.key().as_ref()],
+        bump = order.escrow_bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, address = order.pool)]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// CHECK: Pool authority PDA, validated by amm-pool
+    pub pool_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Pool token A vault, must be the one recorded in the pool
+    #[account(mut, address = pool_state.vault_a)]
+    pub pool_vault_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pool token B vault, must be the one recorded in the pool
+    #[account(mut, address = pool_state.vault_b)]
+    pub pool_vault_b: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// CHECK: Order owner; receives the order and escrow rent
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// Anyone may execute a triggered order and collect its bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub amm_program: Program<'info, AmmPool>,
+    pub token_program: Program<'info, Token>,
+}
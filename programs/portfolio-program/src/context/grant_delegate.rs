@@ -0,0 +1,20 @@
+use crate::state::Delegation;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct GrantDelegate<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Delegation::LEN,
+        seeds = [Delegation::SEED, owner.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
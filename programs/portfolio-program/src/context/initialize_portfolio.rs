@@ -0,0 +1,19 @@
+use crate::state::Portfolio;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializePortfolio<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Portfolio::LEN,
+        seeds = [Portfolio::SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
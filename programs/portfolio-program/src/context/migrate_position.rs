@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    /// CHECK: May still hold a legacy layout that `Account<Position>` cannot
+    /// deserialize; ownership and discriminator are checked in the processor.
+    #[account(mut, owner = crate::ID)]
+    pub position: UncheckedAccount<'info>,
+
+    /// Token the position tracks; older layouts did not record it
+    pub mint: Account<'info, Mint>,
+
+    /// Position owner; pays for the extra rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -2,6 +2,26 @@
 
 pub mod update_position;
 pub mod record_trade;
+pub mod initialize_portfolio;
+pub mod set_portfolio_limits;
+pub mod open_position;
+pub mod close_position;
+pub mod grant_delegate;
+pub mod revoke_delegate;
+pub mod migrate_position;
+pub mod place_trigger_order;
+pub mod execute_trigger_order;
+pub mod cancel_trigger_order;
 
 pub use update_position::*;
 pub use record_trade::*;
+pub use initialize_portfolio::*;
+pub use set_portfolio_limits::*;
+pub use open_position::*;
+pub use close_position::*;
+pub use grant_delegate::*;
+pub use revoke_delegate::*;
+pub use migrate_position::*;
+pub use place_trigger_order::*;
+pub use execute_trigger_order::*;
+pub use cancel_trigger_order::*;
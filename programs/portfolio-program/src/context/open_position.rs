@@ -0,0 +1,25 @@
+use crate::state::{Portfolio, Position};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(init, payer = owner, space = Position::LEN)]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [Portfolio::SEED, owner.key().as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+
+    /// Token the position tracks
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
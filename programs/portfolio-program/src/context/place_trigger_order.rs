@@ -0,0 +1,45 @@
+use crate::state::TriggerOrder;
+use amm_pool::pool_state::PoolState;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceTriggerOrder<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = TriggerOrder::LEN,
+        seeds = [TriggerOrder::SEED, owner.key().as_ref(), &order_id.to_le_bytes()],
+        bump
+    )]
+    pub order: Account<'info, TriggerOrder>,
+
+    /// Holds the order size until execution or cancellation
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TriggerOrder::ESCROW_SEED, order.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = order
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(constraint = pool_state.token_a == mint.key())]
+    pub pool_state: Account<'info, PoolState>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = mint, token::authority = owner)]
+    pub owner_source: Account<'info, TokenAccount>,
+
+    #[account(token::mint = pool_state.token_b)]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
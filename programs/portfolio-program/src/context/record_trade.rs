@@ -1,11 +1,33 @@
 // record_trade.rs
 use anchor_lang::prelude::*;
-use crate::state::Position;
+use amm_pool::pool_state::PoolState;
+use crate::processor::ErrorCode;
+use crate::state::{Delegation, Portfolio, Position};
 
 #[derive(Accounts)]
 pub struct RecordTrade<'info> {
     #[account(mut)]
     pub position: Account<'info, Position>,
+    #[account(
+        seeds = [Portfolio::SEED, position.owner.as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+    /// The portfolio's pricing pool, whose TWAP values the trade; required while
+    /// `max_trade_notional` is set, and must trade the position's mint
+    #[account(
+        address = portfolio.limits.pricing_pool @ ErrorCode::PricingPoolMismatch,
+        constraint = pool_state.token_a == position.mint
+            || pool_state.token_b == position.mint @ ErrorCode::PoolMintMismatch
+    )]
+    pub pool_state: Option<Account<'info, PoolState>>,
+    /// Required when `authority` is a delegate rather than the owner
+    #[account(
+        seeds = [Delegation::SEED, position.owner.as_ref(), authority.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Option<Account<'info, Delegation>>,
+    /// Position owner or a delegate with record-trade permission
     #[account(mut)]
     pub authority: Signer<'info>,
-}
\ No newline at end of file
+}
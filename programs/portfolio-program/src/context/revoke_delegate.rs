@@ -0,0 +1,17 @@
+use crate::state::Delegation;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        close = owner,
+        seeds = [Delegation::SEED, owner.key().as_ref(), delegation.delegate.as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
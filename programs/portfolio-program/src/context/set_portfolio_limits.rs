@@ -0,0 +1,14 @@
+use crate::state::Portfolio;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPortfolioLimits<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [Portfolio::SEED, owner.key().as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+    pub owner: Signer<'info>,
+}
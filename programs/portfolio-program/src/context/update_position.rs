@@ -1,9 +1,22 @@
-use crate::state::Position;
+use crate::state::{Delegation, Portfolio, Position};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct UpdatePosition<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(mut)]
     pub position: Account<'info, Position>,
-    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Portfolio::SEED, position.owner.as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+    /// Required when `authority` is a delegate rather than the owner
+    #[account(
+        seeds = [Delegation::SEED, position.owner.as_ref(), authority.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Option<Account<'info, Delegation>>,
+    /// Position owner or a delegate with update-PnL permission
+    pub authority: Signer<'info>,
 }
@@ -6,19 +6,89 @@ pub mod state;
 
 
 use context::*;
+use state::{RiskLimits, TriggerKind};
 
-// Use the same placeholder - Anchor will generate unique IDs for each program
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+// Must match `portfolio_program` in Anchor.toml and PORTFOLIO_PROGRAM_ID in the backend
+declare_id!("4xJGvDE2b5k9qCfSBXrT1a7HYPRxh2AvKHiQA9hm2ryS");
 
 #[program]
 pub mod portfolio_program {
     use super::*;
 
+    pub fn initialize_portfolio(
+        ctx: Context<InitializePortfolio>,
+        limits: RiskLimits,
+    ) -> Result<()> {
+        processor::initialize_portfolio(ctx, limits)
+    }
+
+    pub fn set_portfolio_limits(ctx: Context<SetPortfolioLimits>, limits: RiskLimits) -> Result<()> {
+        processor::set_portfolio_limits(ctx, limits)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        processor::open_position(ctx)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        processor::close_position(ctx)
+    }
+
+    pub fn grant_delegate(
+        ctx: Context<GrantDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+        expiry_slot: Option<u64>,
+    ) -> Result<()> {
+        processor::grant_delegate(ctx, delegate, permissions, expiry_slot)
+    }
+
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        processor::revoke_delegate(ctx)
+    }
+
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        processor::migrate_position(ctx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_trigger_order(
+        ctx: Context<PlaceTriggerOrder>,
+        order_id: u64,
+        kind: TriggerKind,
+        trigger_price: u64,
+        size: u64,
+        min_out: u64,
+        bounty_lamports: u64,
+    ) -> Result<()> {
+        processor::place_trigger_order(
+            ctx,
+            order_id,
+            kind,
+            trigger_price,
+            size,
+            min_out,
+            bounty_lamports,
+        )
+    }
+
+    pub fn execute_trigger_order(ctx: Context<ExecuteTriggerOrder>) -> Result<()> {
+        processor::execute_trigger_order(ctx)
+    }
+
+    pub fn cancel_trigger_order(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+        processor::cancel_trigger_order(ctx)
+    }
+
     pub fn update_position(ctx: Context<UpdatePosition>, pnl: i64) -> Result<()> {
         processor::update_position(ctx, pnl)
     }
 
-    pub fn record_trade(ctx: Context<RecordTrade>, trade_id: u64) -> Result<()> {
-        processor::record_trade(ctx, trade_id)
+    pub fn record_trade(
+        ctx: Context<RecordTrade>,
+        trade_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        processor::record_trade(ctx, trade_id, amount)
     }
 }
@@ -1,7 +1,396 @@
-use crate::state::Position;
+use crate::state::{Delegation, Position, RiskLimits, TriggerKind, TriggerOrder};
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount, Transfer};
+
+pub fn initialize_portfolio(
+    ctx: Context<crate::context::InitializePortfolio>,
+    limits: RiskLimits,
+) -> Result<()> {
+    require_pricing_pool(&limits)?;
+
+    let portfolio = &mut ctx.accounts.portfolio;
+    portfolio.owner = ctx.accounts.owner.key();
+    portfolio.total_realized_pnl = 0;
+    portfolio.peak_pnl = 0;
+    portfolio.position_count = 0;
+    portfolio.limits = limits;
+    portfolio.bump = ctx.bumps.portfolio;
+
+    Ok(())
+}
+
+pub fn set_portfolio_limits(
+    ctx: Context<crate::context::SetPortfolioLimits>,
+    limits: RiskLimits,
+) -> Result<()> {
+    require_pricing_pool(&limits)?;
+    ctx.accounts.portfolio.limits = limits;
+    Ok(())
+}
+
+// A notional limit is meaningless without a pool to price trades in
+fn require_pricing_pool(limits: &RiskLimits) -> Result<()> {
+    require!(
+        !limits.prices_trades() || limits.pricing_pool != Pubkey::default(),
+        ErrorCode::PricingPoolRequired
+    );
+    Ok(())
+}
+
+pub fn open_position(ctx: Context<crate::context::OpenPosition>) -> Result<()> {
+    let portfolio = &mut ctx.accounts.portfolio;
+
+    // Enforce the max position count before adding another one
+    let position_count = portfolio
+        .position_count
+        .checked_add(1)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+    require!(
+        !portfolio.limits.position_count_exceeded(position_count),
+        ErrorCode::PositionLimitExceeded
+    );
+    portfolio.position_count = position_count;
+
+    let pos = &mut ctx.accounts.position;
+    pos.version = Position::VERSION;
+    pos.owner = ctx.accounts.owner.key();
+    pos.pnl = 0;
+    pos.trade_count = 0;
+    pos.mint = ctx.accounts.mint.key();
+    pos.reserved = [0; Position::RESERVED];
+
+    emit!(PositionOpened {
+        owner: pos.owner,
+        position: pos.key(),
+        mint: pos.mint,
+        slot: Clock::get()?.slot,
+    });
+
+    Ok(())
+}
+
+pub fn close_position(ctx: Context<crate::context::ClosePosition>) -> Result<()> {
+    // Positions opened before their owner's Portfolio existed were never counted
+    let portfolio = &mut ctx.accounts.portfolio;
+    portfolio.position_count = portfolio.position_count.saturating_sub(1);
+
+    // The position account itself is closed by the `close = owner` constraint
+    let pos = &ctx.accounts.position;
+    emit!(PositionClosed {
+        owner: pos.owner,
+        position: pos.key(),
+        mint: pos.mint,
+        final_pnl: pos.pnl,
+        trade_count: pos.trade_count,
+        slot: Clock::get()?.slot,
+    });
+
+    Ok(())
+}
+
+pub fn grant_delegate(
+    ctx: Context<crate::context::GrantDelegate>,
+    delegate: Pubkey,
+    permissions: u8,
+    expiry_slot: Option<u64>,
+) -> Result<()> {
+    require!(
+        permissions != 0 && permissions & !Delegation::ALL_PERMISSIONS == 0,
+        ErrorCode::InvalidPermissions
+    );
+    if let Some(expiry) = expiry_slot {
+        require!(expiry > Clock::get()?.slot, ErrorCode::DelegationExpired);
+    }
+
+    let delegation = &mut ctx.accounts.delegation;
+    delegation.owner = ctx.accounts.owner.key();
+    delegation.delegate = delegate;
+    delegation.permissions = permissions;
+    delegation.expiry_slot = expiry_slot;
+    delegation.bump = ctx.bumps.delegation;
+
+    Ok(())
+}
+
+pub fn revoke_delegate(_ctx: Context<crate::context::RevokeDelegate>) -> Result<()> {
+    // The delegation account is closed by the `close = owner` constraint
+    Ok(())
+}
+
+pub fn migrate_position(ctx: Context<crate::context::MigratePosition>) -> Result<()> {
+    let info = ctx.accounts.position.to_account_info();
+
+    let mut position = Position::from_legacy(&info.try_borrow_data()?)?;
+    require_keys_eq!(
+        position.owner,
+        ctx.accounts.owner.key(),
+        ErrorCode::Unauthorized
+    );
+    position.version = Position::VERSION;
+    position.mint = ctx.accounts.mint.key();
+
+    // Top up rent if the layout grew, then resize the account in place
+    let required = Rent::get()?.minimum_balance(Position::LEN);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(Position::LEN)?;
+
+    let mut data = info.try_borrow_mut_data()?;
+    position.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn place_trigger_order(
+    ctx: Context<crate::context::PlaceTriggerOrder>,
+    order_id: u64,
+    kind: TriggerKind,
+    trigger_price: u64,
+    size: u64,
+    min_out: u64,
+    bounty_lamports: u64,
+) -> Result<()> {
+    require!(size > 0 && trigger_price > 0, ErrorCode::InvalidOrder);
+    // A zero minimum would turn the trigger into an unprotected market swap
+    require!(min_out > 0, ErrorCode::MissingMinOut);
+
+    // Escrow the order size under the order PDA
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_source.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        size,
+    )?;
+
+    // Fund the keeper bounty on top of the order account's rent
+    if bounty_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.order.to_account_info(),
+                },
+            ),
+            bounty_lamports,
+        )?;
+    }
+
+    let order = &mut ctx.accounts.order;
+    order.version = TriggerOrder::VERSION;
+    order.owner = ctx.accounts.owner.key();
+    order.order_id = order_id;
+    order.pool = ctx.accounts.pool_state.key();
+    order.mint = ctx.accounts.mint.key();
+    order.destination = ctx.accounts.destination.key();
+    order.kind = kind;
+    order.trigger_price = trigger_price;
+    order.size = size;
+    order.min_out = min_out;
+    order.bounty_lamports = bounty_lamports;
+    order.bump = ctx.bumps.order;
+    order.escrow_bump = ctx.bumps.escrow;
+    order.reserved = [0; TriggerOrder::RESERVED];
+
+    emit!(TriggerOrderPlaced {
+        owner: order.owner,
+        order: order.key(),
+        pool: order.pool,
+        kind,
+        trigger_price,
+        size,
+        bounty_lamports,
+    });
+
+    Ok(())
+}
+
+pub fn execute_trigger_order(ctx: Context<crate::context::ExecuteTriggerOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+
+    // Only fire once the pool's TWAP has crossed the trigger price
+    let now = Clock::get()?.unix_timestamp;
+    let twap = ctx
+        .accounts
+        .pool_state
+        .twap
+        .twap(now)
+        .ok_or(error!(ErrorCode::TwapUnavailable))?;
+    require!(
+        order.kind.is_triggered(twap, order.trigger_price),
+        ErrorCode::TriggerNotReached
+    );
+
+    let owner = order.owner;
+    let order_id = order.order_id.to_le_bytes();
+    let bump = [order.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[TriggerOrder::SEED, owner.as_ref(), &order_id, &bump]];
+    let (kind, size, min_out, bounty) = (order.kind, order.size, order.min_out, order.bounty_lamports);
+    let (input_mint, output_mint) = (order.mint, ctx.accounts.destination.mint);
+    let balance_before = ctx.accounts.destination.amount;
+
+    // Sell the escrowed tokens through the pool, with the order PDA as swapper
+    amm_pool::cpi::execute_swap(
+        CpiContext::new_with_signer(
+            ctx.accounts.amm_program.to_account_info(),
+            amm_pool::cpi::accounts::ExecuteSwap {
+                pool_state: ctx.accounts.pool_state.to_account_info(),
+                pool_authority: ctx.accounts.pool_authority.to_account_info(),
+                user_source: ctx.accounts.escrow.to_account_info(),
+                user_destination: ctx.accounts.destination.to_account_info(),
+                pool_vault_a: ctx.accounts.pool_vault_a.to_account_info(),
+                pool_vault_b: ctx.accounts.pool_vault_b.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                authority: ctx.accounts.order.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        size,
+        min_out,
+    )?;
+
+    // What the swap actually delivered, for the event
+    ctx.accounts.destination.reload()?;
+    let amount_out = ctx
+        .accounts
+        .destination
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+
+    // Return the emptied escrow's rent to the owner
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.order.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    // Pay the keeper; `close = owner` returns the rest of the order's lamports
+    let order_info = ctx.accounts.order.to_account_info();
+    let keeper_info = ctx.accounts.keeper.to_account_info();
+    let remaining = order_info
+        .lamports()
+        .checked_sub(bounty)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+    **order_info.try_borrow_mut_lamports()? = remaining;
+    **keeper_info.try_borrow_mut_lamports()? = keeper_info
+        .lamports()
+        .checked_add(bounty)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+
+    emit!(TriggerOrderExecuted {
+        owner,
+        order: order_info.key(),
+        keeper: keeper_info.key(),
+        kind,
+        twap,
+        size,
+        input_mint,
+        output_mint,
+        amount_out,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_trigger_order(ctx: Context<crate::context::CancelTriggerOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let owner = order.owner;
+    let order_id = order.order_id.to_le_bytes();
+    let bump = [order.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[TriggerOrder::SEED, owner.as_ref(), &order_id, &bump]];
+
+    // Return the escrowed tokens, then close the escrow
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.owner_destination.to_account_info(),
+                authority: ctx.accounts.order.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        ctx.accounts.escrow.amount,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.order.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    Ok(())
+}
+
+// Allow the position owner, or a delegate holding `permission` that has not expired
+fn authorize(
+    owner: &Pubkey,
+    authority: &Pubkey,
+    delegation: Option<&Account<Delegation>>,
+    permission: u8,
+) -> Result<()> {
+    if authority == owner {
+        return Ok(());
+    }
+
+    let delegation = delegation.ok_or(error!(ErrorCode::Unauthorized))?;
+    let slot = Clock::get()?.slot;
+    require!(!delegation.is_expired(slot), ErrorCode::DelegationExpired);
+    require!(
+        delegation.allows(owner, authority, permission, slot),
+        ErrorCode::Unauthorized
+    );
+
+    Ok(())
+}
 
 pub fn update_position(ctx: Context<crate::context::UpdatePosition>, pnl_delta: i64) -> Result<()> {
+    authorize(
+        &ctx.accounts.position.owner,
+        ctx.accounts.authority.key,
+        ctx.accounts.delegation.as_ref(),
+        Delegation::PERMISSION_UPDATE_PNL,
+    )?;
+
+    let portfolio = &mut ctx.accounts.portfolio;
+
+    // Enforce the drawdown limit on the portfolio-wide realized PnL
+    let (total, peak, drawdown) = portfolio
+        .drawdown_after(pnl_delta)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+    require!(
+        !portfolio.limits.drawdown_exceeded(drawdown),
+        ErrorCode::DrawdownLimitExceeded
+    );
+    portfolio.total_realized_pnl = total;
+    portfolio.peak_pnl = peak;
+
     let pos = &mut ctx.accounts.position;
 
     // Update PnL safely
@@ -10,10 +399,56 @@ pub fn update_position(ctx: Context<crate::context::UpdatePosition>, pnl_delta:
         .checked_add(pnl_delta)
         .ok_or(error!(ErrorCode::MathOverflow))?;
 
+    emit!(PnlUpdated {
+        owner: pos.owner,
+        position: pos.key(),
+        mint: pos.mint,
+        pnl_delta,
+        new_pnl: pos.pnl,
+        portfolio_pnl: total,
+        slot: Clock::get()?.slot,
+    });
+
     Ok(())
 }
 
-pub fn record_trade(ctx: Context<crate::context::RecordTrade>, trade_id: u64) -> Result<()> {
+pub fn record_trade(
+    ctx: Context<crate::context::RecordTrade>,
+    trade_id: u64,
+    amount: u64,
+) -> Result<()> {
+    authorize(
+        &ctx.accounts.position.owner,
+        ctx.accounts.authority.key,
+        ctx.accounts.delegation.as_ref(),
+        Delegation::PERMISSION_RECORD_TRADE,
+    )?;
+
+    // Enforce the single-trade notional limit, valuing the trade at the pricing
+    // pool's TWAP rather than trusting the caller. Unpriced while the limit is off.
+    let limits = ctx.accounts.portfolio.limits;
+    let notional = if limits.prices_trades() {
+        let pool = ctx
+            .accounts
+            .pool_state
+            .as_ref()
+            .ok_or(error!(ErrorCode::PricingPoolRequired))?;
+        let twap = pool
+            .twap
+            .twap(Clock::get()?.unix_timestamp)
+            .ok_or(error!(ErrorCode::TwapUnavailable))?;
+        let is_token_a = pool.token_a == ctx.accounts.position.mint;
+        let notional = RiskLimits::notional(amount, twap, is_token_a)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        require!(
+            !limits.trade_notional_exceeded(notional),
+            ErrorCode::TradeNotionalExceeded
+        );
+        notional
+    } else {
+        0
+    };
+
     let pos = &mut ctx.accounts.position;
 
     // Increment trade_count
@@ -24,18 +459,81 @@ pub fn record_trade(ctx: Context<crate::context::RecordTrade>, trade_id: u64) ->
 
     emit!(TradeRecorded {
         owner: pos.owner,
+        position: pos.key(),
+        mint: pos.mint,
         trade_id,
         trade_number: pos.trade_count,
+        amount,
+        notional,
+        slot: Clock::get()?.slot,
     });
 
     Ok(())
 }
 
+#[event]
+pub struct PositionOpened {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub mint: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PositionClosed {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub mint: Pubkey,
+    pub final_pnl: i64,
+    pub trade_count: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PnlUpdated {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub mint: Pubkey,
+    pub pnl_delta: i64,
+    pub new_pnl: i64,       // Position total after the update
+    pub portfolio_pnl: i64, // Portfolio-wide realized PnL after the update
+    pub slot: u64,
+}
+
 #[event]
 pub struct TradeRecorded {
     pub owner: Pubkey,
+    pub position: Pubkey,
+    pub mint: Pubkey,
     pub trade_id: u64,
     pub trade_number: u64,
+    pub amount: u64,   // Token amount traded, in mint base units
+    pub notional: u64, // Pricing pool TWAP value checked against max_trade_notional; 0 while that is off
+    pub slot: u64,
+}
+
+#[event]
+pub struct TriggerOrderPlaced {
+    pub owner: Pubkey,
+    pub order: Pubkey,
+    pub pool: Pubkey,
+    pub kind: TriggerKind,
+    pub trigger_price: u64,
+    pub size: u64,
+    pub bounty_lamports: u64,
+}
+
+#[event]
+pub struct TriggerOrderExecuted {
+    pub owner: Pubkey,
+    pub order: Pubkey,
+    pub keeper: Pubkey,
+    pub kind: TriggerKind,
+    pub twap: u64,
+    pub size: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_out: u64,
 }
 
 #[error_code]
@@ -44,4 +542,32 @@ pub enum ErrorCode {
     MathOverflow,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Opening this position would exceed the portfolio's max position count")]
+    PositionLimitExceeded,
+    #[msg("This PnL update would exceed the portfolio's max drawdown")]
+    DrawdownLimitExceeded,
+    #[msg("Trade notional exceeds the portfolio's max single-trade notional")]
+    TradeNotionalExceeded,
+    #[msg("Delegation has expired")]
+    DelegationExpired,
+    #[msg("Invalid delegate permissions")]
+    InvalidPermissions,
+    #[msg("Account data does not match any known layout")]
+    InvalidAccountData,
+    #[msg("Account is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Trigger orders need a non-zero size and trigger price")]
+    InvalidOrder,
+    #[msg("Pool TWAP is not available yet")]
+    TwapUnavailable,
+    #[msg("Pool TWAP has not reached the trigger price")]
+    TriggerNotReached,
+    #[msg("Trigger orders need a non-zero minimum output")]
+    MissingMinOut,
+    #[msg("Pool does not trade the position's mint")]
+    PoolMintMismatch,
+    #[msg("Pool is not the portfolio's pricing pool")]
+    PricingPoolMismatch,
+    #[msg("The portfolio's pricing pool is required while max_trade_notional is set")]
+    PricingPoolRequired,
 }
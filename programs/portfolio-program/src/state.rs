@@ -1,13 +1,304 @@
+use amm_pool::twap::PRICE_SCALE_BITS;
 use anchor_lang::prelude::*;
 
+use crate::processor::ErrorCode;
+
 #[account]
 pub struct Position {
+    pub version: u8,
     pub owner: Pubkey,
     pub pnl: i64,
     pub trade_count: u64,
+    pub mint: Pubkey,       // Added in v2; default for v1 accounts until migrated
+    pub reserved: [u8; 32], // RESERVED; shrink when adding fields, then bump VERSION
 }
 
 impl Position {
+    pub const VERSION: u8 = 2;
+    pub const RESERVED: usize = 32;
+
+    // discriminator (8) + version (1) + owner (32) + pnl (8) + trade_count (8) + mint (32) + reserved
+    pub const LEN: usize = 8 + 1 + 32 + 8 + 8 + 32 + Self::RESERVED;
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    /// Read an account still in a legacy layout, discriminator included. The
+    /// version and mint are left for `migrate_position` to set.
+    pub fn from_legacy(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::InvalidAccountData
+        );
+
+        // The unversioned layout is identified by its length; v1 shares the current
+        // size and only lacks the mint, which now sits in its former reserved bytes
+        if data.len() == PositionV0::LEN {
+            let legacy = PositionV0::deserialize(&mut &data[8..])
+                .map_err(|_| error!(ErrorCode::InvalidAccountData))?;
+            return Ok(legacy.into());
+        }
+        let position = Self::try_deserialize(&mut &data[..])
+            .map_err(|_| error!(ErrorCode::InvalidAccountData))?;
+        require!(!position.is_current(), ErrorCode::AlreadyMigrated);
+        Ok(position)
+    }
+}
+
+/// Unversioned `Position` layout written before the version byte existed.
+/// Only read by `migrate_position`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PositionV0 {
+    pub owner: Pubkey,
+    pub pnl: i64,
+    pub trade_count: u64,
+}
+
+impl PositionV0 {
     // discriminator (8) + owner (32) + pnl (8) + trade_count (8)
     pub const LEN: usize = 8 + 32 + 8 + 8;
 }
+
+impl From<PositionV0> for Position {
+    fn from(legacy: PositionV0) -> Self {
+        Self {
+            version: Self::VERSION,
+            owner: legacy.owner,
+            pnl: legacy.pnl,
+            trade_count: legacy.trade_count,
+            mint: Pubkey::default(),
+            reserved: [0; Self::RESERVED],
+        }
+    }
+}
+
+/// Per-owner aggregate of all positions, plus the risk limits enforced on them.
+#[account]
+pub struct Portfolio {
+    pub owner: Pubkey,
+    pub total_realized_pnl: i64,
+    pub peak_pnl: i64, // Highest total_realized_pnl seen, used for drawdown
+    pub position_count: u64,
+    pub limits: RiskLimits,
+    pub bump: u8,
+}
+
+impl Portfolio {
+    pub const SEED: &'static [u8] = b"portfolio";
+
+    // discriminator (8) + owner (32) + 2 i64s (8*2) + position_count (8) + limits + bump (1)
+    pub const LEN: usize = 8 + 32 + (8 * 2) + 8 + RiskLimits::LEN + 1;
+
+    /// Drawdown from peak that applying `pnl_delta` would produce.
+    pub fn drawdown_after(&self, pnl_delta: i64) -> Option<(i64, i64, u64)> {
+        let total = self.total_realized_pnl.checked_add(pnl_delta)?;
+        let peak = self.peak_pnl.max(total);
+        let drawdown = (peak as i128 - total as i128) as u64;
+        Some((total, peak, drawdown))
+    }
+}
+
+/// Risk limits for a portfolio. A limit of 0 disables that check.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct RiskLimits {
+    pub max_position_count: u64,
+    pub max_drawdown: u64,
+    pub max_trade_notional: u64, // In the pricing pool's other token
+    pub pricing_pool: Pubkey,    // amm-pool PoolState whose TWAP values trades
+}
+
+impl RiskLimits {
+    // 3 u64s (8*3) + pricing_pool (32)
+    pub const LEN: usize = (8 * 3) + 32;
+
+    /// Whether trades must be priced, i.e. the single-trade notional limit is set.
+    pub fn prices_trades(&self) -> bool {
+        self.max_trade_notional != 0
+    }
+
+    pub fn position_count_exceeded(&self, position_count: u64) -> bool {
+        self.max_position_count != 0 && position_count > self.max_position_count
+    }
+
+    pub fn drawdown_exceeded(&self, drawdown: u64) -> bool {
+        self.max_drawdown != 0 && drawdown > self.max_drawdown
+    }
+
+    pub fn trade_notional_exceeded(&self, notional: u64) -> bool {
+        self.prices_trades() && notional > self.max_trade_notional
+    }
+
+    /// Value of `amount` in the pool's other token, given its Q32.32 TWAP of
+    /// token B per token A. `is_token_a` says which side `amount` is in.
+    pub fn notional(amount: u64, twap: u64, is_token_a: bool) -> Option<u64> {
+        let value = if is_token_a {
+            (amount as u128 * twap as u128) >> PRICE_SCALE_BITS
+        } else {
+            ((amount as u128) << PRICE_SCALE_BITS).checked_div(twap as u128)?
+        };
+        Some(value.min(u64::MAX as u128) as u64)
+    }
+}
+
+/// Grants `delegate` scoped authority over `owner`'s positions.
+#[account]
+pub struct Delegation {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,
+    pub expiry_slot: Option<u64>, // None = never expires
+    pub bump: u8,
+}
+
+impl Delegation {
+    pub const SEED: &'static [u8] = b"delegation";
+
+    pub const PERMISSION_RECORD_TRADE: u8 = 1 << 0;
+    pub const PERMISSION_UPDATE_PNL: u8 = 1 << 1;
+    pub const ALL_PERMISSIONS: u8 = Self::PERMISSION_RECORD_TRADE | Self::PERMISSION_UPDATE_PNL;
+
+    // discriminator (8) + 2 pubkeys (32*2) + permissions (1) + Option<u64> (1+8) + bump (1)
+    pub const LEN: usize = 8 + (32 * 2) + 1 + (1 + 8) + 1;
+
+    /// Whether this delegation lets `delegate` act on `owner`'s behalf with
+    /// `permission` at `slot`.
+    pub fn allows(&self, owner: &Pubkey, delegate: &Pubkey, permission: u8, slot: u64) -> bool {
+        self.owner == *owner
+            && self.delegate == *delegate
+            && self.permissions & permission == permission
+            && !self.is_expired(slot)
+    }
+
+    pub fn is_expired(&self, slot: u64) -> bool {
+        self.expiry_slot.is_some_and(|expiry| slot >= expiry)
+    }
+}
+
+/// Which side of the trigger price fires the order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerKind {
+    StopLoss,   // Fires when the TWAP falls to or below the trigger price
+    TakeProfit, // Fires when the TWAP rises to or above the trigger price
+}
+
+impl TriggerKind {
+    pub fn is_triggered(&self, twap: u64, trigger_price: u64) -> bool {
+        match self {
+            TriggerKind::StopLoss => twap <= trigger_price,
+            TriggerKind::TakeProfit => twap >= trigger_price,
+        }
+    }
+}
+
+/// Escrowed sell order on an amm-pool pool, executable by any keeper once the
+/// pool's TWAP crosses `trigger_price`.
+#[account]
+pub struct TriggerOrder {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub pool: Pubkey,        // amm-pool PoolState supplying the TWAP
+    pub mint: Pubkey,        // Token sold; the pool's token A
+    pub destination: Pubkey, // Token account receiving the pool's token B
+    pub kind: TriggerKind,
+    pub trigger_price: u64, // Q32.32 token B per token A, same scale as the pool TWAP
+    pub size: u64,
+    pub min_out: u64,
+    pub bounty_lamports: u64, // Paid to the keeper that executes the order
+    pub bump: u8,
+    pub escrow_bump: u8,
+    pub reserved: [u8; 32],
+}
+
+impl TriggerOrder {
+    pub const SEED: &'static [u8] = b"trigger_order";
+    pub const ESCROW_SEED: &'static [u8] = b"trigger_escrow";
+
+    pub const VERSION: u8 = 1;
+    pub const RESERVED: usize = 32;
+
+    // discriminator (8) + version (1) + 4 pubkeys (32*4) + order_id (8) + kind (1)
+    // + 4 u64s (8*4) + 2 bumps (1*2) + reserved
+    pub const LEN: usize = 8 + 1 + (32 * 4) + 8 + 1 + (8 * 4) + 2 + Self::RESERVED;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(version: u8, owner: Pubkey) -> Position {
+        Position {
+            version,
+            owner,
+            pnl: -42,
+            trade_count: 7,
+            mint: Pubkey::default(),
+            reserved: [0; Position::RESERVED],
+        }
+    }
+
+    fn account(position: &Position) -> Vec<u8> {
+        let mut data = Vec::new();
+        position.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn v0_is_recognised_by_length() {
+        let owner = Pubkey::new_unique();
+        let mut data = Position::DISCRIMINATOR.to_vec();
+        PositionV0 {
+            owner,
+            pnl: -42,
+            trade_count: 7,
+        }
+        .serialize(&mut data)
+        .unwrap();
+        assert_eq!(data.len(), PositionV0::LEN);
+
+        let migrated = Position::from_legacy(&data).unwrap();
+        assert_eq!(migrated.owner, owner);
+        assert_eq!((migrated.pnl, migrated.trade_count), (-42, 7));
+        assert_eq!(migrated.mint, Pubkey::default());
+    }
+
+    #[test]
+    fn v1_shares_the_current_size() {
+        let owner = Pubkey::new_unique();
+        let data = account(&position(1, owner));
+        assert_eq!(data.len(), Position::LEN);
+
+        let migrated = Position::from_legacy(&data).unwrap();
+        assert_eq!(migrated.version, 1);
+        assert_eq!(migrated.owner, owner);
+        assert_eq!((migrated.pnl, migrated.trade_count), (-42, 7));
+    }
+
+    #[test]
+    fn current_and_foreign_accounts_are_refused() {
+        let mut data = account(&position(Position::VERSION, Pubkey::new_unique()));
+        assert_eq!(
+            Position::from_legacy(&data).err(),
+            Some(ErrorCode::AlreadyMigrated.into())
+        );
+
+        data[0] ^= 0xff;
+        assert_eq!(
+            Position::from_legacy(&data).err(),
+            Some(ErrorCode::InvalidAccountData.into())
+        );
+    }
+
+    #[test]
+    fn trades_are_priced_only_while_the_notional_limit_is_set() {
+        let mut limits = RiskLimits::default();
+        assert!(!limits.prices_trades());
+        assert!(!limits.trade_notional_exceeded(u64::MAX));
+
+        limits.max_trade_notional = 1_000;
+        assert!(limits.prices_trades());
+        assert!(!limits.trade_notional_exceeded(1_000));
+        assert!(limits.trade_notional_exceeded(1_001));
+    }
+}